@@ -17,32 +17,23 @@ fn bench_hpke(c: &mut Criterion) {
     c.bench_function("hpke_base_accept", |ben| {
         let (enc, _) = hpke_initiate(&b.x25519.public()).unwrap();
         ben.iter(|| {
-            let _ = hpke_accept(
-                black_box(&b.x25519.sk.clone().try_into().unwrap()),
-                black_box(&enc),
-            )
-            .unwrap();
+            let _ = hpke_accept(black_box(&b.x25519.sk), black_box(&enc)).unwrap();
         })
     });
 
     c.bench_function("hpke_auth_initiate", |ben| {
         ben.iter(|| {
-            let _ = hpke_initiate_auth(
-                black_box(&a.x25519.sk.clone().try_into().unwrap()),
-                black_box(&b.x25519.public()),
-            )
-            .unwrap();
+            let _ =
+                hpke_initiate_auth(black_box(&a.x25519.sk), black_box(&b.x25519.public())).unwrap();
         })
     });
 
     c.bench_function("hpke_auth_accept", |ben| {
-        let (enc, _) =
-            hpke_initiate_auth(&a.x25519.sk.clone().try_into().unwrap(), &b.x25519.public())
-                .unwrap();
+        let (enc, _) = hpke_initiate_auth(&a.x25519.sk, &b.x25519.public()).unwrap();
         ben.iter(|| {
             let _ = hpke_accept_auth(
                 black_box(&a.x25519.public()),
-                black_box(&b.x25519.sk.clone().try_into().unwrap()),
+                black_box(&b.x25519.sk),
                 black_box(&enc),
             )
             .unwrap();
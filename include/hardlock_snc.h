@@ -160,6 +160,32 @@ int hardlock_token_verify(const uint8_t *k_s32,
                           uint64_t now_unix_s);
 
 /**
+ * Bourre un clair avant chiffrement (`envelope::pad_plaintext`).
+ *
+ * # Safety
+ * `pt_ptr..pt_ptr+pt_len` lisibles, `out_ptr` capacité `out_cap`.
+ * `profile` : 0=Stealth,1=Balanced,2=Throughput,3=Padme. Retourne taille écrite.
+ */
+int hardlock_pad_plaintext(const uint8_t *pt_ptr,
+                           size_t pt_len,
+                           int32_t profile,
+                           uint8_t *out_ptr,
+                           size_t out_cap);
+
+/**
+ * Longueur du clair sans bourrage (`envelope::unpad_plaintext`); le clair
+ * est le préfixe `buf_ptr[..retour]`.
+ *
+ * # Safety
+ * `buf_ptr..buf_ptr+buf_len` lisibles.
+ */
+int hardlock_unpad_plaintext(const uint8_t *buf_ptr, size_t buf_len);
+
+/**
+ * Ancienne API : complète une trame v1.1 de zéros jusqu'au palier du profil
+ * (bourrage externe, ignoré à la réception). Conservée pour compatibilité
+ * ABI; préférer `hardlock_pad_plaintext` avant chiffrement.
+ *
  * # Safety
  * `frame_ptr..frame_ptr+frame_len` lisibles, `out_ptr` capacité `out_cap`.
  * `profile` : 0=Stealth,1=Balanced,2=Throughput. Retourne taille écrite.
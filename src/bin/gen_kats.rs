@@ -1,4 +1,8 @@
-use hardlock_snc::crypto::hpke_hybrid::{hpke_accept, hpke_initiate_deterministic};
+use hardlock_snc::crypto::hpke_hybrid::{
+    hpke_accept, hpke_accept_auth_psk, hpke_accept_psk, hpke_initiate_auth_psk_deterministic,
+    hpke_initiate_deterministic, hpke_initiate_psk_deterministic,
+};
+use hardlock_snc::crypto::keys::X25519KeyPair;
 use hardlock_snc::identity::DeviceIdentity;
 use hardlock_snc::ratchet;
 use serde::Serialize;
@@ -10,6 +14,18 @@ struct HpkeKat {
     okm: String,
 }
 #[derive(Serialize)]
+struct HpkePskKat {
+    seed: String,
+    recipient_seed: String,
+    sender_seed: Option<String>,
+    psk: String,
+    psk_id: String,
+    enc: String,
+    okm: String,
+    tag: Option<String>,
+    binder: String,
+}
+#[derive(Serialize)]
 struct Msg {
     header: String,
     nonce: String,
@@ -33,11 +49,10 @@ fn hex(x: &[u8]) -> String {
 }
 
 fn main() -> anyhow::Result<()> {
-    let a = DeviceIdentity::generate("a".into(), "d1".into());
     let b = DeviceIdentity::generate("b".into(), "d2".into());
     let seed = [42u8; 32];
     let (enc, okm_a) = hpke_initiate_deterministic(&b.x25519.public(), seed)?;
-    let okm_b = hpke_accept(&b.x25519.sk.clone().try_into().unwrap(), &enc)?;
+    let okm_b = hpke_accept(&b.x25519.sk, &enc)?;
     assert_eq!(okm_a, okm_b);
     let hpke = HpkeKat {
         seed,
@@ -46,20 +61,51 @@ fn main() -> anyhow::Result<()> {
     };
     std::fs::write("KATS/hpke_base.json", serde_json::to_vec_pretty(&hpke)?)?;
 
-    let mut ra = ratchet::init_initiator(
-        okm_a,
-        a.x25519.sk.clone().try_into().unwrap(),
-        b.x25519.public(),
-    );
-    let mut rb = ratchet::init_responder(
-        okm_b,
-        b.x25519.sk.clone().try_into().unwrap(),
-        a.x25519.public(),
-    );
+    let recipient_seed = [1u8; 32];
+    let sender_seed = [2u8; 32];
+    let psk = [3u8; 32];
+    let psk_id = b"tenant-1";
+    let rcp = X25519KeyPair::from_seed(recipient_seed);
+    let snd = X25519KeyPair::from_seed(sender_seed);
+    let rcp_sk = &rcp.sk;
+    let (enc, okm, binder) = hpke_initiate_psk_deterministic(&rcp.public(), &psk, psk_id, seed)?;
+    assert_eq!(hpke_accept_psk(rcp_sk, &psk, psk_id, &enc, &binder)?, okm);
+    let kat = HpkePskKat {
+        seed: hex(&seed),
+        recipient_seed: hex(&recipient_seed),
+        sender_seed: None,
+        psk: hex(&psk),
+        psk_id: hex(psk_id),
+        enc: hex(&enc),
+        okm: hex(&okm),
+        tag: None,
+        binder: hex(&binder),
+    };
+    std::fs::write("KATS/hpke_psk.json", serde_json::to_vec_pretty(&kat)?)?;
+    let (enc, okm, tag, binder) =
+        hpke_initiate_auth_psk_deterministic(&snd.sk, &rcp.public(), &psk, psk_id, seed)?;
+    let okm_r = hpke_accept_auth_psk(&snd.public(), rcp_sk, &psk, psk_id, &enc, &tag, &binder)?;
+    assert_eq!(okm_r, okm);
+    let kat = HpkePskKat {
+        seed: hex(&seed),
+        recipient_seed: hex(&recipient_seed),
+        sender_seed: Some(hex(&sender_seed)),
+        psk: hex(&psk),
+        psk_id: hex(psk_id),
+        enc: hex(&enc),
+        okm: hex(&okm),
+        tag: Some(hex(&tag)),
+        binder: hex(&binder),
+    };
+    std::fs::write("KATS/hpke_auth_psk.json", serde_json::to_vec_pretty(&kat)?)?;
+
+    let eph = X25519KeyPair::generate();
+    let mut ra = ratchet::init_initiator_ephemeral(okm_a, *eph.sk.expose());
+    let mut rb = ratchet::init_responder_ephemeral(okm_b, eph.public());
     let ad = b"kat/ad";
     let mut out = Vec::new();
     for i in 0..5u8 {
-        let (h, n, ct) = ratchet::encrypt(&mut ra, ad, &[i; 16]);
+        let (h, n, ct) = ratchet::encrypt(&mut ra, ad, &[i; 16])?;
         let _ = ratchet::decrypt(&mut rb, ad, &h, &n, &ct).unwrap();
         out.push(Msg {
             header: hex(&[&h.dh_pub[..], &h.pn.to_le_bytes(), &h.n.to_le_bytes()].concat()),
@@ -7,6 +7,8 @@ use rand::RngCore;
 pub const XNONCE_LEN: usize = 24;
 /// Taille clé AEAD.
 pub const KEY_LEN: usize = 32;
+/// Taille du tag Poly1305.
+pub const TAG_LEN: usize = 16;
 
 /// Chiffre un message avec XChaCha20-Poly1305.
 ///
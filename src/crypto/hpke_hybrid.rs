@@ -1,46 +1,97 @@
 #![allow(clippy::missing_errors_doc)]
 
-use crate::HL_INFO;
+use crate::crypto::secret::{SecretBytes, SecretKey32};
+use crate::error::ErrorCode;
+use crate::{suites, HL_INFO};
 use hkdf::Hkdf;
 use hpke::{
     aead::ChaCha20Poly1305, kdf::HkdfSha256, kem::X25519HkdfSha256, setup_receiver, setup_sender,
-    Deserializable, OpModeR, OpModeS, Serializable,
+    Deserializable, OpModeR, OpModeS, PskBundle, Serializable,
 };
+use kem::{Decapsulate, Encapsulate};
+use ml_kem::{Ciphertext, EncodedSizeUser, KemCore, MlKem768};
 use rand_chacha::ChaCha20Rng;
-use rand_core::SeedableRng;
-use sha2::Sha256;
+use rand_core::{CryptoRng, RngCore, SeedableRng};
+use sha2::{Digest, Sha256};
 use subtle::ConstantTimeEq;
+use thiserror::Error;
 use x25519_dalek::{PublicKey as X25519Public, StaticSecret};
 
+/// Erreurs de la couche HPKE (voir [`crate::error::ErrorCode`]).
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum HpkeError {
+    #[error("invalid key encoding")]
+    BadKey,
+    #[error("hpke: {0}")]
+    Hpke(String),
+    #[error("suite binder mismatch")]
+    BinderMismatch,
+    #[error("hpke auth tag mismatch")]
+    AuthMismatch,
+    #[error("ml-kem failure")]
+    Kem,
+}
+impl HpkeError {
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::BadKey => ErrorCode::BadKey,
+            Self::Hpke(_) => ErrorCode::HpkeFailure,
+            Self::BinderMismatch => ErrorCode::BinderMismatch,
+            Self::AuthMismatch => ErrorCode::AuthMismatch,
+            Self::Kem => ErrorCode::KemFailure,
+        }
+    }
+}
+impl From<hpke::HpkeError> for HpkeError {
+    fn from(e: hpke::HpkeError) -> Self {
+        Self::Hpke(format!("{e:?}"))
+    }
+}
+
+pub type HpkeResult<T> = Result<T, HpkeError>;
+
 const EXPORT_LABEL: &[u8] = b"hardlock/export";
 const AUTH_LABEL: &[u8] = b"hardlock/auth";
 const BINDER_LABEL: &[u8] = b"hardlock/suite-binder";
+const HYBRID_LABEL: &[u8] = b"hardlock/hybrid";
+const CONFIRM_LABEL: &[u8] = b"hardlock/confirm";
+const ID_SEAL_LABEL: &[u8] = b"hardlock/id-seal";
+
+/// `(enc, kem_ct, okm, binder)` produit par [`hpke_initiate_hybrid`].
+pub type HybridInit = (Vec<u8>, Vec<u8>, [u8; 32], [u8; 32]);
 
-pub fn hpke_initiate(pk_recipient_bytes: &[u8; 32]) -> anyhow::Result<(Vec<u8>, [u8; 32])> {
+/// `(enc, okm, binder, kc)` produit par [`hpke_initiate_confirm_ctx`].
+pub type ConfirmInit = (Vec<u8>, [u8; 32], [u8; 32], [u8; 32]);
+
+type MlKemEk = <MlKem768 as KemCore>::EncapsulationKey;
+type MlKemDk = <MlKem768 as KemCore>::DecapsulationKey;
+
+pub fn hpke_initiate(pk_recipient_bytes: &[u8; 32]) -> HpkeResult<(Vec<u8>, [u8; 32])> {
     let pk_recipient =
         <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_recipient_bytes)
-            .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+            .map_err(|_| HpkeError::BadKey)?;
     let (enc, sender_ctx) = setup_sender::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256, _>(
         &OpModeS::Base,
         &pk_recipient,
         HL_INFO.as_bytes(),
         &mut rand::rngs::OsRng,
     )
-    .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+    .map_err(HpkeError::from)?;
     let mut okm = [0u8; 32];
     sender_ctx
         .export(EXPORT_LABEL, &mut okm)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(HpkeError::from)?;
     Ok((enc.to_bytes().to_vec(), okm))
 }
 
 pub fn hpke_initiate_deterministic(
     pk_recipient_bytes: &[u8; 32],
     seed32: [u8; 32],
-) -> anyhow::Result<(Vec<u8>, [u8; 32])> {
+) -> HpkeResult<(Vec<u8>, [u8; 32])> {
     let pk_recipient =
         <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_recipient_bytes)
-            .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+            .map_err(|_| HpkeError::BadKey)?;
     let mut rng = ChaCha20Rng::from_seed(seed32);
     let (enc, sender_ctx) = setup_sender::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256, _>(
         &OpModeS::Base,
@@ -48,205 +99,774 @@ pub fn hpke_initiate_deterministic(
         HL_INFO.as_bytes(),
         &mut rng,
     )
-    .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+    .map_err(HpkeError::from)?;
     let mut okm = [0u8; 32];
     sender_ctx
         .export(EXPORT_LABEL, &mut okm)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(HpkeError::from)?;
     Ok((enc.to_bytes().to_vec(), okm))
 }
 
-pub fn hpke_accept(sk_recipient_bytes: &[u8; 32], enc_bytes: &[u8]) -> anyhow::Result<[u8; 32]> {
+pub fn hpke_accept(sk_recipient_bytes: &SecretKey32, enc_bytes: &[u8]) -> HpkeResult<[u8; 32]> {
     let sk_recipient =
-        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_recipient_bytes)
-            .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_recipient_bytes.expose())
+            .map_err(|_| HpkeError::BadKey)?;
     let enc = <X25519HkdfSha256 as hpke::kem::Kem>::EncappedKey::from_bytes(enc_bytes)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(|_| HpkeError::BadKey)?;
     let receiver_ctx = setup_receiver::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256>(
         &OpModeR::Base,
         &sk_recipient,
         &enc,
         HL_INFO.as_bytes(),
     )
-    .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+    .map_err(HpkeError::from)?;
     let mut okm = [0u8; 32];
     receiver_ctx
         .export(EXPORT_LABEL, &mut okm)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(HpkeError::from)?;
     Ok(okm)
 }
 
+/// Chiffrement HPKE Base à usage unique vers `pk_recipient` : renvoie `(enc, ct)`.
+///
+/// `info` doit être propre à l'usage (distinct de `HL_INFO`, réservé au handshake).
+pub fn hpke_seal_base(
+    pk_recipient_bytes: &[u8; 32],
+    info: &[u8],
+    pt: &[u8],
+    aad: &[u8],
+) -> HpkeResult<(Vec<u8>, Vec<u8>)> {
+    let pk_recipient =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_recipient_bytes)
+            .map_err(|_| HpkeError::BadKey)?;
+    let (enc, ct) = hpke::single_shot_seal::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256, _>(
+        &OpModeS::Base,
+        &pk_recipient,
+        info,
+        pt,
+        aad,
+        &mut rand::rngs::OsRng,
+    )
+    .map_err(HpkeError::from)?;
+    Ok((enc.to_bytes().to_vec(), ct))
+}
+
+/// Inverse de [`hpke_seal_base`].
+pub fn hpke_open_base(
+    sk_recipient_bytes: &SecretKey32,
+    enc_bytes: &[u8],
+    info: &[u8],
+    ct: &[u8],
+    aad: &[u8],
+) -> HpkeResult<Vec<u8>> {
+    let sk_recipient =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_recipient_bytes.expose())
+            .map_err(|_| HpkeError::BadKey)?;
+    let enc = <X25519HkdfSha256 as hpke::kem::Kem>::EncappedKey::from_bytes(enc_bytes)
+        .map_err(|_| HpkeError::BadKey)?;
+    hpke::single_shot_open::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256>(
+        &OpModeR::Base,
+        &sk_recipient,
+        &enc,
+        info,
+        ct,
+        aad,
+    )
+    .map_err(HpkeError::from)
+}
+
+/// Comme [`hpke_seal_base`], en mode Auth : le destinataire ne peut ouvrir
+/// qu'avec la clé publique de `sk_sender`, ce qui authentifie l'émetteur.
+pub fn hpke_seal_auth(
+    sk_sender_bytes: &SecretKey32,
+    pk_recipient_bytes: &[u8; 32],
+    info: &[u8],
+    pt: &[u8],
+    aad: &[u8],
+) -> HpkeResult<(Vec<u8>, Vec<u8>)> {
+    let sk_sender =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_sender_bytes.expose())
+            .map_err(|_| HpkeError::BadKey)?;
+    let dalek_sk = x25519_dalek::StaticSecret::from(*sk_sender_bytes.expose());
+    let pk_sender_bytes = x25519_dalek::PublicKey::from(&dalek_sk).to_bytes();
+    let pk_sender = <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(&pk_sender_bytes)
+        .map_err(|_| HpkeError::BadKey)?;
+    let pk_recipient =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_recipient_bytes)
+            .map_err(|_| HpkeError::BadKey)?;
+    let (enc, ct) = hpke::single_shot_seal::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256, _>(
+        &OpModeS::Auth((sk_sender, pk_sender)),
+        &pk_recipient,
+        info,
+        pt,
+        aad,
+        &mut rand::rngs::OsRng,
+    )
+    .map_err(HpkeError::from)?;
+    Ok((enc.to_bytes().to_vec(), ct))
+}
+
+/// Inverse de [`hpke_seal_auth`]; échoue si `pk_sender` n'est pas l'émetteur.
+pub fn hpke_open_auth(
+    pk_sender_bytes: &[u8; 32],
+    sk_recipient_bytes: &SecretKey32,
+    enc_bytes: &[u8],
+    info: &[u8],
+    ct: &[u8],
+    aad: &[u8],
+) -> HpkeResult<Vec<u8>> {
+    let pk_sender = <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_sender_bytes)
+        .map_err(|_| HpkeError::BadKey)?;
+    let sk_recipient =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_recipient_bytes.expose())
+            .map_err(|_| HpkeError::BadKey)?;
+    let enc = <X25519HkdfSha256 as hpke::kem::Kem>::EncappedKey::from_bytes(enc_bytes)
+        .map_err(|_| HpkeError::BadKey)?;
+    hpke::single_shot_open::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256>(
+        &OpModeR::Auth(pk_sender),
+        &sk_recipient,
+        &enc,
+        info,
+        ct,
+        aad,
+    )
+    .map_err(HpkeError::from)
+}
+
 pub fn hpke_initiate_auth(
-    sk_sender_bytes: &[u8; 32],
+    sk_sender_bytes: &SecretKey32,
     pk_recipient_bytes: &[u8; 32],
-) -> anyhow::Result<(Vec<u8>, [u8; 32])> {
-    let sk_sender = <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_sender_bytes)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
-    let dalek_sk = x25519_dalek::StaticSecret::from(*sk_sender_bytes);
+) -> HpkeResult<(Vec<u8>, [u8; 32])> {
+    let sk_sender =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_sender_bytes.expose())
+            .map_err(|_| HpkeError::BadKey)?;
+    let dalek_sk = x25519_dalek::StaticSecret::from(*sk_sender_bytes.expose());
     let pk_sender_bytes = x25519_dalek::PublicKey::from(&dalek_sk).to_bytes();
     let pk_sender = <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(&pk_sender_bytes)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(|_| HpkeError::BadKey)?;
     let pk_recipient =
         <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_recipient_bytes)
-            .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+            .map_err(|_| HpkeError::BadKey)?;
     let (enc, sender_ctx) = setup_sender::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256, _>(
         &OpModeS::Auth((sk_sender, pk_sender)),
         &pk_recipient,
         HL_INFO.as_bytes(),
         &mut rand::rngs::OsRng,
     )
-    .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+    .map_err(HpkeError::from)?;
     let mut okm = [0u8; 32];
     sender_ctx
         .export(EXPORT_LABEL, &mut okm)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(HpkeError::from)?;
     Ok((enc.to_bytes().to_vec(), okm))
 }
 
 pub fn hpke_accept_auth(
     pk_sender_bytes: &[u8; 32],
-    sk_recipient_bytes: &[u8; 32],
+    sk_recipient_bytes: &SecretKey32,
     enc_bytes: &[u8],
-) -> anyhow::Result<[u8; 32]> {
+) -> HpkeResult<[u8; 32]> {
     let pk_sender = <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_sender_bytes)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(|_| HpkeError::BadKey)?;
     let sk_recipient =
-        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_recipient_bytes)
-            .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_recipient_bytes.expose())
+            .map_err(|_| HpkeError::BadKey)?;
     let enc = <X25519HkdfSha256 as hpke::kem::Kem>::EncappedKey::from_bytes(enc_bytes)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(|_| HpkeError::BadKey)?;
     let receiver_ctx = setup_receiver::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256>(
         &OpModeR::Auth(pk_sender),
         &sk_recipient,
         &enc,
         HL_INFO.as_bytes(),
     )
-    .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+    .map_err(HpkeError::from)?;
     let mut okm = [0u8; 32];
     receiver_ctx
         .export(EXPORT_LABEL, &mut okm)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(HpkeError::from)?;
     Ok(okm)
 }
 
 pub fn hpke_initiate_auth_tagged(
-    sk_sender_bytes: &[u8; 32],
+    sk_sender_bytes: &SecretKey32,
     pk_recipient_bytes: &[u8; 32],
-) -> anyhow::Result<(Vec<u8>, [u8; 32], [u8; 32])> {
-    let sk_sender = <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_sender_bytes)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
-    let dalek_sk = x25519_dalek::StaticSecret::from(*sk_sender_bytes);
+) -> HpkeResult<(Vec<u8>, [u8; 32], [u8; 32])> {
+    let sk_sender =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_sender_bytes.expose())
+            .map_err(|_| HpkeError::BadKey)?;
+    let dalek_sk = x25519_dalek::StaticSecret::from(*sk_sender_bytes.expose());
     let pk_sender_bytes = x25519_dalek::PublicKey::from(&dalek_sk).to_bytes();
     let pk_sender = <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(&pk_sender_bytes)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(|_| HpkeError::BadKey)?;
     let pk_recipient =
         <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_recipient_bytes)
-            .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+            .map_err(|_| HpkeError::BadKey)?;
     let (enc, sender_ctx) = setup_sender::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256, _>(
         &OpModeS::Auth((sk_sender, pk_sender)),
         &pk_recipient,
         HL_INFO.as_bytes(),
         &mut rand::rngs::OsRng,
     )
-    .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+    .map_err(HpkeError::from)?;
     let mut okm = [0u8; 32];
     sender_ctx
         .export(EXPORT_LABEL, &mut okm)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(HpkeError::from)?;
     let mut tag = [0u8; 32];
     sender_ctx
         .export(AUTH_LABEL, &mut tag)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(HpkeError::from)?;
     Ok((enc.to_bytes().to_vec(), okm, tag))
 }
 
 pub fn hpke_accept_auth_check(
     pk_sender_bytes: &[u8; 32],
-    sk_recipient_bytes: &[u8; 32],
+    sk_recipient_bytes: &SecretKey32,
     enc_bytes: &[u8],
     expected_tag: &[u8; 32],
-) -> anyhow::Result<[u8; 32]> {
+) -> HpkeResult<[u8; 32]> {
     let pk_sender = <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_sender_bytes)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(|_| HpkeError::BadKey)?;
     let sk_recipient =
-        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_recipient_bytes)
-            .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_recipient_bytes.expose())
+            .map_err(|_| HpkeError::BadKey)?;
     let enc = <X25519HkdfSha256 as hpke::kem::Kem>::EncappedKey::from_bytes(enc_bytes)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(|_| HpkeError::BadKey)?;
     let receiver_ctx = setup_receiver::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256>(
         &OpModeR::Auth(pk_sender),
         &sk_recipient,
         &enc,
         HL_INFO.as_bytes(),
     )
-    .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+    .map_err(HpkeError::from)?;
     let mut okm = [0u8; 32];
     receiver_ctx
         .export(EXPORT_LABEL, &mut okm)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(HpkeError::from)?;
     let mut tag = [0u8; 32];
     receiver_ctx
         .export(AUTH_LABEL, &mut tag)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(HpkeError::from)?;
     if !bool::from(tag.ct_eq(expected_tag)) {
-        anyhow::bail!("hpke auth tag mismatch");
+        return Err(HpkeError::AuthMismatch);
     }
     Ok(okm)
 }
 
-pub fn hpke_initiate_with_binder(
+/// `(enc, okm, tag, binder)` produit par [`hpke_initiate_auth_with_binder`].
+pub type AuthBinderInit = (Vec<u8>, [u8; 32], [u8; 32], [u8; 32]);
+
+/// HPKE Auth avec tag d'authentification et binder de suite (`INIT_AUTH` v2).
+pub fn hpke_initiate_auth_with_binder(
+    sk_sender_bytes: &SecretKey32,
     pk_recipient_bytes: &[u8; 32],
     suite: u8,
-) -> anyhow::Result<(Vec<u8>, [u8; 32], [u8; 32])> {
+) -> HpkeResult<AuthBinderInit> {
+    let sk_sender =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_sender_bytes.expose())
+            .map_err(|_| HpkeError::BadKey)?;
+    let dalek_sk = x25519_dalek::StaticSecret::from(*sk_sender_bytes.expose());
+    let pk_sender_bytes = x25519_dalek::PublicKey::from(&dalek_sk).to_bytes();
+    let pk_sender = <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(&pk_sender_bytes)
+        .map_err(|_| HpkeError::BadKey)?;
     let pk_recipient =
         <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_recipient_bytes)
-            .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+            .map_err(|_| HpkeError::BadKey)?;
     let (enc, sender_ctx) = setup_sender::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256, _>(
-        &OpModeS::Base,
+        &OpModeS::Auth((sk_sender, pk_sender)),
         &pk_recipient,
         HL_INFO.as_bytes(),
         &mut rand::rngs::OsRng,
     )
-    .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+    .map_err(HpkeError::from)?;
     let mut okm = [0u8; 32];
     sender_ctx
         .export(EXPORT_LABEL, &mut okm)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(HpkeError::from)?;
+    let mut tag = [0u8; 32];
+    sender_ctx
+        .export(AUTH_LABEL, &mut tag)
+        .map_err(HpkeError::from)?;
     let mut binder = [0u8; 32];
     sender_ctx
         .export(&[BINDER_LABEL, &[suite]].concat(), &mut binder)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
-    Ok((enc.to_bytes().to_vec(), okm, binder))
+        .map_err(HpkeError::from)?;
+    Ok((enc.to_bytes().to_vec(), okm, tag, binder))
+}
+
+/// Côté récepteur de `INIT_AUTH` v2 : vérifie le binder puis le tag.
+pub fn hpke_accept_auth_with_binder(
+    suite: u8,
+    pk_sender_bytes: &[u8; 32],
+    sk_recipient_bytes: &SecretKey32,
+    enc_bytes: &[u8],
+    expected_tag: &[u8; 32],
+    expected_binder: &[u8; 32],
+) -> HpkeResult<[u8; 32]> {
+    let pk_sender = <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_sender_bytes)
+        .map_err(|_| HpkeError::BadKey)?;
+    let sk_recipient =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_recipient_bytes.expose())
+            .map_err(|_| HpkeError::BadKey)?;
+    let enc = <X25519HkdfSha256 as hpke::kem::Kem>::EncappedKey::from_bytes(enc_bytes)
+        .map_err(|_| HpkeError::BadKey)?;
+    let receiver_ctx = setup_receiver::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256>(
+        &OpModeR::Auth(pk_sender),
+        &sk_recipient,
+        &enc,
+        HL_INFO.as_bytes(),
+    )
+    .map_err(HpkeError::from)?;
+    let mut binder = [0u8; 32];
+    receiver_ctx
+        .export(&[BINDER_LABEL, &[suite]].concat(), &mut binder)
+        .map_err(HpkeError::from)?;
+    if !bool::from(binder.ct_eq(expected_binder)) {
+        return Err(HpkeError::BinderMismatch);
+    }
+    let mut tag = [0u8; 32];
+    receiver_ctx
+        .export(AUTH_LABEL, &mut tag)
+        .map_err(HpkeError::from)?;
+    if !bool::from(tag.ct_eq(expected_tag)) {
+        return Err(HpkeError::AuthMismatch);
+    }
+    let mut okm = [0u8; 32];
+    receiver_ctx
+        .export(EXPORT_LABEL, &mut okm)
+        .map_err(HpkeError::from)?;
+    Ok(okm)
+}
+
+pub fn hpke_initiate_with_binder(
+    pk_recipient_bytes: &[u8; 32],
+    suite: u8,
+) -> HpkeResult<(Vec<u8>, [u8; 32], [u8; 32])> {
+    hpke_initiate_with_binder_ctx(pk_recipient_bytes, suite, &[])
+}
+
+/// Comme [`hpke_initiate_with_binder`], avec `ctx` ajouté aux deux contextes
+/// d'export (`okm` et binder sont alors liés à `ctx`).
+pub fn hpke_initiate_with_binder_ctx(
+    pk_recipient_bytes: &[u8; 32],
+    suite: u8,
+    ctx: &[u8],
+) -> HpkeResult<(Vec<u8>, [u8; 32], [u8; 32])> {
+    let (enc, okm, binder, _kc) = hpke_initiate_confirm_ctx(pk_recipient_bytes, suite, ctx)?;
+    Ok((enc, okm, binder))
+}
+
+/// Comme [`hpke_initiate_with_binder_ctx`], exporte en plus la clé de
+/// confirmation `kc := export("hardlock/confirm" || ctx)`.
+pub fn hpke_initiate_confirm_ctx(
+    pk_recipient_bytes: &[u8; 32],
+    suite: u8,
+    ctx: &[u8],
+) -> HpkeResult<ConfirmInit> {
+    let pk_recipient =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_recipient_bytes)
+            .map_err(|_| HpkeError::BadKey)?;
+    let (enc, sender_ctx) = setup_sender::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256, _>(
+        &OpModeS::Base,
+        &pk_recipient,
+        HL_INFO.as_bytes(),
+        &mut rand::rngs::OsRng,
+    )
+    .map_err(HpkeError::from)?;
+    let mut okm = [0u8; 32];
+    sender_ctx
+        .export(&[EXPORT_LABEL, ctx].concat(), &mut okm)
+        .map_err(HpkeError::from)?;
+    let mut binder = [0u8; 32];
+    sender_ctx
+        .export(&[BINDER_LABEL, &[suite], ctx].concat(), &mut binder)
+        .map_err(HpkeError::from)?;
+    let mut kc = [0u8; 32];
+    sender_ctx
+        .export(&[CONFIRM_LABEL, ctx].concat(), &mut kc)
+        .map_err(HpkeError::from)?;
+    Ok((enc.to_bytes().to_vec(), okm, binder, kc))
 }
 
 pub fn hpke_accept_with_binder(
     suite: u8,
-    sk_recipient_bytes: &[u8; 32],
+    sk_recipient_bytes: &SecretKey32,
+    enc_bytes: &[u8],
+    expected_binder: &[u8; 32],
+) -> HpkeResult<[u8; 32]> {
+    hpke_accept_with_binder_ctx(suite, sk_recipient_bytes, enc_bytes, &[], expected_binder)
+}
+
+/// Côté récepteur de [`hpke_initiate_with_binder_ctx`].
+pub fn hpke_accept_with_binder_ctx(
+    suite: u8,
+    sk_recipient_bytes: &SecretKey32,
     enc_bytes: &[u8],
+    ctx: &[u8],
     expected_binder: &[u8; 32],
-) -> anyhow::Result<[u8; 32]> {
+) -> HpkeResult<[u8; 32]> {
+    let (okm, _kc) =
+        hpke_accept_confirm_ctx(suite, sk_recipient_bytes, enc_bytes, ctx, expected_binder)?;
+    Ok(okm)
+}
+
+/// Côté récepteur de [`hpke_initiate_confirm_ctx`] : renvoie `(okm, kc)`.
+pub fn hpke_accept_confirm_ctx(
+    suite: u8,
+    sk_recipient_bytes: &SecretKey32,
+    enc_bytes: &[u8],
+    ctx: &[u8],
+    expected_binder: &[u8; 32],
+) -> HpkeResult<([u8; 32], [u8; 32])> {
     let sk_recipient =
-        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_recipient_bytes)
-            .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_recipient_bytes.expose())
+            .map_err(|_| HpkeError::BadKey)?;
     let enc = <X25519HkdfSha256 as hpke::kem::Kem>::EncappedKey::from_bytes(enc_bytes)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(|_| HpkeError::BadKey)?;
     let receiver_ctx = setup_receiver::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256>(
         &OpModeR::Base,
         &sk_recipient,
         &enc,
         HL_INFO.as_bytes(),
     )
-    .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+    .map_err(HpkeError::from)?;
     let mut okm = [0u8; 32];
     receiver_ctx
+        .export(&[EXPORT_LABEL, ctx].concat(), &mut okm)
+        .map_err(HpkeError::from)?;
+    let mut binder = [0u8; 32];
+    receiver_ctx
+        .export(&[BINDER_LABEL, &[suite], ctx].concat(), &mut binder)
+        .map_err(HpkeError::from)?;
+    if !bool::from(binder.ct_eq(expected_binder)) {
+        return Err(HpkeError::BinderMismatch);
+    }
+    let mut kc = [0u8; 32];
+    receiver_ctx
+        .export(&[CONFIRM_LABEL, ctx].concat(), &mut kc)
+        .map_err(HpkeError::from)?;
+    Ok((okm, kc))
+}
+
+/// MAC de confirmation de clé : `HKDF(kc, "hardlock/confirm" || SHA256(init_frame))`.
+#[must_use]
+pub fn key_confirmation_mac(kc: &[u8; 32], init_frame: &[u8]) -> [u8; 32] {
+    let th = Sha256::digest(init_frame);
+    kdf32(kc, &[CONFIRM_LABEL, &th[..]].concat())
+}
+
+/// Clé de scellement de l'identité (INIT masqué) : `HKDF(kc, "hardlock/id-seal")`.
+#[must_use]
+pub fn identity_seal_key(kc: &[u8; 32]) -> [u8; 32] {
+    kdf32(kc, ID_SEAL_LABEL)
+}
+
+fn psk_binder_ctx(suite: u8, psk_id: &[u8]) -> Vec<u8> {
+    [BINDER_LABEL, &[suite], psk_id].concat()
+}
+
+fn psk_initiate_with_rng<R: CryptoRng + RngCore>(
+    sk_sender_bytes: Option<&SecretKey32>,
+    pk_recipient_bytes: &[u8; 32],
+    psk: &[u8],
+    psk_id: &[u8],
+    rng: &mut R,
+) -> HpkeResult<AuthBinderInit> {
+    let pk_recipient =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_recipient_bytes)
+            .map_err(|_| HpkeError::BadKey)?;
+    let bundle = PskBundle { psk, psk_id };
+    let (mode, suite) = match sk_sender_bytes {
+        Some(sk_bytes) => {
+            let sk_sender =
+                <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_bytes.expose())
+                    .map_err(|_| HpkeError::BadKey)?;
+            let dalek_sk = x25519_dalek::StaticSecret::from(*sk_bytes.expose());
+            let pk_sender_bytes = x25519_dalek::PublicKey::from(&dalek_sk).to_bytes();
+            let pk_sender =
+                <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(&pk_sender_bytes)
+                    .map_err(|_| HpkeError::BadKey)?;
+            (
+                OpModeS::AuthPsk((sk_sender, pk_sender), bundle),
+                suites::HL1_AUTH_PSK,
+            )
+        }
+        None => (OpModeS::Psk(bundle), suites::HL1_PSK),
+    };
+    let (enc, sender_ctx) = setup_sender::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256, _>(
+        &mode,
+        &pk_recipient,
+        HL_INFO.as_bytes(),
+        rng,
+    )
+    .map_err(HpkeError::from)?;
+    let mut okm = [0u8; 32];
+    sender_ctx
         .export(EXPORT_LABEL, &mut okm)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .map_err(HpkeError::from)?;
+    let mut tag = [0u8; 32];
+    sender_ctx
+        .export(AUTH_LABEL, &mut tag)
+        .map_err(HpkeError::from)?;
+    let mut binder = [0u8; 32];
+    sender_ctx
+        .export(&psk_binder_ctx(suite, psk_id), &mut binder)
+        .map_err(HpkeError::from)?;
+    Ok((enc.to_bytes().to_vec(), okm, tag, binder))
+}
+
+fn psk_accept(
+    pk_sender_bytes: Option<&[u8; 32]>,
+    sk_recipient_bytes: &SecretKey32,
+    psk: &[u8],
+    psk_id: &[u8],
+    enc_bytes: &[u8],
+    expected_tag: Option<&[u8; 32]>,
+    expected_binder: &[u8; 32],
+) -> HpkeResult<[u8; 32]> {
+    let sk_recipient =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_recipient_bytes.expose())
+            .map_err(|_| HpkeError::BadKey)?;
+    let enc = <X25519HkdfSha256 as hpke::kem::Kem>::EncappedKey::from_bytes(enc_bytes)
+        .map_err(|_| HpkeError::BadKey)?;
+    let bundle = PskBundle { psk, psk_id };
+    let (mode, suite) = match pk_sender_bytes {
+        Some(pk_bytes) => {
+            let pk_sender = <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_bytes)
+                .map_err(|_| HpkeError::BadKey)?;
+            (OpModeR::AuthPsk(pk_sender, bundle), suites::HL1_AUTH_PSK)
+        }
+        None => (OpModeR::Psk(bundle), suites::HL1_PSK),
+    };
+    let receiver_ctx = setup_receiver::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256>(
+        &mode,
+        &sk_recipient,
+        &enc,
+        HL_INFO.as_bytes(),
+    )
+    .map_err(HpkeError::from)?;
     let mut binder = [0u8; 32];
     receiver_ctx
-        .export(&[BINDER_LABEL, &[suite]].concat(), &mut binder)
-        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        .export(&psk_binder_ctx(suite, psk_id), &mut binder)
+        .map_err(HpkeError::from)?;
+    if !bool::from(binder.ct_eq(expected_binder)) {
+        return Err(HpkeError::BinderMismatch);
+    }
+    if let Some(expected_tag) = expected_tag {
+        let mut tag = [0u8; 32];
+        receiver_ctx
+            .export(AUTH_LABEL, &mut tag)
+            .map_err(HpkeError::from)?;
+        if !bool::from(tag.ct_eq(expected_tag)) {
+            return Err(HpkeError::AuthMismatch);
+        }
+    }
+    let mut okm = [0u8; 32];
+    receiver_ctx
+        .export(EXPORT_LABEL, &mut okm)
+        .map_err(HpkeError::from)?;
+    Ok(okm)
+}
+
+/// HPKE mode PSK (suite `HL1_PSK`) : renvoie `(enc, okm, binder)`.
+///
+/// `binder := export("hardlock/suite-binder" || suite || psk_id)`.
+pub fn hpke_initiate_psk(
+    pk_recipient_bytes: &[u8; 32],
+    psk: &[u8],
+    psk_id: &[u8],
+) -> HpkeResult<(Vec<u8>, [u8; 32], [u8; 32])> {
+    let (enc, okm, _tag, binder) = psk_initiate_with_rng(
+        None,
+        pk_recipient_bytes,
+        psk,
+        psk_id,
+        &mut rand::rngs::OsRng,
+    )?;
+    Ok((enc, okm, binder))
+}
+
+pub fn hpke_initiate_psk_deterministic(
+    pk_recipient_bytes: &[u8; 32],
+    psk: &[u8],
+    psk_id: &[u8],
+    seed32: [u8; 32],
+) -> HpkeResult<(Vec<u8>, [u8; 32], [u8; 32])> {
+    let mut rng = ChaCha20Rng::from_seed(seed32);
+    let (enc, okm, _tag, binder) =
+        psk_initiate_with_rng(None, pk_recipient_bytes, psk, psk_id, &mut rng)?;
+    Ok((enc, okm, binder))
+}
+
+pub fn hpke_accept_psk(
+    sk_recipient_bytes: &SecretKey32,
+    psk: &[u8],
+    psk_id: &[u8],
+    enc_bytes: &[u8],
+    expected_binder: &[u8; 32],
+) -> HpkeResult<[u8; 32]> {
+    psk_accept(
+        None,
+        sk_recipient_bytes,
+        psk,
+        psk_id,
+        enc_bytes,
+        None,
+        expected_binder,
+    )
+}
+
+/// HPKE mode `AuthPSK` (suite `HL1_AUTH_PSK`) : renvoie `(enc, okm, tag, binder)`.
+pub fn hpke_initiate_auth_psk(
+    sk_sender_bytes: &SecretKey32,
+    pk_recipient_bytes: &[u8; 32],
+    psk: &[u8],
+    psk_id: &[u8],
+) -> HpkeResult<AuthBinderInit> {
+    psk_initiate_with_rng(
+        Some(sk_sender_bytes),
+        pk_recipient_bytes,
+        psk,
+        psk_id,
+        &mut rand::rngs::OsRng,
+    )
+}
+
+pub fn hpke_initiate_auth_psk_deterministic(
+    sk_sender_bytes: &SecretKey32,
+    pk_recipient_bytes: &[u8; 32],
+    psk: &[u8],
+    psk_id: &[u8],
+    seed32: [u8; 32],
+) -> HpkeResult<AuthBinderInit> {
+    let mut rng = ChaCha20Rng::from_seed(seed32);
+    psk_initiate_with_rng(
+        Some(sk_sender_bytes),
+        pk_recipient_bytes,
+        psk,
+        psk_id,
+        &mut rng,
+    )
+}
+
+pub fn hpke_accept_auth_psk(
+    pk_sender_bytes: &[u8; 32],
+    sk_recipient_bytes: &SecretKey32,
+    psk: &[u8],
+    psk_id: &[u8],
+    enc_bytes: &[u8],
+    expected_tag: &[u8; 32],
+    expected_binder: &[u8; 32],
+) -> HpkeResult<[u8; 32]> {
+    psk_accept(
+        Some(pk_sender_bytes),
+        sk_recipient_bytes,
+        psk,
+        psk_id,
+        enc_bytes,
+        Some(expected_tag),
+        expected_binder,
+    )
+}
+
+/// Suite `HL1_HYB` : HPKE Base X25519 + encapsulation ML-KEM-768.
+///
+/// Renvoie `(enc, kem_ct, okm, binder)`; `okm` combine l'export HPKE et le
+/// secret ML-KEM, `binder` couvre la suite 0x11 et les deux chiffrés.
+pub fn hpke_initiate_hybrid(
+    pk_recipient_bytes: &[u8; 32],
+    ek_recipient_bytes: &[u8],
+) -> HpkeResult<HybridInit> {
+    let pk_recipient =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PublicKey::from_bytes(pk_recipient_bytes)
+            .map_err(|_| HpkeError::BadKey)?;
+    if !mlkem_ek_modulus_ok(ek_recipient_bytes) {
+        return Err(HpkeError::BadKey);
+    }
+    let ek_enc = ek_recipient_bytes
+        .try_into()
+        .map_err(|_| HpkeError::BadKey)?;
+    let ek_recipient = MlKemEk::from_bytes(ek_enc);
+    let (enc, sender_ctx) = setup_sender::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256, _>(
+        &OpModeS::Base,
+        &pk_recipient,
+        HL_INFO.as_bytes(),
+        &mut rand::rngs::OsRng,
+    )
+    .map_err(HpkeError::from)?;
+    let mut okm_x = [0u8; 32];
+    sender_ctx
+        .export(EXPORT_LABEL, &mut okm_x)
+        .map_err(HpkeError::from)?;
+    let mut binder_x = [0u8; 32];
+    sender_ctx
+        .export(&[BINDER_LABEL, &[suites::HL1_HYB]].concat(), &mut binder_x)
+        .map_err(HpkeError::from)?;
+    let (kem_ct, ss) = ek_recipient
+        .encapsulate(&mut rand::rngs::OsRng)
+        .map_err(|()| HpkeError::Kem)?;
+    let enc = enc.to_bytes().to_vec();
+    let kem_ct = kem_ct.to_vec();
+    let (okm, binder) = combine_hybrid(&okm_x, &binder_x, &ss, &enc, &kem_ct);
+    Ok((enc, kem_ct, okm, binder))
+}
+
+/// Module ML-KEM (FIPS 203).
+const MLKEM_Q: u16 = 3329;
+/// `ek` ML-KEM-768 : 3 polynômes × 384 o (`ByteEncode12`) puis `rho` (32 o).
+const MLKEM768_EK_LEN: usize = 3 * 384 + 32;
+
+/// Contrôle de module de FIPS 203 §7.2 : `ek` doit faire 1184 o et chaque
+/// coefficient 12 bits doit être < q (encodage canonique).
+fn mlkem_ek_modulus_ok(ek: &[u8]) -> bool {
+    ek.len() == MLKEM768_EK_LEN
+        && ek[..MLKEM768_EK_LEN - 32].chunks_exact(3).all(|c| {
+            let a = u16::from(c[0]) | (u16::from(c[1] & 0x0f) << 8);
+            let b = u16::from(c[1] >> 4) | (u16::from(c[2]) << 4);
+            a < MLKEM_Q && b < MLKEM_Q
+        })
+}
+
+/// Côté récepteur de `HL1_HYB` : vérifie le binder puis renvoie `okm`.
+pub fn hpke_accept_hybrid(
+    sk_recipient_bytes: &SecretKey32,
+    dk_recipient_bytes: &SecretBytes,
+    enc_bytes: &[u8],
+    kem_ct_bytes: &[u8],
+    expected_binder: &[u8; 32],
+) -> HpkeResult<[u8; 32]> {
+    let sk_recipient =
+        <X25519HkdfSha256 as hpke::kem::Kem>::PrivateKey::from_bytes(sk_recipient_bytes.expose())
+            .map_err(|_| HpkeError::BadKey)?;
+    let dk_enc = dk_recipient_bytes
+        .expose()
+        .try_into()
+        .map_err(|_| HpkeError::BadKey)?;
+    let dk_recipient = MlKemDk::from_bytes(dk_enc);
+    let kem_ct: &Ciphertext<MlKem768> = kem_ct_bytes.try_into().map_err(|_| HpkeError::Kem)?;
+    let enc = <X25519HkdfSha256 as hpke::kem::Kem>::EncappedKey::from_bytes(enc_bytes)
+        .map_err(|_| HpkeError::BadKey)?;
+    let receiver_ctx = setup_receiver::<ChaCha20Poly1305, HkdfSha256, X25519HkdfSha256>(
+        &OpModeR::Base,
+        &sk_recipient,
+        &enc,
+        HL_INFO.as_bytes(),
+    )
+    .map_err(HpkeError::from)?;
+    let mut okm_x = [0u8; 32];
+    receiver_ctx
+        .export(EXPORT_LABEL, &mut okm_x)
+        .map_err(HpkeError::from)?;
+    let mut binder_x = [0u8; 32];
+    receiver_ctx
+        .export(&[BINDER_LABEL, &[suites::HL1_HYB]].concat(), &mut binder_x)
+        .map_err(HpkeError::from)?;
+    let ss = dk_recipient
+        .decapsulate(kem_ct)
+        .map_err(|()| HpkeError::Kem)?;
+    let (okm, binder) = combine_hybrid(&okm_x, &binder_x, &ss, enc_bytes, kem_ct_bytes);
     if !bool::from(binder.ct_eq(expected_binder)) {
-        anyhow::bail!("binder-mismatch");
+        return Err(HpkeError::BinderMismatch);
     }
     Ok(okm)
 }
@@ -274,6 +894,30 @@ pub fn derive_initial_secret(
     }
 }
 
+/// Combine l'export HPKE X25519 et le secret ML-KEM, liés aux deux chiffrés.
+/// Renvoie `(okm, binder)`.
+fn combine_hybrid(
+    okm_x: &[u8; 32],
+    binder_x: &[u8; 32],
+    ss_kem: &[u8],
+    enc: &[u8],
+    kem_ct: &[u8],
+) -> ([u8; 32], [u8; 32]) {
+    let mut h = Sha256::new();
+    h.update(enc);
+    h.update(kem_ct);
+    let mut t = Vec::with_capacity(6 + 1 + 32);
+    t.extend_from_slice(b"HL1-H:");
+    t.push(suites::HL1_HYB);
+    t.extend_from_slice(&h.finalize());
+    let okm = kdf32(&[&okm_x[..], ss_kem].concat(), &[HYBRID_LABEL, &t].concat());
+    let binder = kdf32(
+        &[&binder_x[..], ss_kem].concat(),
+        &[BINDER_LABEL, &t].concat(),
+    );
+    (okm, binder)
+}
+
 fn kdf32(ikm: &[u8], info: &[u8]) -> [u8; 32] {
     let hk = Hkdf::<Sha256>::new(None, ikm);
     let mut okm = [0u8; 32];
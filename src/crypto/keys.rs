@@ -1,17 +1,20 @@
 #![allow(clippy::missing_panics_doc)]
 
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use ml_kem::{EncodedSizeUser, KemCore, MlKem768};
+
+pub use super::secret::{SecretBytes, SecretKey32};
 use rand::rngs::OsRng;
 use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use x25519_dalek::{PublicKey as X25519Public, StaticSecret};
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Ed25519Identity {
     #[serde(with = "serde_bytes")]
-    pub pk: Vec<u8>,
-    #[serde(with = "serde_bytes")]
-    pub sk: Vec<u8>,
+    pub pk: [u8; 32],
+    pub sk: SecretKey32,
 }
 impl Ed25519Identity {
     #[must_use]
@@ -19,30 +22,34 @@ impl Ed25519Identity {
         let sk = SigningKey::generate(&mut OsRng);
         let pk = sk.verifying_key();
         Self {
-            pk: pk.to_bytes().to_vec(),
-            sk: sk.to_bytes().to_vec(),
+            pk: pk.to_bytes(),
+            sk: SecretKey32::from_bytes(sk.to_bytes()),
         }
     }
     #[must_use]
     pub fn sign(&self, data: &[u8]) -> Signature {
-        let sk_bytes: [u8; 32] = self.sk.clone().try_into().expect("sk32");
-        let sk = SigningKey::from_bytes(&sk_bytes);
-        sk.sign(data)
+        SigningKey::from_bytes(self.sk.expose()).sign(data)
     }
     #[must_use]
     pub fn verify(&self, data: &[u8], sig: &Signature) -> bool {
-        let pk_bytes: [u8; 32] = self.pk.clone().try_into().expect("pk32");
-        let vk = VerifyingKey::from_bytes(&pk_bytes).expect("vk");
+        let vk = VerifyingKey::from_bytes(&self.pk).expect("vk");
         vk.verify(data, sig).is_ok()
     }
 }
+impl fmt::Debug for Ed25519Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ed25519Identity")
+            .field("pk", &hex::encode(self.pk))
+            .field("sk", &self.sk)
+            .finish()
+    }
+}
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct X25519KeyPair {
+    pub sk: SecretKey32,
     #[serde(with = "serde_bytes")]
-    pub sk: Vec<u8>,
-    #[serde(with = "serde_bytes")]
-    pub pk: Vec<u8>,
+    pub pk: [u8; 32],
 }
 impl X25519KeyPair {
     #[must_use]
@@ -50,8 +57,8 @@ impl X25519KeyPair {
         let sk = StaticSecret::random_from_rng(OsRng);
         let pk = X25519Public::from(&sk);
         Self {
-            sk: sk.to_bytes().to_vec(),
-            pk: pk.to_bytes().to_vec(),
+            sk: SecretKey32::from_bytes(sk.to_bytes()),
+            pk: pk.to_bytes(),
         }
     }
     #[must_use]
@@ -59,18 +66,47 @@ impl X25519KeyPair {
         let sk = StaticSecret::from(seed32);
         let pk = X25519Public::from(&sk);
         Self {
-            sk: sk.to_bytes().to_vec(),
-            pk: pk.to_bytes().to_vec(),
+            sk: SecretKey32::from_bytes(sk.to_bytes()),
+            pk: pk.to_bytes(),
         }
     }
     #[must_use]
     pub fn public(&self) -> [u8; 32] {
-        self.pk.clone().try_into().unwrap()
+        self.pk
     }
     #[must_use]
     pub fn secret(&self) -> StaticSecret {
-        let bytes: [u8; 32] = self.sk.clone().try_into().expect("sk32");
-        StaticSecret::from(bytes)
+        StaticSecret::from(*self.sk.expose())
+    }
+}
+impl fmt::Debug for X25519KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("X25519KeyPair")
+            .field("sk", &self.sk)
+            .field("pk", &hex::encode(self.pk))
+            .finish()
+    }
+}
+
+/// Paire ML-KEM-768 (suite `HL1_HYB`) : `dk` décapsulation, `ek` encapsulation.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct MlKem768KeyPair {
+    pub dk: SecretBytes,
+    #[serde(with = "serde_bytes")]
+    pub ek: Vec<u8>,
+}
+impl MlKem768KeyPair {
+    #[must_use]
+    pub fn generate() -> Self {
+        let (dk, ek) = MlKem768::generate(&mut OsRng);
+        Self {
+            dk: SecretBytes::new(dk.as_bytes().to_vec()),
+            ek: ek.as_bytes().to_vec(),
+        }
+    }
+    #[must_use]
+    pub fn public(&self) -> Vec<u8> {
+        self.ek.clone()
     }
 }
 
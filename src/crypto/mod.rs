@@ -1,3 +1,4 @@
 pub mod aeadx;
 pub mod hpke_hybrid;
 pub mod keys;
+pub mod secret;
@@ -0,0 +1,124 @@
+//! Certificats d'émetteur délivrés par le serveur (sealed sender, ENVELOPE.md).
+//!
+//! `cert := ver:u8 = 1 | expiry:u64 | identity_ed25519:32 | identity_x25519:32 | device_len:u8 | device_id | sig:64`
+//!
+//! `sig := Ed25519_serveur("hardlock/sender-cert" | cert sans sig)`. Le
+//! certificat ne voyage que chiffré vers le destinataire (`sealed_sender`).
+
+use super::EnvelopeError;
+use crate::crypto::keys::Ed25519Identity;
+use crate::wire::WireError;
+use ed25519_dalek::{Signature, VerifyingKey};
+
+pub const SENDER_CERT_VERSION: u8 = 1;
+const SENDER_CERT_LABEL: &[u8] = b"hardlock/sender-cert";
+const SIG_LEN: usize = 64;
+
+/// Atteste, jusqu'à `expiry_unix_s`, que `identity_ed25519` et `identity_x25519`
+/// sont les clés d'identité de l'appareil `device_id`. `identity_x25519` est la
+/// clé qui authentifie l'émetteur en sealed sender (HPKE Auth).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SenderCertificate {
+    pub identity_ed25519: [u8; 32],
+    pub identity_x25519: [u8; 32],
+    pub device_id: String,
+    pub expiry_unix_s: u64,
+    pub signature: [u8; SIG_LEN],
+}
+
+impl SenderCertificate {
+    /// Signe un certificat avec la clé du serveur.
+    ///
+    /// # Errors
+    /// `DeviceIdTooLong` si `device_id` dépasse 255 octets.
+    pub fn issue(
+        server: &Ed25519Identity,
+        identity_ed25519: [u8; 32],
+        identity_x25519: [u8; 32],
+        device_id: &str,
+        expiry_unix_s: u64,
+    ) -> Result<Self, EnvelopeError> {
+        let mut cert = Self {
+            identity_ed25519,
+            identity_x25519,
+            device_id: device_id.to_owned(),
+            expiry_unix_s,
+            signature: [0u8; SIG_LEN],
+        };
+        cert.signature = server.sign(&cert.signed_bytes()?).to_bytes();
+        Ok(cert)
+    }
+
+    fn body(&self) -> Result<Vec<u8>, EnvelopeError> {
+        let dl = u8::try_from(self.device_id.len()).map_err(|_| EnvelopeError::DeviceIdTooLong)?;
+        let mut out = Vec::with_capacity(1 + 8 + 32 + 32 + 1 + self.device_id.len());
+        out.push(SENDER_CERT_VERSION);
+        out.extend_from_slice(&self.expiry_unix_s.to_le_bytes());
+        out.extend_from_slice(&self.identity_ed25519);
+        out.extend_from_slice(&self.identity_x25519);
+        out.push(dl);
+        out.extend_from_slice(self.device_id.as_bytes());
+        Ok(out)
+    }
+
+    fn signed_bytes(&self) -> Result<Vec<u8>, EnvelopeError> {
+        Ok([SENDER_CERT_LABEL, &self.body()?].concat())
+    }
+
+    /// # Errors
+    /// `DeviceIdTooLong` si `device_id` dépasse 255 octets.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EnvelopeError> {
+        let mut out = self.body()?;
+        out.extend_from_slice(&self.signature);
+        Ok(out)
+    }
+
+    /// Décode sans vérifier la signature (voir [`Self::verify`]).
+    ///
+    /// # Errors
+    /// `ShortHeader`, `BadVersion` ou `Malformed` (longueur, `device_id` non UTF-8).
+    pub fn from_bytes(b: &[u8]) -> Result<Self, WireError> {
+        if b.len() < 1 + 8 + 32 + 32 + 1 + SIG_LEN {
+            return Err(WireError::ShortHeader);
+        }
+        if b[0] != SENDER_CERT_VERSION {
+            return Err(WireError::BadVersion(u16::from(b[0])));
+        }
+        let expiry_unix_s = u64::from_le_bytes(b[1..9].try_into().unwrap());
+        let identity_ed25519 = b[9..41].try_into().unwrap();
+        let identity_x25519 = b[41..73].try_into().unwrap();
+        let dl = usize::from(b[73]);
+        if b.len() != 74 + dl + SIG_LEN {
+            return Err(WireError::Malformed("sender certificate length"));
+        }
+        let device_id = std::str::from_utf8(&b[74..74 + dl])
+            .map_err(|_| WireError::Malformed("sender certificate device id"))?
+            .to_owned();
+        Ok(Self {
+            identity_ed25519,
+            identity_x25519,
+            device_id,
+            expiry_unix_s,
+            signature: b[74 + dl..].try_into().unwrap(),
+        })
+    }
+
+    /// Vérifie (strictement) la signature du serveur puis l'expiration.
+    ///
+    /// # Errors
+    /// `BadCertificate` si la signature (ou la clé serveur) est invalide ou si
+    /// `device_id` dépasse 255 octets; `CertificateExpired` si
+    /// `now_unix_s > expiry_unix_s`.
+    pub fn verify(&self, server_pub: &[u8; 32], now_unix_s: u64) -> Result<(), EnvelopeError> {
+        let vk = VerifyingKey::from_bytes(server_pub).map_err(|_| EnvelopeError::BadCertificate)?;
+        let msg = self
+            .signed_bytes()
+            .map_err(|_| EnvelopeError::BadCertificate)?;
+        vk.verify_strict(&msg, &Signature::from_bytes(&self.signature))
+            .map_err(|_| EnvelopeError::BadCertificate)?;
+        if now_unix_s > self.expiry_unix_s {
+            return Err(EnvelopeError::CertificateExpired);
+        }
+        Ok(())
+    }
+}
@@ -18,6 +18,30 @@ pub fn derive_k_s(master: &[u8], salt: &[u8]) -> [u8; KEY_LEN] {
     k
 }
 
+/// Clair du jeton : `expiry:u64 | sender_pub:32 | scope_len:u16 | scope`.
+/// `None` si `scope` dépasse 65 535 octets.
+fn token_plaintext(expiry_unix_s: u64, sender_pub32: &[u8; 32], scope: &[u8]) -> Option<Vec<u8>> {
+    let slen = u16::try_from(scope.len()).ok()?;
+    let mut pt = Vec::with_capacity(8 + 32 + 2 + scope.len());
+    pt.extend_from_slice(&expiry_unix_s.to_le_bytes());
+    pt.extend_from_slice(sender_pub32);
+    pt.extend_from_slice(&slen.to_le_bytes());
+    pt.extend_from_slice(scope);
+    Some(pt)
+}
+
+/// Inverse de [`token_plaintext`]; n'examine pas l'expiration.
+fn parse_token_plaintext(pt: &[u8]) -> Option<(u64, [u8; 32], Vec<u8>)> {
+    if pt.len() < 8 + 32 + 2 {
+        return None;
+    }
+    let expiry = u64::from_le_bytes(pt[0..8].try_into().ok()?);
+    let pk: [u8; 32] = pt[8..40].try_into().ok()?;
+    let sl = u16::from_le_bytes(pt[40..42].try_into().ok()?) as usize;
+    let scope = pt.get(42..42 + sl)?.to_vec();
+    Some((expiry, pk, scope))
+}
+
 #[must_use]
 pub fn token_build(
     k_s: &[u8; KEY_LEN],
@@ -25,49 +49,32 @@ pub fn token_build(
     sender_pub32: &[u8; 32],
     scope: &[u8],
 ) -> SenderToken {
-    let mut pt = Vec::with_capacity(8 + 32 + 2 + scope.len());
-    pt.extend_from_slice(&expiry_unix_s.to_le_bytes());
-    pt.extend_from_slice(sender_pub32);
-    let slen = u16::try_from(scope.len()).expect("scope");
-    pt.extend_from_slice(&slen.to_le_bytes());
-    pt.extend_from_slice(scope);
-
+    let pt = token_plaintext(expiry_unix_s, sender_pub32, scope).expect("scope");
     let nonce = rand_nonce();
     let ad = b"hardlock/sealed-sender";
     let ct = seal_xchacha(k_s, &nonce, &pt, ad);
     SenderToken { nonce, ct }
 }
 
+/// Ouvre le jeton sans examiner l'expiration : `(expiry, sender_pub, scope)`.
+fn token_open(k_s: &[u8; KEY_LEN], token: &SenderToken) -> Option<(u64, [u8; 32], Vec<u8>)> {
+    let ad = b"hardlock/sealed-sender";
+    let pt = open_xchacha(k_s, &token.nonce, &token.ct, ad)?;
+    parse_token_plaintext(&pt)
+}
+
+/// Vérifie l'AEAD et l'expiration seulement : un jeton capturé reste
+/// rejouable jusqu'à expiration (voir [`TokenVerifier`]).
 #[must_use]
 pub fn token_verify(
     k_s: &[u8; KEY_LEN],
     token: &SenderToken,
     now_unix_s: u64,
 ) -> Option<(u64, [u8; 32], Vec<u8>)> {
-    let ad = b"hardlock/sealed-sender";
-    let pt = open_xchacha(k_s, &token.nonce, &token.ct, ad)?;
-    if pt.len() < 8 + 32 + 2 {
-        return None;
-    }
-    let mut off = 0usize;
-    let mut tsb = [0u8; 8];
-    tsb.copy_from_slice(&pt[off..off + 8]);
-    off += 8;
-    let expiry = u64::from_le_bytes(tsb);
+    let (expiry, pk, scope) = token_open(k_s, token)?;
     if now_unix_s > expiry {
         return None;
     }
-    let mut pk = [0u8; 32];
-    pk.copy_from_slice(&pt[off..off + 32]);
-    off += 32;
-    let mut slb = [0u8; 2];
-    slb.copy_from_slice(&pt[off..off + 2]);
-    off += 2;
-    let sl = u16::from_le_bytes(slb) as usize;
-    if pt.len() < off + sl {
-        return None;
-    }
-    let scope = pt[off..off + sl].to_vec();
     Some((expiry, pk, scope))
 }
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -75,6 +82,10 @@ pub enum PadProfile {
     Stealth,
     Balanced,
     Throughput,
+    /// Padmé : surcoût ≤ 12 %, ne révèle que O(log log L) bits de la longueur.
+    Padme,
+    /// Seaux fournis par l'appelant; au-delà du dernier, Padmé.
+    Custom(PadBuckets),
 }
 
 #[must_use]
@@ -86,6 +97,10 @@ pub fn pad_bucket_for(len: usize, profile: PadProfile) -> usize {
         PadProfile::Stealth => &buckets_stealth[..],
         PadProfile::Balanced => &buckets_bal[..],
         PadProfile::Throughput => &buckets_tp[..],
+        PadProfile::Padme => return padme(len),
+        PadProfile::Custom(b) => {
+            return b.iter().find(|&s| len <= s).unwrap_or_else(|| padme(len));
+        }
     };
     for &b in buckets {
         if len <= b {
@@ -96,13 +111,45 @@ pub fn pad_bucket_for(len: usize, profile: PadProfile) -> usize {
     len.div_ceil(last).saturating_mul(last)
 }
 
+/// Marqueur de fin du clair (bourrage ISO/IEC 7816-4 : `0x80` puis des zéros).
+pub const PAD_MARKER: u8 = 0x80;
+
+/// Bourre le clair **avant** chiffrement : `pt || 0x80 || 0x00*`, longueur
+/// totale = seau du profil pour `pt.len() + 1`. Le marqueur est toujours
+/// présent, le retrait est donc non ambigu.
 #[must_use]
-pub fn apply_padding(mut frame: Vec<u8>, profile: PadProfile) -> Vec<u8> {
-    let target = pad_bucket_for(frame.len(), profile);
-    if target > frame.len() {
-        frame.resize(target, 0);
-    }
-    frame
+pub fn pad_plaintext(pt: &[u8], profile: PadProfile) -> Vec<u8> {
+    let target = pad_bucket_for(pt.len() + 1, profile);
+    let mut out = Vec::with_capacity(target);
+    out.extend_from_slice(pt);
+    out.push(PAD_MARKER);
+    out.resize(target, 0);
+    out
 }
 
+/// Retire le bourrage de [`pad_plaintext`]; `None` si le marqueur manque.
+#[must_use]
+pub fn unpad_plaintext(padded: &[u8]) -> Option<&[u8]> {
+    let end = padded.iter().rposition(|&b| b != 0)?;
+    (padded[end] == PAD_MARKER).then(|| &padded[..end])
+}
+
+pub mod certificate;
+pub mod padding;
+pub mod seal;
+pub mod sealed_sender;
 pub mod transport;
+pub mod verifier;
+
+pub use certificate::SenderCertificate;
+pub use padding::{padme, PadBuckets, PadStats, MAX_CUSTOM_BUCKETS};
+pub use seal::{
+    open_envelope, seal_envelope, EnvelopeError, OpenedEnvelope, SenderClaims, ENVELOPE_MAX_SKEW_S,
+};
+pub use sealed_sender::{
+    open_sealed_sender, seal_sealed_sender, OpenedSealedSender, SEALED_SENDER_VERSION,
+};
+pub use verifier::{
+    Clock, ReplayCache, RevocationList, SystemClock, TokenVerifier, VerifiedSender,
+    DEFAULT_REPLAY_CAPACITY,
+};
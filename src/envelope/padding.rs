@@ -0,0 +1,100 @@
+//! Seaux de bourrage : Padmé, seaux personnalisés et statistiques de surcoût.
+
+use super::{pad_bucket_for, PadProfile};
+
+/// Nombre maximal de seaux d'un profil `Custom` (borne l'en-tête transport).
+pub const MAX_CUSTOM_BUCKETS: usize = 8;
+
+/// Padmé (Nikitin et al., PETS 2019) : arrondit `len` en ne gardant que les
+/// `⌊log2 ⌊log2 len⌋⌋ + 1` bits de poids fort de l'exposant.
+#[must_use]
+pub fn padme(len: usize) -> usize {
+    if len < 2 {
+        return len;
+    }
+    let e = usize::BITS - 1 - len.leading_zeros();
+    let s = u32::BITS - e.leading_zeros();
+    let mask = (1usize << (e - s)) - 1;
+    (len + mask) & !mask
+}
+
+/// Seaux strictement croissants fournis par l'appelant (`Copy`, taille fixe).
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct PadBuckets {
+    len: u8,
+    sizes: [u32; MAX_CUSTOM_BUCKETS],
+}
+
+impl PadBuckets {
+    /// `None` si vide, plus de [`MAX_CUSTOM_BUCKETS`] seaux, seau nul ou ordre
+    /// non strictement croissant.
+    #[must_use]
+    pub fn new(sizes: &[u32]) -> Option<Self> {
+        if sizes.is_empty()
+            || sizes.len() > MAX_CUSTOM_BUCKETS
+            || sizes[0] == 0
+            || sizes.windows(2).any(|w| w[0] >= w[1])
+        {
+            return None;
+        }
+        let mut out = Self {
+            len: u8::try_from(sizes.len()).ok()?,
+            sizes: [0; MAX_CUSTOM_BUCKETS],
+        };
+        out.sizes[..sizes.len()].copy_from_slice(sizes);
+        Some(out)
+    }
+    #[must_use]
+    pub fn as_slice(&self) -> &[u32] {
+        &self.sizes[..usize::from(self.len)]
+    }
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.as_slice().iter().map(|&s| s as usize)
+    }
+}
+
+/// Statistiques de surcoût cumulées, pour comparer des profils.
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub struct PadStats {
+    pub messages: u64,
+    pub payload_bytes: u64,
+    pub padded_bytes: u64,
+    /// Plus grand `(padded - payload) / payload` observé.
+    pub max_overhead_ratio: f64,
+}
+
+impl PadStats {
+    /// Enregistre un message de `payload` octets bourré à `padded` octets.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn record(&mut self, payload: usize, padded: usize) {
+        self.messages += 1;
+        self.payload_bytes += payload as u64;
+        self.padded_bytes += padded as u64;
+        if payload > 0 {
+            let r = padded.saturating_sub(payload) as f64 / payload as f64;
+            self.max_overhead_ratio = self.max_overhead_ratio.max(r);
+        }
+    }
+    /// Applique `profile` (comme `pad_plaintext`) à chaque longueur de clair.
+    #[must_use]
+    pub fn simulate(profile: PadProfile, lens: impl IntoIterator<Item = usize>) -> Self {
+        let mut st = Self::default();
+        for len in lens {
+            st.record(len, pad_bucket_for(len + 1, profile));
+        }
+        st
+    }
+    #[must_use]
+    pub fn overhead_bytes(&self) -> u64 {
+        self.padded_bytes.saturating_sub(self.payload_bytes)
+    }
+    /// Surcoût moyen pondéré (octets ajoutés / octets utiles).
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn overhead_ratio(&self) -> f64 {
+        if self.payload_bytes == 0 {
+            return 0.0;
+        }
+        self.overhead_bytes() as f64 / self.payload_bytes as f64
+    }
+}
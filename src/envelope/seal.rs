@@ -0,0 +1,170 @@
+//! Enveloppe réseau complète (ENVELOPE.md) : `header | token | frame`.
+//!
+//! `header` est l'en-tête transport ([`TransportHeader`]) dont `nonce` est
+//! celui du jeton. Le jeton est scellé sous `k_s` avec
+//! `AD = "hardlock/envelope" | header | SHA-256(frame)` : toute altération de
+//! l'en-tête ou de la trame invalide le jeton, et le jeton ne peut être
+//! détaché pour accompagner une autre trame.
+
+use super::transport::{encode_transport_header, split_transport, TransportHeader, TransportParts};
+use super::{parse_token_plaintext, token_plaintext, PadProfile};
+use crate::crypto::aeadx::{open_xchacha, rand_nonce, seal_xchacha, KEY_LEN, TAG_LEN};
+use crate::crypto::hpke_hybrid::HpkeError;
+use crate::error::ErrorCode;
+use crate::suites;
+use crate::wire::WireError;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+const ENVELOPE_AD_LABEL: &[u8] = b"hardlock/envelope";
+
+/// Écart toléré entre `ts_unix_s` de l'en-tête et l'horloge du vérificateur,
+/// dans les deux sens : une enveloppe plus ancienne (ou datée du futur) est
+/// rejetée même si son jeton n'a pas expiré.
+pub const ENVELOPE_MAX_SKEW_S: u64 = 300;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum EnvelopeError {
+    #[error(transparent)]
+    Wire(#[from] WireError),
+    #[error("sender token rejected")]
+    BadToken,
+    #[error("sender token expired")]
+    Expired,
+    #[error(transparent)]
+    Hpke(#[from] HpkeError),
+    #[error("sender certificate rejected")]
+    BadCertificate,
+    #[error("sender certificate expired")]
+    CertificateExpired,
+    #[error("sender token scope mismatch")]
+    ScopeMismatch,
+    #[error("sender token replayed")]
+    Replayed,
+    #[error("sender revoked")]
+    Revoked,
+    #[error("sender token scope too long")]
+    ScopeTooLong,
+    #[error("sender certificate device id too long")]
+    DeviceIdTooLong,
+    #[error("envelope timestamp outside freshness window")]
+    Stale,
+    #[error("replay cache full of unexpired tokens")]
+    ReplayCacheFull,
+}
+
+impl EnvelopeError {
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Wire(e) => e.code(),
+            Self::BadToken => ErrorCode::BadAead,
+            Self::Expired | Self::CertificateExpired => ErrorCode::TokenExpired,
+            Self::Hpke(e) => e.code(),
+            Self::BadCertificate => ErrorCode::BadCertificate,
+            Self::ScopeMismatch => ErrorCode::ScopeMismatch,
+            Self::Replayed => ErrorCode::TokenReplayed,
+            Self::Revoked => ErrorCode::SenderRevoked,
+            Self::ScopeTooLong | Self::DeviceIdTooLong => ErrorCode::LengthOverflow,
+            Self::Stale => ErrorCode::StaleEnvelope,
+            Self::ReplayCacheFull => ErrorCode::ReplayCacheFull,
+        }
+    }
+}
+
+/// Identité attestée par le jeton.
+#[derive(Clone, Copy, Debug)]
+pub struct SenderClaims<'a> {
+    pub sender_pub: [u8; 32],
+    pub scope: &'a [u8],
+    pub expiry_unix_s: u64,
+}
+
+/// Enveloppe vérifiée; `frame` est une tranche de l'entrée.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpenedEnvelope<'a> {
+    pub header: TransportHeader,
+    pub sender_pub: [u8; 32],
+    pub scope: Vec<u8>,
+    pub expiry_unix_s: u64,
+    pub frame: &'a [u8],
+}
+
+fn envelope_ad(head: &[u8], frame: &[u8]) -> Vec<u8> {
+    let mut ad = Vec::with_capacity(ENVELOPE_AD_LABEL.len() + head.len() + 32);
+    ad.extend_from_slice(ENVELOPE_AD_LABEL);
+    ad.extend_from_slice(head);
+    ad.extend_from_slice(&Sha256::digest(frame));
+    ad
+}
+
+/// Construit l'enveloppe autour d'une trame ratchet (`pack_message`...).
+///
+/// # Errors
+/// `ScopeTooLong` si `claims.scope` dépasse 65 535 octets.
+pub fn seal_envelope(
+    k_s: &[u8; KEY_LEN],
+    suite: u8,
+    ts_unix_s: u64,
+    profile: PadProfile,
+    claims: &SenderClaims<'_>,
+    frame: &[u8],
+) -> Result<Vec<u8>, EnvelopeError> {
+    let pt = token_plaintext(claims.expiry_unix_s, &claims.sender_pub, claims.scope)
+        .ok_or(EnvelopeError::ScopeTooLong)?;
+    let h = TransportHeader {
+        suite,
+        ts_unix_s,
+        nonce: rand_nonce(),
+        token_len: u32::try_from(pt.len() + TAG_LEN).map_err(|_| EnvelopeError::ScopeTooLong)?,
+        profile,
+    };
+    let mut out = encode_transport_header(&h, &[]);
+    let token = seal_xchacha(k_s, &h.nonce, &pt, &envelope_ad(&out, frame));
+    debug_assert_eq!(token.len(), h.token_len as usize);
+    out.reserve(token.len() + frame.len());
+    out.extend_from_slice(&token);
+    out.extend_from_slice(frame);
+    Ok(out)
+}
+
+/// Vérifie l'enveloppe et renvoie la trame interne et l'identité de l'émetteur.
+///
+/// # Errors
+/// `Wire(Malformed)` si l'en-tête ou le jeton est tronqué;
+/// `Wire(UnsupportedSuite)` si la suite est inconnue; `BadToken` si le jeton
+/// ne s'ouvre pas sous `k_s` (clé, en-tête ou trame altérés); `Expired` si
+/// `now_unix_s` dépasse l'expiration du jeton; `Stale` si `ts_unix_s` (authentifié
+/// par le jeton) s'écarte de `now_unix_s` de plus de [`ENVELOPE_MAX_SKEW_S`].
+pub fn open_envelope<'a>(
+    k_s: &[u8; KEY_LEN],
+    envelope: &'a [u8],
+    now_unix_s: u64,
+) -> Result<OpenedEnvelope<'a>, EnvelopeError> {
+    let TransportParts {
+        header,
+        head,
+        token,
+        rest: frame,
+    } = split_transport(envelope).ok_or(WireError::Malformed("truncated envelope header"))?;
+    if !suites::ALL.contains(&header.suite) {
+        return Err(WireError::UnsupportedSuite(header.suite).into());
+    }
+    let pt = open_xchacha(k_s, &header.nonce, token, &envelope_ad(head, frame))
+        .ok_or(EnvelopeError::BadToken)?;
+    let (expiry_unix_s, sender_pub, scope) =
+        parse_token_plaintext(&pt).ok_or(EnvelopeError::BadToken)?;
+    if now_unix_s > expiry_unix_s {
+        return Err(EnvelopeError::Expired);
+    }
+    if header.ts_unix_s.abs_diff(now_unix_s) > ENVELOPE_MAX_SKEW_S {
+        return Err(EnvelopeError::Stale);
+    }
+    Ok(OpenedEnvelope {
+        header,
+        sender_pub,
+        scope,
+        expiry_unix_s,
+        frame,
+    })
+}
@@ -0,0 +1,137 @@
+//! Sealed sender à certificats (ENVELOPE.md) : seul le destinataire apprend
+//! qui a émis le message.
+//!
+//! `sealed := ver:u8 = 1 | enc_e:32 | ct_e | enc_s:32 | ct_s`, en deux couches :
+//! - `ct_e := HPKE_Base(pk_dest, sender_x25519:32, aad = ver)` (info
+//!   `"hardlock/sealed-sender/v2/eph"`) cache la clé de l'émetteur;
+//! - `ct_s := HPKE_Auth(sk_sender, pk_dest, cert_len:u16 | cert | frame,
+//!   aad = ver | enc_e | ct_e)` (info `"hardlock/sealed-sender/v2"`)
+//!   authentifie l'émetteur par sa clé X25519 d'identité.
+//!
+//! Le relais ne voit que les `enc` (éphémères) et la taille. Le destinataire
+//! exige que la clé authentifiée soit `certificate.identity_x25519` : le
+//! certificat ne peut être rejoué par un autre émetteur. Il doit encore
+//! vérifier que `certificate.identity_ed25519` est l'identité du pair de la
+//! session ratchet qui déchiffre `frame`.
+
+use super::certificate::SenderCertificate;
+use super::EnvelopeError;
+use crate::crypto::aeadx::TAG_LEN;
+use crate::crypto::hpke_hybrid::{hpke_open_auth, hpke_open_base, hpke_seal_auth, hpke_seal_base};
+use crate::crypto::secret::SecretKey32;
+use crate::wire::WireError;
+use x25519_dalek::{PublicKey as X25519Public, StaticSecret};
+
+pub const SEALED_SENDER_VERSION: u8 = 1;
+const SEALED_SENDER_INFO: &[u8] = b"hardlock/sealed-sender/v2";
+const SEALED_SENDER_EPH_INFO: &[u8] = b"hardlock/sealed-sender/v2/eph";
+const ENC_LEN: usize = 32;
+/// `ver | enc_e | ct_e` : couche éphémère, AAD de la couche statique.
+const EPH_LAYER_LEN: usize = 1 + ENC_LEN + 32 + TAG_LEN;
+
+/// Message sealed sender ouvert; le certificat est vérifié.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpenedSealedSender {
+    pub certificate: SenderCertificate,
+    pub frame: Vec<u8>,
+}
+
+/// Chiffre `certificate` et `frame` vers la clé d'identité X25519 du
+/// destinataire, authentifiés par la clé X25519 d'identité `sender_sk`.
+///
+/// # Errors
+/// `BadCertificate` si `certificate.identity_x25519` n'est pas la clé publique
+/// de `sender_sk`; `DeviceIdTooLong` (voir [`SenderCertificate::to_bytes`]);
+/// `Hpke(BadKey)` si `recipient_x25519` est invalide.
+///
+/// # Panics
+/// Si le certificat encodé dépasse 65 535 octets (impossible : `device_id` ≤ 255 o).
+pub fn seal_sealed_sender(
+    sender_sk: &SecretKey32,
+    recipient_x25519: &[u8; 32],
+    certificate: &SenderCertificate,
+    frame: &[u8],
+) -> Result<Vec<u8>, EnvelopeError> {
+    let sender_pub = X25519Public::from(&StaticSecret::from(*sender_sk.expose())).to_bytes();
+    if sender_pub != certificate.identity_x25519 {
+        return Err(EnvelopeError::BadCertificate);
+    }
+    let (enc_e, ct_e) = hpke_seal_base(
+        recipient_x25519,
+        SEALED_SENDER_EPH_INFO,
+        &sender_pub,
+        &[SEALED_SENDER_VERSION],
+    )?;
+    let mut out = Vec::with_capacity(EPH_LAYER_LEN + ENC_LEN + TAG_LEN + frame.len() + 256);
+    out.push(SEALED_SENDER_VERSION);
+    out.extend_from_slice(&enc_e);
+    out.extend_from_slice(&ct_e);
+    debug_assert_eq!(out.len(), EPH_LAYER_LEN);
+
+    let cert = certificate.to_bytes()?;
+    let mut pt = Vec::with_capacity(2 + cert.len() + frame.len());
+    pt.extend_from_slice(&u16::try_from(cert.len()).expect("cert").to_le_bytes());
+    pt.extend_from_slice(&cert);
+    pt.extend_from_slice(frame);
+    let (enc_s, ct_s) = hpke_seal_auth(sender_sk, recipient_x25519, SEALED_SENDER_INFO, &pt, &out)?;
+    out.extend_from_slice(&enc_s);
+    out.extend_from_slice(&ct_s);
+    Ok(out)
+}
+
+/// Déchiffre avec la clé d'identité du destinataire, vérifie que l'émetteur
+/// authentifié est `certificate.identity_x25519`, puis vérifie le certificat
+/// contre la clé publique Ed25519 du serveur.
+///
+/// # Errors
+/// `Wire(ShortHeader | BadVersion | Malformed)` si le message ou le certificat
+/// est mal formé; `Hpke(_)` si une couche ne s'ouvre pas (dont émetteur non
+/// authentifié); `BadCertificate` si le certificat n'est pas celui de
+/// l'émetteur authentifié ou `CertificateExpired` (voir [`SenderCertificate::verify`]).
+pub fn open_sealed_sender(
+    recipient_sk: &SecretKey32,
+    server_pub: &[u8; 32],
+    sealed: &[u8],
+    now_unix_s: u64,
+) -> Result<OpenedSealedSender, EnvelopeError> {
+    if sealed.len() < EPH_LAYER_LEN + ENC_LEN {
+        return Err(WireError::ShortHeader.into());
+    }
+    if sealed[0] != SEALED_SENDER_VERSION {
+        return Err(WireError::BadVersion(u16::from(sealed[0])).into());
+    }
+    let (eph, stat) = sealed.split_at(EPH_LAYER_LEN);
+    let sender = hpke_open_base(
+        recipient_sk,
+        &eph[1..=ENC_LEN],
+        SEALED_SENDER_EPH_INFO,
+        &eph[1 + ENC_LEN..],
+        &[SEALED_SENDER_VERSION],
+    )?;
+    let sender_x25519: [u8; 32] = sender
+        .try_into()
+        .map_err(|_| WireError::Malformed("sealed sender key"))?;
+    let pt = hpke_open_auth(
+        &sender_x25519,
+        recipient_sk,
+        &stat[..ENC_LEN],
+        SEALED_SENDER_INFO,
+        &stat[ENC_LEN..],
+        eph,
+    )?;
+    let (len, rest) = pt
+        .split_first_chunk::<2>()
+        .ok_or(WireError::Malformed("sealed sender payload"))?;
+    let (cert, frame) = rest
+        .split_at_checked(usize::from(u16::from_le_bytes(*len)))
+        .ok_or(WireError::Malformed("sealed sender payload"))?;
+    let certificate = SenderCertificate::from_bytes(cert)?;
+    if certificate.identity_x25519 != sender_x25519 {
+        return Err(EnvelopeError::BadCertificate);
+    }
+    certificate.verify(server_pub, now_unix_s)?;
+    Ok(OpenedSealedSender {
+        certificate,
+        frame: frame.to_vec(),
+    })
+}
@@ -1,64 +1,120 @@
-use crate::envelope::PadProfile;
+//! En-tête transport de l'enveloppe (ENVELOPE.md) :
+//! `suite:u8 | ts:u64 | nonce:24 | token_len:u32 | profile[...] | token`.
+
+use crate::crypto::aeadx::XNONCE_LEN;
+use crate::envelope::{PadBuckets, PadProfile, MAX_CUSTOM_BUCKETS};
+
+/// Longueur de l'en-tête avant `profile`.
+const FIXED_LEN: usize = 1 + 8 + XNONCE_LEN + 4;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct TransportHeader {
+    pub suite: u8,
     pub ts_unix_s: u64,
+    /// Nonce du jeton sealed-sender.
+    pub nonce: [u8; XNONCE_LEN],
     pub token_len: u32,
     pub profile: PadProfile,
 }
 
-fn profile_to_u8(p: PadProfile) -> u8 {
+const PROFILE_PADME: u8 = 3;
+const PROFILE_CUSTOM: u8 = 4;
+
+/// `profile:u8`, suivi pour `Custom` de `n:u8 | n × bucket:u32`.
+fn put_profile(out: &mut Vec<u8>, p: PadProfile) {
     match p {
-        PadProfile::Stealth => 0,
-        PadProfile::Balanced => 1,
-        PadProfile::Throughput => 2,
+        PadProfile::Stealth => out.push(0),
+        PadProfile::Balanced => out.push(1),
+        PadProfile::Throughput => out.push(2),
+        PadProfile::Padme => out.push(PROFILE_PADME),
+        PadProfile::Custom(b) => {
+            out.push(PROFILE_CUSTOM);
+            let sizes = b.as_slice();
+            out.push(u8::try_from(sizes.len()).expect("bucket count fits u8"));
+            for s in sizes {
+                out.extend_from_slice(&s.to_le_bytes());
+            }
+        }
     }
 }
-fn u8_to_profile(x: u8) -> PadProfile {
-    match x {
+/// Renvoie le profil et le nombre d'octets lus; octet inconnu ⇒ `Throughput`.
+fn read_profile(b: &[u8]) -> Option<(PadProfile, usize)> {
+    let p = match *b.first()? {
         0 => PadProfile::Stealth,
         1 => PadProfile::Balanced,
+        PROFILE_PADME => PadProfile::Padme,
+        PROFILE_CUSTOM => {
+            let n = usize::from(*b.get(1)?);
+            if n > MAX_CUSTOM_BUCKETS || b.len() < 2 + 4 * n {
+                return None;
+            }
+            let sizes: Vec<u32> = b[2..2 + 4 * n]
+                .chunks_exact(4)
+                .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+                .collect();
+            return Some((PadProfile::Custom(PadBuckets::new(&sizes)?), 2 + 4 * n));
+        }
         _ => PadProfile::Throughput,
-    }
+    };
+    Some((p, 1))
 }
 
 #[must_use]
 pub fn encode_transport_header(h: &TransportHeader, token: &[u8]) -> Vec<u8> {
-    let mut out = Vec::with_capacity(8 + 4 + 1 + token.len());
+    let mut out = Vec::with_capacity(FIXED_LEN + 1 + token.len());
+    out.push(h.suite);
     out.extend_from_slice(&h.ts_unix_s.to_le_bytes());
+    out.extend_from_slice(&h.nonce);
     out.extend_from_slice(&h.token_len.to_le_bytes());
-    out.push(profile_to_u8(h.profile));
+    put_profile(&mut out, h.profile);
     out.extend_from_slice(token);
     out
 }
 
-#[must_use]
-pub fn decode_transport_header(b: &[u8]) -> Option<(TransportHeader, Vec<u8>)> {
-    if b.len() < 8 + 4 + 1 {
+/// En-tête décodé et découpage de l'entrée.
+pub(crate) struct TransportParts<'a> {
+    pub header: TransportHeader,
+    /// Octets encodés de l'en-tête, jeton exclu.
+    pub head: &'a [u8],
+    pub token: &'a [u8],
+    /// Ce qui suit le jeton.
+    pub rest: &'a [u8],
+}
+
+pub(crate) fn split_transport(b: &[u8]) -> Option<TransportParts<'_>> {
+    if b.len() < FIXED_LEN + 1 {
         return None;
     }
-    let mut ts_bytes = [0u8; 8];
-    ts_bytes.copy_from_slice(&b[0..8]);
-    let ts = u64::from_le_bytes(ts_bytes);
-
-    let mut len_bytes = [0u8; 4];
-    len_bytes.copy_from_slice(&b[8..12]);
-    let token_len_u32 = u32::from_le_bytes(len_bytes);
+    let ts = u64::from_le_bytes(b[1..9].try_into().ok()?);
+    let nonce: [u8; XNONCE_LEN] = b[9..9 + XNONCE_LEN].try_into().ok()?;
+    let token_len_u32 = u32::from_le_bytes(b[FIXED_LEN - 4..FIXED_LEN].try_into().ok()?);
     let token_len = token_len_u32 as usize;
 
-    let prof = u8_to_profile(b[12]);
+    let (prof, used) = read_profile(&b[FIXED_LEN..])?;
+    let (head, rest) = b.split_at(FIXED_LEN + used);
+    let (tok, rest) = rest.split_at_checked(token_len)?;
 
-    if b.len() < 13 + token_len {
-        return None;
-    }
-    let tok = b[13..13 + token_len].to_vec();
-
-    Some((
-        TransportHeader {
+    Some(TransportParts {
+        header: TransportHeader {
+            suite: b[0],
             ts_unix_s: ts,
+            nonce,
             token_len: token_len_u32,
             profile: prof,
         },
-        tok,
-    ))
+        head,
+        token: tok,
+        rest,
+    })
+}
+
+/// Variante empruntée : le jeton est une tranche de `b`.
+#[must_use]
+pub fn decode_transport_header_ref(b: &[u8]) -> Option<(TransportHeader, &[u8])> {
+    split_transport(b).map(|p| (p.header, p.token))
+}
+
+#[must_use]
+pub fn decode_transport_header(b: &[u8]) -> Option<(TransportHeader, Vec<u8>)> {
+    decode_transport_header_ref(b).map(|(h, tok)| (h, tok.to_vec()))
 }
@@ -0,0 +1,271 @@
+//! Vérification des jetons sealed-sender avec anti-rejeu et révocation.
+//!
+//! [`TokenVerifier`] enchaîne : AEAD sous `k_s`, expiration, portée attendue,
+//! révocation, puis rejeu. Le nonce n'entre dans le cache qu'une fois toutes
+//! les autres vérifications passées, un jeton refusé ne le pollue donc pas.
+
+use super::{open_envelope, token_open, EnvelopeError, OpenedEnvelope, SenderToken};
+use crate::crypto::aeadx::{KEY_LEN, XNONCE_LEN};
+use crate::crypto::secret::SecretKey32;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Nonces retenus par défaut.
+pub const DEFAULT_REPLAY_CAPACITY: usize = 4096;
+
+/// Source de l'heure courante (secondes Unix).
+pub trait Clock {
+    fn now_unix_s(&self) -> u64;
+}
+
+/// Horloge système.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_unix_s(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs())
+    }
+}
+
+impl<F: Fn() -> u64> Clock for F {
+    fn now_unix_s(&self) -> u64 {
+        self()
+    }
+}
+
+/// Cache borné des nonces de jetons déjà acceptés.
+///
+/// Plein, il oublie les jetons expirés (qui seraient refusés de toute façon).
+/// S'il ne reste que des jetons valides, il refuse le nouveau plutôt que
+/// d'oublier un nonce encore rejouable : la capacité doit couvrir le nombre
+/// de jetons attendus sur leur durée de validité.
+pub struct ReplayCache {
+    seen: HashSet<[u8; XNONCE_LEN]>,
+    order: VecDeque<([u8; XNONCE_LEN], u64)>,
+    capacity: usize,
+}
+
+impl ReplayCache {
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            seen: HashSet::new(),
+            order: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+    #[must_use]
+    pub fn contains(&self, nonce: &[u8; XNONCE_LEN]) -> bool {
+        self.seen.contains(nonce)
+    }
+
+    /// Enregistre `nonce`.
+    ///
+    /// # Errors
+    /// `Replayed` s'il était déjà présent; `ReplayCacheFull` si le cache est
+    /// plein de jetons non expirés à `now_unix_s`.
+    pub fn insert(
+        &mut self,
+        nonce: [u8; XNONCE_LEN],
+        expiry_unix_s: u64,
+        now_unix_s: u64,
+    ) -> Result<(), EnvelopeError> {
+        if self.seen.contains(&nonce) {
+            return Err(EnvelopeError::Replayed);
+        }
+        if self.seen.len() >= self.capacity {
+            self.purge_expired(now_unix_s);
+        }
+        if self.seen.len() >= self.capacity {
+            return Err(EnvelopeError::ReplayCacheFull);
+        }
+        self.seen.insert(nonce);
+        self.order.push_back((nonce, expiry_unix_s));
+        Ok(())
+    }
+
+    /// Oublie les nonces de jetons expirés à `now_unix_s`; renvoie leur nombre.
+    pub fn purge_expired(&mut self, now_unix_s: u64) -> usize {
+        let before = self.order.len();
+        let seen = &mut self.seen;
+        self.order.retain(|(n, expiry)| {
+            let keep = now_unix_s <= *expiry;
+            if !keep {
+                seen.remove(n);
+            }
+            keep
+        });
+        before - self.order.len()
+    }
+}
+
+impl Default for ReplayCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPLAY_CAPACITY)
+    }
+}
+
+/// Émetteurs révoqués, pour une portée ou pour toutes.
+#[derive(Clone, Debug, Default)]
+pub struct RevocationList {
+    all_scopes: HashSet<[u8; 32]>,
+    scoped: HashMap<[u8; 32], HashSet<Vec<u8>>>,
+}
+
+impl RevocationList {
+    /// Révoque les jetons de `sender_pub32` pour `scope` seulement.
+    pub fn revoke(&mut self, sender_pub32: [u8; 32], scope: &[u8]) {
+        self.scoped
+            .entry(sender_pub32)
+            .or_default()
+            .insert(scope.to_vec());
+    }
+    /// Révoque tous les jetons de `sender_pub32`, quelle que soit la portée.
+    pub fn revoke_sender(&mut self, sender_pub32: [u8; 32]) {
+        self.all_scopes.insert(sender_pub32);
+    }
+    /// Lève toute révocation (globale ou par portée) de `sender_pub32`.
+    pub fn reinstate(&mut self, sender_pub32: &[u8; 32]) {
+        self.all_scopes.remove(sender_pub32);
+        self.scoped.remove(sender_pub32);
+    }
+    #[must_use]
+    pub fn is_revoked(&self, sender_pub32: &[u8; 32], scope: &[u8]) -> bool {
+        self.all_scopes.contains(sender_pub32)
+            || self
+                .scoped
+                .get(sender_pub32)
+                .is_some_and(|s| s.contains(scope))
+    }
+}
+
+/// Émetteur attesté par un jeton accepté.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedSender {
+    pub sender_pub: [u8; 32],
+    pub scope: Vec<u8>,
+    pub expiry_unix_s: u64,
+}
+
+/// Vérificateur de jetons avec état (cache de rejeu, révocations).
+pub struct TokenVerifier<C = SystemClock> {
+    k_s: SecretKey32,
+    clock: C,
+    replay: ReplayCache,
+    revocations: RevocationList,
+}
+
+impl TokenVerifier {
+    /// Horloge système, cache de [`DEFAULT_REPLAY_CAPACITY`] nonces.
+    #[must_use]
+    pub fn new(k_s: [u8; KEY_LEN]) -> Self {
+        Self::with_clock(k_s, SystemClock, DEFAULT_REPLAY_CAPACITY)
+    }
+}
+
+impl<C: Clock> TokenVerifier<C> {
+    #[must_use]
+    pub fn with_clock(k_s: [u8; KEY_LEN], clock: C, replay_capacity: usize) -> Self {
+        Self {
+            k_s: SecretKey32::from_bytes(k_s),
+            clock,
+            replay: ReplayCache::new(replay_capacity),
+            revocations: RevocationList::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn revocations(&self) -> &RevocationList {
+        &self.revocations
+    }
+    pub fn revocations_mut(&mut self) -> &mut RevocationList {
+        &mut self.revocations
+    }
+    #[must_use]
+    pub fn replay_cache(&self) -> &ReplayCache {
+        &self.replay
+    }
+
+    /// Vérifie un [`SenderToken`] pour `expected_scope` et le consomme.
+    ///
+    /// # Errors
+    /// `BadToken`, `Expired`, `ScopeMismatch`, `Revoked`, `Replayed` ou
+    /// `ReplayCacheFull`.
+    pub fn verify(
+        &mut self,
+        token: &SenderToken,
+        expected_scope: &[u8],
+    ) -> Result<VerifiedSender, EnvelopeError> {
+        let now = self.clock.now_unix_s();
+        let (expiry_unix_s, sender_pub, scope) =
+            token_open(self.k_s.expose(), token).ok_or(EnvelopeError::BadToken)?;
+        if now > expiry_unix_s {
+            return Err(EnvelopeError::Expired);
+        }
+        self.admit(
+            token.nonce,
+            &sender_pub,
+            &scope,
+            expiry_unix_s,
+            expected_scope,
+            now,
+        )?;
+        Ok(VerifiedSender {
+            sender_pub,
+            scope,
+            expiry_unix_s,
+        })
+    }
+
+    /// [`open_envelope`] puis portée, révocation et rejeu (clé : nonce de l'en-tête).
+    ///
+    /// # Errors
+    /// Erreurs de [`open_envelope`], puis `ScopeMismatch`, `Revoked`, `Replayed`
+    /// ou `ReplayCacheFull`.
+    pub fn verify_envelope<'a>(
+        &mut self,
+        envelope: &'a [u8],
+        expected_scope: &[u8],
+    ) -> Result<OpenedEnvelope<'a>, EnvelopeError> {
+        let now = self.clock.now_unix_s();
+        let o = open_envelope(self.k_s.expose(), envelope, now)?;
+        self.admit(
+            o.header.nonce,
+            &o.sender_pub,
+            &o.scope,
+            o.expiry_unix_s,
+            expected_scope,
+            now,
+        )?;
+        Ok(o)
+    }
+
+    fn admit(
+        &mut self,
+        nonce: [u8; XNONCE_LEN],
+        sender_pub: &[u8; 32],
+        scope: &[u8],
+        expiry_unix_s: u64,
+        expected_scope: &[u8],
+        now: u64,
+    ) -> Result<(), EnvelopeError> {
+        if scope != expected_scope {
+            return Err(EnvelopeError::ScopeMismatch);
+        }
+        if self.revocations.is_revoked(sender_pub, scope) {
+            return Err(EnvelopeError::Revoked);
+        }
+        self.replay.insert(nonce, expiry_unix_s, now)
+    }
+}
@@ -0,0 +1,145 @@
+//! Codes d'erreur stables (PROTOCOL.md §5) et erreur agrégée de la crate.
+//!
+//! Chaque couche a son enum (`WireError`, `HpkeError`, `RatchetError`,
+//! `EnvelopeError`); toutes se projettent sur [`ErrorCode`], dont la valeur
+//! négée est le code de retour FFI.
+
+use crate::crypto::hpke_hybrid::HpkeError;
+use crate::envelope::EnvelopeError;
+use crate::ratchet::RatchetError;
+use crate::wire::WireError;
+use std::ffi::c_int;
+use thiserror::Error;
+
+/// Table des codes (valeurs stables, ne jamais renuméroter).
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    BadVersion = 1,
+    ShortHeader = 2,
+    TruncatedCt = 3,
+    BadAead = 4,
+    Desync = 5,
+    KeyChangePending = 6,
+    BadFrameType = 8,
+    MalformedFrame = 9,
+    UnsupportedSuite = 10,
+    BadKey = 11,
+    HpkeFailure = 12,
+    BinderMismatch = 13,
+    AuthMismatch = 14,
+    KemFailure = 15,
+    InvalidArgument = 16,
+    BufferTooSmall = 17,
+    LengthOverflow = 18,
+    BadPadding = 19,
+    FrameTooLarge = 20,
+    TokenExpired = 21,
+    BadCertificate = 22,
+    ScopeMismatch = 23,
+    TokenReplayed = 24,
+    SenderRevoked = 25,
+    HeaderModeMismatch = 26,
+    StaleEnvelope = 27,
+    ReplayCacheFull = 28,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 27] = [
+        Self::BadVersion,
+        Self::ShortHeader,
+        Self::TruncatedCt,
+        Self::BadAead,
+        Self::Desync,
+        Self::KeyChangePending,
+        Self::BadFrameType,
+        Self::MalformedFrame,
+        Self::UnsupportedSuite,
+        Self::BadKey,
+        Self::HpkeFailure,
+        Self::BinderMismatch,
+        Self::AuthMismatch,
+        Self::KemFailure,
+        Self::InvalidArgument,
+        Self::BufferTooSmall,
+        Self::LengthOverflow,
+        Self::BadPadding,
+        Self::FrameTooLarge,
+        Self::TokenExpired,
+        Self::BadCertificate,
+        Self::ScopeMismatch,
+        Self::TokenReplayed,
+        Self::SenderRevoked,
+        Self::HeaderModeMismatch,
+        Self::StaleEnvelope,
+        Self::ReplayCacheFull,
+    ];
+
+    /// Nom tel qu'il apparaît dans PROTOCOL.md §5.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::BadVersion => "BAD_VERSION",
+            Self::ShortHeader => "SHORT_HEADER",
+            Self::TruncatedCt => "TRUNCATED_CT",
+            Self::BadAead => "BAD_AEAD",
+            Self::Desync => "DESYNC",
+            Self::KeyChangePending => "KEY_CHANGE_PENDING",
+            Self::BadFrameType => "BAD_FRAME_TYPE",
+            Self::MalformedFrame => "MALFORMED_FRAME",
+            Self::UnsupportedSuite => "UNSUPPORTED_SUITE",
+            Self::BadKey => "BAD_KEY",
+            Self::HpkeFailure => "HPKE_FAILURE",
+            Self::BinderMismatch => "BINDER_MISMATCH",
+            Self::AuthMismatch => "AUTH_MISMATCH",
+            Self::KemFailure => "KEM_FAILURE",
+            Self::InvalidArgument => "INVALID_ARGUMENT",
+            Self::BufferTooSmall => "BUFFER_TOO_SMALL",
+            Self::LengthOverflow => "LENGTH_OVERFLOW",
+            Self::BadPadding => "BAD_PADDING",
+            Self::FrameTooLarge => "FRAME_TOO_LARGE",
+            Self::TokenExpired => "TOKEN_EXPIRED",
+            Self::BadCertificate => "BAD_CERTIFICATE",
+            Self::ScopeMismatch => "SCOPE_MISMATCH",
+            Self::TokenReplayed => "TOKEN_REPLAYED",
+            Self::SenderRevoked => "SENDER_REVOKED",
+            Self::HeaderModeMismatch => "HEADER_MODE_MISMATCH",
+            Self::StaleEnvelope => "STALE_ENVELOPE",
+            Self::ReplayCacheFull => "REPLAY_CACHE_FULL",
+        }
+    }
+    /// Code de retour FFI (toujours négatif).
+    #[must_use]
+    pub fn as_ffi(self) -> c_int {
+        -(self as i32)
+    }
+    #[must_use]
+    pub fn from_ffi(rc: c_int) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_ffi() == rc)
+    }
+}
+
+/// Erreur agrégée des couches protocolaires.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Wire(#[from] WireError),
+    #[error(transparent)]
+    Hpke(#[from] HpkeError),
+    #[error(transparent)]
+    Ratchet(#[from] RatchetError),
+    #[error(transparent)]
+    Envelope(#[from] EnvelopeError),
+}
+
+impl Error {
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Wire(e) => e.code(),
+            Self::Hpke(e) => e.code(),
+            Self::Ratchet(e) => e.code(),
+            Self::Envelope(e) => e.code(),
+        }
+    }
+}
@@ -2,11 +2,13 @@
 
 use crate::crypto::aeadx::XNONCE_LEN;
 use crate::crypto::hpke_hybrid::{hpke_accept, hpke_initiate};
-use crate::crypto::keys::X25519KeyPair;
+use crate::crypto::keys::{SecretKey32, X25519KeyPair};
+use crate::error::ErrorCode;
 use crate::ratchet;
 use crate::wire::{header_to_bytes, HEADER_LEN};
 use std::ffi::c_int;
 use std::ptr;
+use zeroize::Zeroize;
 
 #[repr(C)]
 pub struct RatchetHandle {
@@ -34,10 +36,10 @@ pub unsafe extern "C" fn hardlock_consts_nonce_len() -> usize {
 #[no_mangle]
 pub unsafe extern "C" fn hardlock_x25519_keygen(sk_out: *mut u8, pk_out: *mut u8) -> c_int {
     if sk_out.is_null() || pk_out.is_null() {
-        return -1;
+        return ErrorCode::InvalidArgument.as_ffi();
     }
     let kp = X25519KeyPair::generate();
-    ptr::copy_nonoverlapping(kp.sk.as_ptr(), sk_out, 32);
+    ptr::copy_nonoverlapping(kp.sk.expose().as_ptr(), sk_out, 32);
     ptr::copy_nonoverlapping(kp.pk.as_ptr(), pk_out, 32);
     0
 }
@@ -54,19 +56,20 @@ pub unsafe extern "C" fn hardlock_hpke_initiate(
     okm_out32: *mut u8,
 ) -> c_int {
     if pk_recipient32.is_null() || enc_out.is_null() || okm_out32.is_null() {
-        return -1;
+        return ErrorCode::InvalidArgument.as_ffi();
     }
     let mut pk = [0u8; 32];
     ptr::copy_nonoverlapping(pk_recipient32, pk.as_mut_ptr(), 32);
-    let Ok((enc, okm)) = hpke_initiate(&pk) else {
-        return -2;
+    let (enc, okm) = match hpke_initiate(&pk) {
+        Ok(v) => v,
+        Err(e) => return e.code().as_ffi(),
     };
     if enc.len() > enc_cap {
-        return -3;
+        return ErrorCode::BufferTooSmall.as_ffi();
     }
     ptr::copy_nonoverlapping(enc.as_ptr(), enc_out, enc.len());
     ptr::copy_nonoverlapping(okm.as_ptr(), okm_out32, 32);
-    c_int::try_from(enc.len()).unwrap_or(-5)
+    c_int::try_from(enc.len()).unwrap_or(ErrorCode::LengthOverflow.as_ffi())
 }
 
 /// # Safety
@@ -81,13 +84,16 @@ pub unsafe extern "C" fn hardlock_hpke_accept(
     okm_out32: *mut u8,
 ) -> c_int {
     if sk_recipient32.is_null() || enc_ptr.is_null() || okm_out32.is_null() {
-        return -1;
+        return ErrorCode::InvalidArgument.as_ffi();
     }
-    let mut sk = [0u8; 32];
-    ptr::copy_nonoverlapping(sk_recipient32, sk.as_mut_ptr(), 32);
+    let mut raw = [0u8; 32];
+    ptr::copy_nonoverlapping(sk_recipient32, raw.as_mut_ptr(), 32);
+    let sk = SecretKey32::from_bytes(raw);
+    raw.zeroize();
     let enc = std::slice::from_raw_parts(enc_ptr, enc_len);
-    let Ok(okm) = hpke_accept(&sk, enc) else {
-        return -2;
+    let okm = match hpke_accept(&sk, enc) {
+        Ok(v) => v,
+        Err(e) => return e.code().as_ffi(),
     };
     ptr::copy_nonoverlapping(okm.as_ptr(), okm_out32, 32);
     0
@@ -111,6 +117,8 @@ pub unsafe extern "C" fn hardlock_ratchet_new_initiator(
     ptr::copy_nonoverlapping(dh_s_priv32, sk.as_mut_ptr(), 32);
     ptr::copy_nonoverlapping(dh_r_pub32, pk.as_mut_ptr(), 32);
     let st = ratchet::init_initiator(okm, sk, pk);
+    okm.zeroize();
+    sk.zeroize();
     let ptr = Box::into_raw(Box::new(st));
     Box::into_raw(Box::new(RatchetHandle { ptr }))
 }
@@ -133,6 +141,8 @@ pub unsafe extern "C" fn hardlock_ratchet_new_responder(
     ptr::copy_nonoverlapping(dh_s_priv32, sk.as_mut_ptr(), 32);
     ptr::copy_nonoverlapping(dh_r_pub32, pk.as_mut_ptr(), 32);
     let st = ratchet::init_responder(okm, sk, pk);
+    okm.zeroize();
+    sk.zeroize();
     let ptr = Box::into_raw(Box::new(st));
     Box::into_raw(Box::new(RatchetHandle { ptr }))
 }
@@ -171,7 +181,7 @@ pub unsafe extern "C" fn hardlock_ratchet_encrypt(
         || nonce_out.is_null()
         || ct_out.is_null()
     {
-        return -1;
+        return ErrorCode::InvalidArgument.as_ffi();
     }
     let st = &mut *(*h).ptr;
     let ad = if ad_ptr.is_null() {
@@ -180,15 +190,18 @@ pub unsafe extern "C" fn hardlock_ratchet_encrypt(
         std::slice::from_raw_parts(ad_ptr, ad_len)
     };
     let pt = std::slice::from_raw_parts(pt_ptr, pt_len);
-    let (hdr, nonce, ct) = ratchet::encrypt(st, ad, pt);
+    let (hdr, nonce, ct) = match ratchet::encrypt(st, ad, pt) {
+        Ok(v) => v,
+        Err(e) => return e.code().as_ffi(),
+    };
     if ct.len() > ct_cap {
-        return -2;
+        return ErrorCode::BufferTooSmall.as_ffi();
     }
     let hb = header_to_bytes(&hdr);
     ptr::copy_nonoverlapping(hb.as_ptr(), header_out, HEADER_LEN);
     ptr::copy_nonoverlapping(nonce.as_ptr(), nonce_out, XNONCE_LEN);
     ptr::copy_nonoverlapping(ct.as_ptr(), ct_out, ct.len());
-    c_int::try_from(ct.len()).unwrap_or(-5)
+    c_int::try_from(ct.len()).unwrap_or(ErrorCode::LengthOverflow.as_ffi())
 }
 
 /// # Safety
@@ -213,7 +226,7 @@ pub unsafe extern "C" fn hardlock_ratchet_decrypt(
         || ct_ptr.is_null()
         || pt_out.is_null()
     {
-        return -1;
+        return ErrorCode::InvalidArgument.as_ffi();
     }
     let st = &mut *(*h).ptr;
     let ad = if ad_ptr.is_null() {
@@ -222,24 +235,22 @@ pub unsafe extern "C" fn hardlock_ratchet_decrypt(
         std::slice::from_raw_parts(ad_ptr, ad_len)
     };
     let hb = std::slice::from_raw_parts(header_ptr, HEADER_LEN);
-    let hdr = {
-        use crate::wire::header_from_bytes;
-        let Ok(hh) = header_from_bytes(hb) else {
-            return -2;
-        };
-        hh
+    let hdr = match crate::wire::header_from_bytes(hb) {
+        Ok(hh) => hh,
+        Err(e) => return e.code().as_ffi(),
     };
     let mut nonce = [0u8; XNONCE_LEN];
     ptr::copy_nonoverlapping(nonce_ptr, nonce.as_mut_ptr(), XNONCE_LEN);
     let ct = std::slice::from_raw_parts(ct_ptr, ct_len);
-    let Ok(pt) = ratchet::decrypt(st, ad, &hdr, &nonce, ct) else {
-        return -3;
+    let pt = match ratchet::decrypt(st, ad, &hdr, &nonce, ct) {
+        Ok(pt) => pt,
+        Err(e) => return e.code().as_ffi(),
     };
     if pt.len() > pt_cap {
-        return -4;
+        return ErrorCode::BufferTooSmall.as_ffi();
     }
     ptr::copy_nonoverlapping(pt.as_ptr(), pt_out, pt.len());
-    c_int::try_from(pt.len()).unwrap_or(-5)
+    c_int::try_from(pt.len()).unwrap_or(ErrorCode::LengthOverflow.as_ffi())
 }
 
 /// # Safety
@@ -257,7 +268,7 @@ pub unsafe extern "C" fn hardlock_token_build(
     ct_cap: usize,
 ) -> c_int {
     if k_s32.is_null() || sender_pub32.is_null() || ct_out.is_null() || nonce_out.is_null() {
-        return -1;
+        return ErrorCode::InvalidArgument.as_ffi();
     }
     let k_s = {
         let mut k = [0u8; 32];
@@ -276,7 +287,7 @@ pub unsafe extern "C" fn hardlock_token_build(
     };
     let t = crate::envelope::token_build(&k_s, expiry_unix_s, &sp, scope);
     if t.ct.len() > ct_cap {
-        return -2;
+        return ErrorCode::BufferTooSmall.as_ffi();
     }
     ptr::copy_nonoverlapping(
         t.nonce.as_ptr(),
@@ -284,7 +295,7 @@ pub unsafe extern "C" fn hardlock_token_build(
         crate::crypto::aeadx::XNONCE_LEN,
     );
     ptr::copy_nonoverlapping(t.ct.as_ptr(), ct_out, t.ct.len());
-    c_int::try_from(t.ct.len()).unwrap_or(-5)
+    c_int::try_from(t.ct.len()).unwrap_or(ErrorCode::LengthOverflow.as_ffi())
 }
 
 /// # Safety
@@ -299,7 +310,7 @@ pub unsafe extern "C" fn hardlock_token_verify(
     now_unix_s: u64,
 ) -> c_int {
     if k_s32.is_null() || nonce_ptr.is_null() || ct_ptr.is_null() {
-        return -1;
+        return ErrorCode::InvalidArgument.as_ffi();
     }
     let k_s = {
         let mut k = [0u8; 32];
@@ -315,13 +326,69 @@ pub unsafe extern "C" fn hardlock_token_verify(
     };
     match crate::envelope::token_verify(&k_s, &tok, now_unix_s) {
         Some(_) => 0,
-        None => -2,
+        None => ErrorCode::BadAead.as_ffi(),
     }
 }
 
+/// Bourre un clair avant chiffrement (`envelope::pad_plaintext`).
+///
+/// # Safety
+/// `pt_ptr..pt_ptr+pt_len` lisibles, `out_ptr` capacité `out_cap`.
+/// `profile` : 0=Stealth,1=Balanced,2=Throughput,3=Padme. Retourne taille écrite.
+#[no_mangle]
+pub unsafe extern "C" fn hardlock_pad_plaintext(
+    pt_ptr: *const u8,
+    pt_len: usize,
+    profile: i32,
+    out_ptr: *mut u8,
+    out_cap: usize,
+) -> c_int {
+    if pt_ptr.is_null() || out_ptr.is_null() {
+        return ErrorCode::InvalidArgument.as_ffi();
+    }
+    let pt = std::slice::from_raw_parts(pt_ptr, pt_len);
+    let p = match profile {
+        0 => crate::envelope::PadProfile::Stealth,
+        1 => crate::envelope::PadProfile::Balanced,
+        3 => crate::envelope::PadProfile::Padme,
+        _ => crate::envelope::PadProfile::Throughput,
+    };
+    let mut out = crate::envelope::pad_plaintext(pt, p);
+    if out.len() > out_cap {
+        out.zeroize();
+        return ErrorCode::BufferTooSmall.as_ffi();
+    }
+    ptr::copy_nonoverlapping(out.as_ptr(), out_ptr, out.len());
+    let rc = c_int::try_from(out.len()).unwrap_or(ErrorCode::LengthOverflow.as_ffi());
+    out.zeroize();
+    rc
+}
+
+/// Longueur du clair sans bourrage (`envelope::unpad_plaintext`); le clair
+/// est le préfixe `buf_ptr[..retour]`.
+///
+/// # Safety
+/// `buf_ptr..buf_ptr+buf_len` lisibles.
+#[no_mangle]
+pub unsafe extern "C" fn hardlock_unpad_plaintext(buf_ptr: *const u8, buf_len: usize) -> c_int {
+    if buf_ptr.is_null() {
+        return ErrorCode::InvalidArgument.as_ffi();
+    }
+    let buf = std::slice::from_raw_parts(buf_ptr, buf_len);
+    match crate::envelope::unpad_plaintext(buf) {
+        Some(pt) => c_int::try_from(pt.len()).unwrap_or(ErrorCode::LengthOverflow.as_ffi()),
+        None => ErrorCode::BadPadding.as_ffi(),
+    }
+}
+
+/// Ancienne API : complète une trame v1.1 de zéros jusqu'au palier du profil
+/// (bourrage externe, ignoré à la réception). Conservée pour compatibilité
+/// ABI; préférer `hardlock_pad_plaintext` avant chiffrement.
+///
 /// # Safety
 /// `frame_ptr..frame_ptr+frame_len` lisibles, `out_ptr` capacité `out_cap`.
 /// `profile` : 0=Stealth,1=Balanced,2=Throughput. Retourne taille écrite.
+#[deprecated(note = "le bourrage externe fuit la taille; utiliser hardlock_pad_plaintext")]
 #[no_mangle]
 pub unsafe extern "C" fn hardlock_apply_padding(
     frame_ptr: *const u8,
@@ -331,18 +398,24 @@ pub unsafe extern "C" fn hardlock_apply_padding(
     out_cap: usize,
 ) -> c_int {
     if frame_ptr.is_null() || out_ptr.is_null() {
-        return -1;
+        return ErrorCode::InvalidArgument.as_ffi();
+    }
+    let frame = std::slice::from_raw_parts(frame_ptr, frame_len);
+    match crate::wire::version::peek_version(frame) {
+        Ok(crate::wire::version::WIRE_V1_1) => {}
+        Ok(v) => return crate::wire::WireError::BadVersion(v).code().as_ffi(),
+        Err(e) => return e.code().as_ffi(),
     }
-    let f = std::slice::from_raw_parts(frame_ptr, frame_len).to_vec();
     let p = match profile {
         0 => crate::envelope::PadProfile::Stealth,
         1 => crate::envelope::PadProfile::Balanced,
         _ => crate::envelope::PadProfile::Throughput,
     };
-    let out = crate::envelope::apply_padding(f, p);
-    if out.len() > out_cap {
-        return -2;
+    let target = crate::envelope::pad_bucket_for(frame_len, p).max(frame_len);
+    if target > out_cap {
+        return ErrorCode::BufferTooSmall.as_ffi();
     }
-    ptr::copy_nonoverlapping(out.as_ptr(), out_ptr, out.len());
-    c_int::try_from(out.len()).unwrap_or(-5)
+    ptr::copy_nonoverlapping(frame.as_ptr(), out_ptr, frame_len);
+    ptr::write_bytes(out_ptr.add(frame_len), 0, target - frame_len);
+    c_int::try_from(target).unwrap_or(ErrorCode::LengthOverflow.as_ffi())
 }
@@ -29,7 +29,6 @@ impl DeviceIdentity {
     /// Panique si la clé Ed25519 est invalide (ne doit pas arriver).
     #[must_use]
     pub fn verifying_key(&self) -> VerifyingKey {
-        let pk: [u8; 32] = self.ed25519.pk.clone().try_into().unwrap();
-        ed25519_dalek::VerifyingKey::from_bytes(&pk).unwrap()
+        ed25519_dalek::VerifyingKey::from_bytes(&self.ed25519.pk).unwrap()
     }
 }
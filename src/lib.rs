@@ -3,12 +3,15 @@
 
 pub mod crypto;
 pub mod envelope;
+pub mod error;
 pub mod ffi;
 pub mod identity;
 pub mod kt;
+pub mod prekeys;
 pub mod ratchet;
 pub mod session;
 pub mod store;
+pub mod verification;
 pub mod wire;
 
 pub const HL_INFO: &str = "hardlock/v1.1";
@@ -17,5 +20,26 @@ pub const HL_VERSION: u16 = 0x0110;
 pub mod suites {
     pub const HL1_BASE: u8 = 0x01;
     pub const HL1_AUTH: u8 = 0x02;
+    pub const HL1_PSK: u8 = 0x03;
+    pub const HL1_AUTH_PSK: u8 = 0x04;
     pub const HL1_HYB: u8 = 0x11;
+
+    /// Suites reconnues par cette implémentation.
+    pub const ALL: [u8; 5] = [HL1_BASE, HL1_AUTH, HL1_PSK, HL1_AUTH_PSK, HL1_HYB];
+
+    /// Bit d'option : la session négocie les en-têtes chiffrés du ratchet.
+    /// Porté par l'octet de suite de l'INIT, donc couvert par le binder HPKE.
+    pub const FLAG_HEADER_ENC: u8 = 0x80;
+
+    /// Suite débarrassée de ses bits d'option.
+    #[must_use]
+    pub const fn base(suite: u8) -> u8 {
+        suite & !FLAG_HEADER_ENC
+    }
+
+    /// `true` si la suite négocie les en-têtes chiffrés.
+    #[must_use]
+    pub const fn header_encrypted(suite: u8) -> bool {
+        suite & FLAG_HEADER_ENC != 0
+    }
 }
@@ -0,0 +1,292 @@
+#![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+
+use crate::crypto::aeadx::XNONCE_LEN;
+use crate::crypto::keys::{SecretKey32, X25519KeyPair};
+use crate::identity::DeviceIdentity;
+use crate::ratchet::{self, state::Header, state::RatchetState};
+use ed25519_dalek::{Signature, VerifyingKey};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use x25519_dalek::{PublicKey as X25519Public, StaticSecret};
+use zeroize::Zeroize;
+
+const SPK_SIG_LABEL: &[u8] = b"hardlock/prekey/spk";
+const X3DH_INFO: &[u8] = b"hardlock/x3dh";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SignedPreKey {
+    pub id: u32,
+    pub kp: X25519KeyPair,
+    #[serde(with = "serde_bytes")]
+    pub sig: Vec<u8>,
+}
+
+fn spk_message(id: u32, pk: &[u8; 32]) -> Vec<u8> {
+    let mut m = Vec::with_capacity(SPK_SIG_LABEL.len() + 4 + 32);
+    m.extend_from_slice(SPK_SIG_LABEL);
+    m.extend_from_slice(&id.to_le_bytes());
+    m.extend_from_slice(pk);
+    m
+}
+
+impl SignedPreKey {
+    #[must_use]
+    pub fn generate(identity: &DeviceIdentity, id: u32) -> Self {
+        let kp = X25519KeyPair::generate();
+        let sig = identity.sign(&spk_message(id, &kp.public()));
+        Self {
+            id,
+            kp,
+            sig: sig.to_bytes().to_vec(),
+        }
+    }
+}
+
+/// Côté propriétaire : prekey signée courante + réserve de prekeys à usage unique.
+#[derive(Serialize, Deserialize)]
+pub struct PreKeyPool {
+    pub spk: SignedPreKey,
+    pub one_time: BTreeMap<u32, X25519KeyPair>,
+    pub next_otk_id: u32,
+    /// Prekey signée précédente, encore acceptée jusqu'à la rotation suivante
+    /// (ou [`PreKeyPool::retire_previous`]) pour les INIT déjà en vol.
+    #[serde(default)]
+    pub previous_spk: Option<SignedPreKey>,
+}
+impl PreKeyPool {
+    #[must_use]
+    pub fn generate(identity: &DeviceIdentity, spk_id: u32, n_one_time: u32) -> Self {
+        let mut pool = Self {
+            spk: SignedPreKey::generate(identity, spk_id),
+            one_time: BTreeMap::new(),
+            next_otk_id: 0,
+            previous_spk: None,
+        };
+        pool.replenish(n_one_time);
+        pool
+    }
+    /// Ajoute `n` prekeys à usage unique.
+    pub fn replenish(&mut self, n: u32) {
+        for _ in 0..n {
+            self.one_time
+                .insert(self.next_otk_id, X25519KeyPair::generate());
+            self.next_otk_id = self.next_otk_id.wrapping_add(1);
+        }
+    }
+    /// Remplace la prekey signée (rotation périodique). L'ancienne reste
+    /// acceptée pendant une période de rotation; celle d'avant est effacée.
+    pub fn rotate_signed(&mut self, identity: &DeviceIdentity, spk_id: u32) {
+        let old = std::mem::replace(&mut self.spk, SignedPreKey::generate(identity, spk_id));
+        self.previous_spk = Some(old);
+    }
+    /// Met fin à la période de grâce de la prekey signée précédente.
+    pub fn retire_previous(&mut self) {
+        self.previous_spk = None;
+    }
+    /// Prekey signée courante ou précédente portant `id`.
+    #[must_use]
+    pub fn signed_prekey(&self, id: u32) -> Option<&SignedPreKey> {
+        std::iter::once(&self.spk)
+            .chain(self.previous_spk.as_ref())
+            .find(|spk| spk.id == id)
+    }
+    /// Supprime la prekey à usage unique utilisée par `init`. À n'appeler
+    /// qu'après déchiffrement du premier message ratchet (voir [`x3dh_accept_first`]).
+    pub fn consume(&mut self, init: &PreKeyInit) {
+        if let Some(id) = init.one_time_id {
+            self.one_time.remove(&id);
+        }
+    }
+    /// Clés publiques à usage unique à publier sur l'annuaire.
+    #[must_use]
+    pub fn one_time_publics(&self) -> Vec<(u32, [u8; 32])> {
+        self.one_time
+            .iter()
+            .map(|(id, kp)| (*id, kp.public()))
+            .collect()
+    }
+    /// Bundle public avec la plus ancienne prekey à usage unique disponible.
+    #[must_use]
+    pub fn bundle(&self, identity: &DeviceIdentity) -> PreKeyBundle {
+        PreKeyBundle {
+            user_id: identity.user_id.clone(),
+            device_id: identity.device_id.clone(),
+            identity_ed25519: identity.verifying_key().to_bytes(),
+            identity_x25519: identity.x25519.public(),
+            spk_id: self.spk.id,
+            spk_pub: self.spk.kp.public(),
+            spk_sig: self.spk.sig.clone(),
+            one_time: self
+                .one_time
+                .iter()
+                .next()
+                .map(|(id, kp)| (*id, kp.public())),
+        }
+    }
+}
+
+/// Bundle publié par l'annuaire pour démarrer une session hors ligne.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PreKeyBundle {
+    pub user_id: String,
+    pub device_id: String,
+    pub identity_ed25519: [u8; 32],
+    pub identity_x25519: [u8; 32],
+    pub spk_id: u32,
+    pub spk_pub: [u8; 32],
+    #[serde(with = "serde_bytes")]
+    pub spk_sig: Vec<u8>,
+    pub one_time: Option<(u32, [u8; 32])>,
+}
+impl PreKeyBundle {
+    /// Vérifie la signature Ed25519 de la prekey signée (stricte : clés de
+    /// faible ordre et signatures malléables refusées).
+    #[must_use]
+    pub fn verify(&self) -> bool {
+        let Ok(vk) = VerifyingKey::from_bytes(&self.identity_ed25519) else {
+            return false;
+        };
+        let Ok(sig_bytes) = <[u8; 64]>::try_from(self.spk_sig.as_slice()) else {
+            return false;
+        };
+        let sig = Signature::from_bytes(&sig_bytes);
+        vk.verify_strict(&spk_message(self.spk_id, &self.spk_pub), &sig)
+            .is_ok()
+    }
+}
+
+/// Message initial envoyé par l'initiateur (en clair, à côté du premier message ratchet).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PreKeyInit {
+    pub identity_x25519: [u8; 32],
+    pub ephemeral: [u8; 32],
+    pub spk_id: u32,
+    pub one_time_id: Option<u32>,
+}
+
+fn dh(sk: &SecretKey32, pk: &[u8; 32]) -> [u8; 32] {
+    StaticSecret::from(*sk.expose())
+        .diffie_hellman(&X25519Public::from(*pk))
+        .to_bytes()
+}
+
+/// Données associées X3DH : `IK_A || IK_B` (clés X25519 d'identité).
+#[must_use]
+pub fn x3dh_ad(ik_a: &[u8; 32], ik_b: &[u8; 32]) -> [u8; 64] {
+    let mut ad = [0u8; 64];
+    ad[..32].copy_from_slice(ik_a);
+    ad[32..].copy_from_slice(ik_b);
+    ad
+}
+
+/// Session issue d'X3DH : état ratchet et AD à passer à chaque message.
+pub struct X3dhSession {
+    pub state: RatchetState,
+    pub ad: [u8; 64],
+}
+
+fn x3dh_kdf(parts: &[[u8; 32]], ad: &[u8; 64]) -> [u8; 32] {
+    let mut ikm = Vec::with_capacity(32 + parts.len() * 32);
+    ikm.extend_from_slice(&[0xFFu8; 32]);
+    for p in parts {
+        ikm.extend_from_slice(p);
+    }
+    let hk = Hkdf::<Sha256>::new(Some(&[0u8; 32]), &ikm);
+    let mut sk = [0u8; 32];
+    hk.expand(&[X3DH_INFO, ad].concat(), &mut sk).expect("hkdf");
+    ikm.zeroize();
+    sk
+}
+
+/// Initiateur : vérifie le bundle, dérive SK et initialise le ratchet.
+///
+/// Le ratchet démarre avec l'éphémère `EK_A` et la prekey signée `SPK_B`.
+pub fn x3dh_initiate(
+    identity: &DeviceIdentity,
+    bundle: &PreKeyBundle,
+) -> anyhow::Result<(PreKeyInit, X3dhSession)> {
+    if !bundle.verify() {
+        anyhow::bail!("bad prekey signature");
+    }
+    let ik_a = &identity.x25519.sk;
+    let ek = X25519KeyPair::generate();
+    let ek_a = &ek.sk;
+    let mut parts = vec![
+        dh(ik_a, &bundle.spk_pub),
+        dh(ek_a, &bundle.identity_x25519),
+        dh(ek_a, &bundle.spk_pub),
+    ];
+    if let Some((_, otk)) = bundle.one_time {
+        parts.push(dh(ek_a, &otk));
+    }
+    let ad = x3dh_ad(&identity.x25519.public(), &bundle.identity_x25519);
+    let sk = x3dh_kdf(&parts, &ad);
+    for p in &mut parts {
+        p.zeroize();
+    }
+    let init = PreKeyInit {
+        identity_x25519: identity.x25519.public(),
+        ephemeral: ek.public(),
+        spk_id: bundle.spk_id,
+        one_time_id: bundle.one_time.map(|(id, _)| id),
+    };
+    let state = ratchet::init_initiator(sk, *ek_a.expose(), bundle.spk_pub);
+    Ok((init, X3dhSession { state, ad }))
+}
+
+/// Récepteur : dérive SK et initialise le ratchet sans toucher à la réserve.
+///
+/// `PreKeyInit` n'est pas authentifié : la prekey à usage unique ne doit être
+/// supprimée ([`PreKeyPool::consume`]) qu'une fois le premier message déchiffré.
+pub fn x3dh_accept(
+    identity: &DeviceIdentity,
+    pool: &PreKeyPool,
+    init: &PreKeyInit,
+) -> anyhow::Result<X3dhSession> {
+    let spk = pool
+        .signed_prekey(init.spk_id)
+        .ok_or_else(|| anyhow::anyhow!("unknown signed prekey"))?;
+    let otk = match init.one_time_id {
+        Some(id) => Some(
+            pool.one_time
+                .get(&id)
+                .ok_or_else(|| anyhow::anyhow!("unknown one-time prekey"))?,
+        ),
+        None => None,
+    };
+    let ik_b = &identity.x25519.sk;
+    let spk_b = &spk.kp.sk;
+    let mut parts = vec![
+        dh(spk_b, &init.identity_x25519),
+        dh(ik_b, &init.ephemeral),
+        dh(spk_b, &init.ephemeral),
+    ];
+    if let Some(kp) = otk {
+        parts.push(dh(&kp.sk, &init.ephemeral));
+    }
+    let ad = x3dh_ad(&init.identity_x25519, &identity.x25519.public());
+    let sk = x3dh_kdf(&parts, &ad);
+    for p in &mut parts {
+        p.zeroize();
+    }
+    let state = ratchet::init_responder(sk, *spk_b.expose(), init.ephemeral);
+    Ok(X3dhSession { state, ad })
+}
+
+/// Récepteur : [`x3dh_accept`] puis déchiffre le premier message (AD X3DH);
+/// la prekey à usage unique n'est consommée qu'en cas de succès.
+pub fn x3dh_accept_first(
+    identity: &DeviceIdentity,
+    pool: &mut PreKeyPool,
+    init: &PreKeyInit,
+    header: &Header,
+    nonce: &[u8; XNONCE_LEN],
+    ct: &[u8],
+) -> anyhow::Result<(X3dhSession, Vec<u8>)> {
+    let mut session = x3dh_accept(identity, pool, init)?;
+    let pt = ratchet::decrypt(&mut session.state, &session.ad, header, nonce, ct)?;
+    pool.consume(init);
+    Ok((session, pt))
+}
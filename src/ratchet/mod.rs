@@ -2,8 +2,10 @@ pub mod schedule;
 pub mod state;
 
 use crate::crypto::aeadx::{open_xchacha, rand_nonce, seal_xchacha, XNONCE_LEN};
+use crate::envelope::{pad_plaintext, unpad_plaintext, PadProfile};
+use crate::error::ErrorCode;
 use crate::ratchet::state::{Header, RatchetState};
-use crate::wire::header_to_bytes;
+use crate::wire::{header_from_bytes, header_to_bytes, ENC_HEADER_LEN, HEADER_LEN};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -12,6 +14,26 @@ pub enum RatchetError {
     Decrypt,
     #[error("state desync")]
     Desync,
+    /// DESYNC : l'en-tête exige plus de clés sautées que `max_skip`.
+    #[error("state desync: too many skipped message keys")]
+    TooManySkipped,
+    /// Clair authentifié mais bourrage invalide (`envelope::unpad_plaintext`).
+    #[error("bad padding")]
+    BadPadding,
+    /// Appel en clair sur une session à en-têtes chiffrés, ou l'inverse.
+    #[error("header encryption mode mismatch")]
+    HeaderModeMismatch,
+}
+impl RatchetError {
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Decrypt => ErrorCode::BadAead,
+            Self::Desync | Self::TooManySkipped => ErrorCode::Desync,
+            Self::BadPadding => ErrorCode::BadPadding,
+            Self::HeaderModeMismatch => ErrorCode::HeaderModeMismatch,
+        }
+    }
 }
 
 /// Initialise le ratchet côté initiateur.
@@ -25,6 +47,48 @@ pub fn init_responder(root_key: [u8; 32], dh_s_priv: [u8; 32], dh_r_pub: [u8; 32
     RatchetState::init_responder(root_key, dh_s_priv, dh_r_pub)
 }
 
+/// Initialise le ratchet côté initiateur avec une clé ratchet éphémère.
+#[must_use]
+pub fn init_initiator_ephemeral(root_key: [u8; 32], dh_s_priv: [u8; 32]) -> RatchetState {
+    RatchetState::init_initiator_ephemeral(root_key, dh_s_priv)
+}
+/// Initialise le ratchet côté récepteur à partir de la clé ratchet de l'initiateur.
+#[must_use]
+pub fn init_responder_ephemeral(root_key: [u8; 32], dh_r_pub: [u8; 32]) -> RatchetState {
+    RatchetState::init_responder_ephemeral(root_key, dh_r_pub)
+}
+/// Message à en-tête chiffré : `(enc_header, nonce, ct)`.
+pub type HeMessage = (Vec<u8>, [u8; XNONCE_LEN], Vec<u8>);
+
+/// [`init_initiator_ephemeral`] en mode en-têtes chiffrés.
+#[must_use]
+pub fn init_initiator_ephemeral_he(root_key: [u8; 32], dh_s_priv: [u8; 32]) -> RatchetState {
+    RatchetState::init_initiator_ephemeral_he(root_key, dh_s_priv)
+}
+/// [`init_responder_ephemeral`] en mode en-têtes chiffrés.
+#[must_use]
+pub fn init_responder_ephemeral_he(root_key: [u8; 32], dh_r_pub: [u8; 32]) -> RatchetState {
+    RatchetState::init_responder_ephemeral_he(root_key, dh_r_pub)
+}
+/// Initialise le ratchet côté initiateur, en-têtes chiffrés.
+#[must_use]
+pub fn init_initiator_he(
+    root_key: [u8; 32],
+    dh_s_priv: [u8; 32],
+    dh_r_pub: [u8; 32],
+) -> RatchetState {
+    RatchetState::init_initiator_he(root_key, dh_s_priv, dh_r_pub)
+}
+/// Initialise le ratchet côté récepteur, en-têtes chiffrés.
+#[must_use]
+pub fn init_responder_he(
+    root_key: [u8; 32],
+    dh_s_priv: [u8; 32],
+    dh_r_pub: [u8; 32],
+) -> RatchetState {
+    RatchetState::init_responder_he(root_key, dh_s_priv, dh_r_pub)
+}
+
 fn make_aad(user_ad: &[u8], header: &Header) -> Vec<u8> {
     let hb = header_to_bytes(header);
     let mut aad = Vec::with_capacity(user_ad.len() + hb.len());
@@ -34,46 +98,216 @@ fn make_aad(user_ad: &[u8], header: &Header) -> Vec<u8> {
 }
 
 /// Chiffre un message (AEAD AAD = ad||header).
-#[must_use]
+///
+/// # Errors
+/// `HeaderModeMismatch` si la session a négocié les en-têtes chiffrés : l'en-tête
+/// partirait en clair (utiliser [`encrypt_he`]).
 pub fn encrypt(
     state: &mut RatchetState,
     ad: &[u8],
     plaintext: &[u8],
-) -> (Header, [u8; XNONCE_LEN], Vec<u8>) {
+) -> Result<(Header, [u8; XNONCE_LEN], Vec<u8>), RatchetError> {
+    if state.header_encrypted() {
+        return Err(RatchetError::HeaderModeMismatch);
+    }
     let (mk, header) = state.next_sending_key();
     let nonce = rand_nonce();
     let aad = make_aad(ad, &header);
     let ct = seal_xchacha(&mk, &nonce, plaintext, &aad);
-    (header, nonce, ct)
+    Ok((header, nonce, ct))
 }
 
 /// Déchiffre un message; rejette les replays.
 ///
+/// Transactionnel : le pas DH, les clés sautées et les compteurs sont calculés
+/// sur une copie de travail et ne sont validés qu'après vérification du tag AEAD.
+///
 /// # Errors
-/// Renvoie `Decrypt` si l’authentification AEAD échoue ou si un replay est détecté.
+/// Renvoie `Decrypt` si l’authentification AEAD échoue ou si un replay est détecté,
+/// `TooManySkipped` (DESYNC) si l'en-tête dépasse `max_skip`; l'état est alors inchangé.
+/// `HeaderModeMismatch` si la session a négocié les en-têtes chiffrés.
 pub fn decrypt(
     state: &mut RatchetState,
     ad: &[u8],
     header: &Header,
     nonce: &[u8; XNONCE_LEN],
     ct: &[u8],
+) -> Result<Vec<u8>, RatchetError> {
+    if state.header_encrypted() {
+        return Err(RatchetError::HeaderModeMismatch);
+    }
+    let aad = make_aad(ad, header);
+    decrypt_inner(state, &aad, header, nonce, ct, true)
+}
+
+fn decrypt_inner(
+    state: &mut RatchetState,
+    aad: &[u8],
+    header: &Header,
+    nonce: &[u8; XNONCE_LEN],
+    ct: &[u8],
+    may_step: bool,
 ) -> Result<Vec<u8>, RatchetError> {
     if state.was_delivered(header) {
         return Err(RatchetError::Decrypt);
     }
-    let aad = make_aad(ad, header);
-    if let Some(mk) = state.try_skipped(header) {
-        if let Some(pt) = open_xchacha(&mk, nonce, ct, &aad) {
-            state.mark_delivered(header);
-            return Ok(pt);
-        }
+    if let Some(mk) = state.peek_skipped(header) {
+        let pt = open_xchacha(&mk, nonce, ct, aad).ok_or(RatchetError::Decrypt)?;
+        let _ = state.try_skipped(header);
+        state.mark_delivered(header);
+        return Ok(pt);
+    }
+    if !may_step && header.dh_pub != state.dh_r_pub {
+        return Err(RatchetError::Decrypt);
     }
-    state.maybe_step(header);
-    if header.dh_pub == state.dh_r_pub && header.n > state.nr {
-        state.skip_recv_until(header.n);
+    let mut scratch = state.staged();
+    scratch.maybe_step(header)?;
+    if header.dh_pub == scratch.dh_r_pub && header.n > scratch.nr {
+        scratch.skip_recv_until(header.n)?;
     }
-    let mk = state.next_recv_key();
-    let out = open_xchacha(&mk, nonce, ct, &aad).ok_or(RatchetError::Decrypt)?;
+    let mk = scratch.next_recv_key();
+    let out = open_xchacha(&mk, nonce, ct, aad).ok_or(RatchetError::Decrypt)?;
+    state.commit(scratch);
     state.mark_delivered(header);
     Ok(out)
 }
+
+fn seal_header(hk: &[u8; 32], ad: &[u8], header: &Header) -> Vec<u8> {
+    let nonce = rand_nonce();
+    let ct = seal_xchacha(hk, &nonce, &header_to_bytes(header), ad);
+    let mut out = Vec::with_capacity(ENC_HEADER_LEN);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ct);
+    out
+}
+
+fn open_header(hk: &[u8; 32], ad: &[u8], enc_header: &[u8]) -> Option<Header> {
+    if enc_header.len() != ENC_HEADER_LEN || *hk == [0u8; 32] {
+        return None;
+    }
+    let mut nonce = [0u8; XNONCE_LEN];
+    nonce.copy_from_slice(&enc_header[..XNONCE_LEN]);
+    let hb = open_xchacha(hk, &nonce, &enc_header[XNONCE_LEN..], ad)?;
+    if hb.len() != HEADER_LEN {
+        return None;
+    }
+    header_from_bytes(&hb).ok()
+}
+
+/// Chiffre un message en mode en-têtes chiffrés.
+///
+/// Renvoie `(enc_header, nonce, ct)`; AEAD AAD = `ad || enc_header`.
+///
+/// # Errors
+/// `HeaderModeMismatch` si la session n'a pas négocié les en-têtes chiffrés.
+pub fn encrypt_he(
+    state: &mut RatchetState,
+    ad: &[u8],
+    plaintext: &[u8],
+) -> Result<HeMessage, RatchetError> {
+    if !state.header_encrypted() {
+        return Err(RatchetError::HeaderModeMismatch);
+    }
+    let (mk, header) = state.next_sending_key();
+    let hk_s = state.he.as_ref().map_or([0u8; 32], |he| he.hk_s);
+    let enc_header = seal_header(&hk_s, ad, &header);
+    let nonce = rand_nonce();
+    let aad = [ad, &enc_header[..]].concat();
+    let ct = seal_xchacha(&mk, &nonce, plaintext, &aad);
+    Ok((enc_header, nonce, ct))
+}
+
+/// Déchiffre un message en mode en-têtes chiffrés.
+///
+/// L'en-tête est essayé avec `HKr` (et les clés des chaînes précédentes), puis
+/// avec `NHKr` qui annonce un pas DH. Même garantie transactionnelle que [`decrypt`].
+///
+/// # Errors
+/// `Decrypt` si aucune clé d'en-tête ne convient, si l'AEAD échoue ou en cas de
+/// replay; `HeaderModeMismatch` si la session n'est pas en mode en-têtes chiffrés;
+/// `TooManySkipped` (DESYNC) si l'en-tête dépasse `max_skip`.
+pub fn decrypt_he(
+    state: &mut RatchetState,
+    ad: &[u8],
+    enc_header: &[u8],
+    nonce: &[u8; XNONCE_LEN],
+    ct: &[u8],
+) -> Result<Vec<u8>, RatchetError> {
+    let he = state.he.as_ref().ok_or(RatchetError::HeaderModeMismatch)?;
+    let current = std::iter::once(&he.hk_r)
+        .chain(he.prev_hk_r.iter().rev())
+        .find_map(|hk| open_header(hk, ad, enc_header));
+    let (header, may_step) = if let Some(h) = current {
+        (h, false)
+    } else {
+        let h = open_header(&he.nhk_r, ad, enc_header).ok_or(RatchetError::Decrypt)?;
+        if h.dh_pub == state.dh_r_pub {
+            return Err(RatchetError::Decrypt);
+        }
+        (h, true)
+    };
+    let aad = [ad, enc_header].concat();
+    decrypt_inner(state, &aad, &header, nonce, ct, may_step)
+}
+
+/// [`encrypt`] avec bourrage du clair selon `profile` avant scellement :
+/// la longueur réelle et le bourrage sont couverts par le tag AEAD.
+///
+/// # Errors
+/// Celles de [`encrypt`].
+pub fn encrypt_padded(
+    state: &mut RatchetState,
+    ad: &[u8],
+    plaintext: &[u8],
+    profile: PadProfile,
+) -> Result<(Header, [u8; XNONCE_LEN], Vec<u8>), RatchetError> {
+    encrypt(state, ad, &pad_plaintext(plaintext, profile))
+}
+
+/// [`decrypt`] puis retrait du bourrage.
+///
+/// # Errors
+/// Celles de [`decrypt`]; `BadPadding` si le clair authentifié est mal bourré
+/// (le message est alors consommé).
+pub fn decrypt_padded(
+    state: &mut RatchetState,
+    ad: &[u8],
+    header: &Header,
+    nonce: &[u8; XNONCE_LEN],
+    ct: &[u8],
+) -> Result<Vec<u8>, RatchetError> {
+    let padded = decrypt(state, ad, header, nonce, ct)?;
+    unpad_plaintext(&padded)
+        .map(<[u8]>::to_vec)
+        .ok_or(RatchetError::BadPadding)
+}
+
+/// [`encrypt_he`] avec bourrage du clair.
+///
+/// # Errors
+/// Celles de [`encrypt_he`].
+pub fn encrypt_he_padded(
+    state: &mut RatchetState,
+    ad: &[u8],
+    plaintext: &[u8],
+    profile: PadProfile,
+) -> Result<HeMessage, RatchetError> {
+    encrypt_he(state, ad, &pad_plaintext(plaintext, profile))
+}
+
+/// [`decrypt_he`] puis retrait du bourrage.
+///
+/// # Errors
+/// Celles de [`decrypt_he`]; `BadPadding` si le clair authentifié est mal bourré.
+pub fn decrypt_he_padded(
+    state: &mut RatchetState,
+    ad: &[u8],
+    enc_header: &[u8],
+    nonce: &[u8; XNONCE_LEN],
+    ct: &[u8],
+) -> Result<Vec<u8>, RatchetError> {
+    let padded = decrypt_he(state, ad, enc_header, nonce, ct)?;
+    unpad_plaintext(&padded)
+        .map(<[u8]>::to_vec)
+        .ok_or(RatchetError::BadPadding)
+}
@@ -34,3 +34,39 @@ pub fn kdf_ck(chain_key: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
     mk.copy_from_slice(&okm[32..]);
     (ck, mk)
 }
+
+/// KDF Root (mode en-têtes chiffrés) -> (RK', CK, `NHK`)
+///
+/// # Panics
+/// Panique si HKDF échoue (théoriquement impossible avec longueurs valides).
+#[must_use]
+pub fn kdf_rk_he(root_key: &[u8; 32], dh_out: &[u8; 32]) -> ([u8; 32], [u8; 32], [u8; 32]) {
+    let hk = Hkdf::<Sha256>::new(Some(root_key), dh_out);
+    let mut okm = [0u8; 96];
+    hk.expand(b"hardlock/ratchet/kdf_rk_he", &mut okm)
+        .expect("hkdf");
+    let mut rk = [0u8; 32];
+    let mut ck = [0u8; 32];
+    let mut nhk = [0u8; 32];
+    rk.copy_from_slice(&okm[..32]);
+    ck.copy_from_slice(&okm[32..64]);
+    nhk.copy_from_slice(&okm[64..]);
+    (rk, ck, nhk)
+}
+
+/// Clés d'en-tête initiales partagées -> (`HKa`, `NHKb`)
+///
+/// # Panics
+/// Panique si HKDF échoue (longueurs invalides).
+#[must_use]
+pub fn kdf_hk_init(root_key: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let hk = Hkdf::<Sha256>::new(None, root_key);
+    let mut okm = [0u8; 64];
+    hk.expand(b"hardlock/ratchet/header-keys", &mut okm)
+        .expect("hkdf");
+    let mut hka = [0u8; 32];
+    let mut nhkb = [0u8; 32];
+    hka.copy_from_slice(&okm[..32]);
+    nhkb.copy_from_slice(&okm[32..]);
+    (hka, nhkb)
+}
@@ -1,5 +1,7 @@
-use crate::ratchet::schedule::{kdf_ck, kdf_rk};
-use serde::{Deserialize, Serialize};
+use crate::ratchet::schedule::{kdf_ck, kdf_hk_init, kdf_rk, kdf_rk_he};
+use crate::ratchet::RatchetError;
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::{HashMap, VecDeque};
 use x25519_dalek::{PublicKey as X25519Public, StaticSecret};
 use zeroize::Zeroize;
@@ -16,6 +18,141 @@ pub struct SkippedKey {
     pub mk: [u8; 32],
 }
 
+/// Nombre maximal de clés sautées conservées (les plus anciennes sont évincées).
+pub const SKIPPED_MAX: usize = 2048;
+
+/// Nombre maximal de clés qu'un seul en-tête peut faire sauter sur une chaîne.
+pub const DEFAULT_MAX_SKIP: u32 = 1000;
+
+fn default_max_skip() -> u32 {
+    DEFAULT_MAX_SKIP
+}
+
+/// Cache borné des clés de message sautées, ordonné par insertion.
+///
+/// Sérialisé comme une liste `(dh_pub, n, mk)` dans l'ordre d'insertion afin
+/// que l'éviction FIFO survive à un cycle save/load.
+#[derive(Default)]
+pub struct SkippedKeys {
+    map: HashMap<([u8; 32], u32), [u8; 32]>,
+    order: VecDeque<([u8; 32], u32)>,
+}
+impl SkippedKeys {
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+    pub fn insert(&mut self, key: ([u8; 32], u32), mk: [u8; 32]) {
+        if self.map.contains_key(&key) {
+            return;
+        }
+        if self.map.len() >= SKIPPED_MAX {
+            if let Some(old) = self.order.pop_front() {
+                if let Some(mut mk_old) = self.map.remove(&old) {
+                    mk_old.zeroize();
+                }
+            }
+        }
+        self.order.push_back(key);
+        self.map.insert(key, mk);
+    }
+    #[must_use]
+    pub fn get(&self, key: &([u8; 32], u32)) -> Option<[u8; 32]> {
+        self.map.get(key).copied()
+    }
+    pub fn take(&mut self, key: &([u8; 32], u32)) -> Option<[u8; 32]> {
+        let mk = self.map.remove(key)?;
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            self.order.remove(pos);
+        }
+        Some(mk)
+    }
+    /// Déplace les entrées de `other` (dans leur ordre d'insertion) à la fin du cache.
+    pub fn append(&mut self, other: &mut Self) {
+        while let Some(key) = other.order.pop_front() {
+            if let Some(mut mk) = other.map.remove(&key) {
+                self.insert(key, mk);
+                mk.zeroize();
+            }
+        }
+    }
+}
+impl Serialize for SkippedKeys {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.order.len()))?;
+        for k in &self.order {
+            if let Some(mk) = self.map.get(k) {
+                seq.serialize_element(&(k.0, k.1, *mk))?;
+            }
+        }
+        seq.end()
+    }
+}
+impl<'de> Deserialize<'de> for SkippedKeys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = Vec::<([u8; 32], u32, [u8; 32])>::deserialize(deserializer)?;
+        let mut out = Self::default();
+        for (dh_pub, n, mk) in entries {
+            out.insert((dh_pub, n), mk);
+        }
+        Ok(out)
+    }
+}
+impl Drop for SkippedKeys {
+    fn drop(&mut self) {
+        for mk in self.map.values_mut() {
+            mk.zeroize();
+        }
+    }
+}
+
+/// Nombre de clés d'en-tête de réception des chaînes précédentes conservées
+/// (pour les messages en retard du mode en-têtes chiffrés).
+pub const PREV_HEADER_KEYS_MAX: usize = 8;
+
+/// Clés d'en-tête du mode « header encryption ».
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct HeaderKeys {
+    pub hk_s: [u8; 32],
+    pub hk_r: [u8; 32],
+    pub nhk_s: [u8; 32],
+    pub nhk_r: [u8; 32],
+    pub prev_hk_r: VecDeque<[u8; 32]>,
+}
+impl HeaderKeys {
+    fn rotate_recv(&mut self, nhk_r_next: [u8; 32]) {
+        if self.hk_r != [0u8; 32] {
+            if self.prev_hk_r.len() >= PREV_HEADER_KEYS_MAX {
+                if let Some(mut old) = self.prev_hk_r.pop_front() {
+                    old.zeroize();
+                }
+            }
+            self.prev_hk_r.push_back(self.hk_r);
+        }
+        self.hk_r = self.nhk_r;
+        self.nhk_r = nhk_r_next;
+    }
+    fn rotate_send(&mut self, nhk_s_next: [u8; 32]) {
+        self.hk_s = self.nhk_s;
+        self.nhk_s = nhk_s_next;
+    }
+}
+impl Drop for HeaderKeys {
+    fn drop(&mut self) {
+        self.hk_s.zeroize();
+        self.hk_r.zeroize();
+        self.nhk_s.zeroize();
+        self.nhk_r.zeroize();
+        for k in &mut self.prev_hk_r {
+            k.zeroize();
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DeliveredWindow {
     pub dh_pub: [u8; 32],
@@ -100,13 +237,15 @@ pub struct RatchetState {
     pub ns: u32,
     pub nr: u32,
     pub pn: u32,
-    #[serde(with = "serde_bytes")]
-    pub skipped_index: Vec<u8>,
-    #[serde(skip)]
-    pub skipped: HashMap<([u8; 32], u32), [u8; 32]>,
-    #[serde(skip)]
-    pub skipped_order: VecDeque<([u8; 32], u32)>,
+    #[serde(default)]
+    pub skipped: SkippedKeys,
     pub delivered_win: DeliveredWindow,
+    /// Absent des anciennes sauvegardes : [`DEFAULT_MAX_SKIP`].
+    #[serde(default = "default_max_skip")]
+    pub max_skip: u32,
+    /// `Some` si la session a négocié le mode en-têtes chiffrés.
+    #[serde(default)]
+    pub he: Option<HeaderKeys>,
 }
 impl core::fmt::Debug for RatchetState {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
@@ -137,10 +276,10 @@ impl RatchetState {
             ns: 0,
             nr: 0,
             pn: 0,
-            skipped_index: Vec::new(),
-            skipped: HashMap::new(),
-            skipped_order: VecDeque::new(),
+            skipped: SkippedKeys::default(),
             delivered_win: DeliveredWindow::new(dh_r_pub),
+            max_skip: DEFAULT_MAX_SKIP,
+            he: None,
         }
     }
     #[must_use]
@@ -161,10 +300,162 @@ impl RatchetState {
             ns: 0,
             nr: 0,
             pn: 0,
-            skipped_index: Vec::new(),
-            skipped: HashMap::new(),
-            skipped_order: VecDeque::new(),
+            skipped: SkippedKeys::default(),
+            delivered_win: DeliveredWindow::new(dh_r_pub),
+            max_skip: DEFAULT_MAX_SKIP,
+            he: None,
+        }
+    }
+    /// Initiateur avec une paire ratchet éphémère : la première chaîne d'envoi
+    /// est dérivée de `root_key` liée à `dh_s_pub`, sans DH sur une clé d'identité.
+    ///
+    /// `kdf_rk` reçoit ici une clé publique et non une sortie DH : le secret vient
+    /// entièrement de `root_key` (export HPKE, déjà lié à `dh_s_pub` par le
+    /// contexte `/ratchet:`), l'entrée publique ne sert que de séparation de
+    /// domaine. Le premier vrai DH ratchet a lieu au premier envoi du répondeur.
+    #[must_use]
+    pub fn init_initiator_ephemeral(root_key: [u8; 32], dh_s_priv: [u8; 32]) -> Self {
+        let dh_s_public = X25519Public::from(&StaticSecret::from(dh_s_priv)).to_bytes();
+        let (rk, ck_s) = kdf_rk(&root_key, &dh_s_public);
+        Self {
+            dh_s_priv,
+            dh_s_pub: dh_s_public,
+            dh_r_pub: [0u8; 32],
+            rk,
+            ck_s,
+            ck_r: [0u8; 32],
+            ns: 0,
+            nr: 0,
+            pn: 0,
+            skipped: SkippedKeys::default(),
+            delivered_win: DeliveredWindow::new([0u8; 32]),
+            max_skip: DEFAULT_MAX_SKIP,
+            he: None,
+        }
+    }
+    /// Récepteur face à [`RatchetState::init_initiator_ephemeral`] : `dh_r_pub`
+    /// est la clé ratchet éphémère de l'initiateur. Aucune paire locale n'est
+    /// tirée ici : `ck_s` étant vide, le premier envoi passe par
+    /// `dh_ratchet_send`, qui tire la paire et fait le premier DH.
+    #[must_use]
+    pub fn init_responder_ephemeral(root_key: [u8; 32], dh_r_pub: [u8; 32]) -> Self {
+        let (rk, ck_r) = kdf_rk(&root_key, &dh_r_pub);
+        Self {
+            dh_s_priv: [0u8; 32],
+            dh_s_pub: [0u8; 32],
+            dh_r_pub,
+            rk,
+            ck_s: [0u8; 32],
+            ck_r,
+            ns: 0,
+            nr: 0,
+            pn: 0,
+            skipped: SkippedKeys::default(),
             delivered_win: DeliveredWindow::new(dh_r_pub),
+            max_skip: DEFAULT_MAX_SKIP,
+            he: None,
+        }
+    }
+    /// [`RatchetState::init_initiator_ephemeral`] en mode en-têtes chiffrés.
+    #[must_use]
+    pub fn init_initiator_ephemeral_he(root_key: [u8; 32], dh_s_priv: [u8; 32]) -> Self {
+        let mut st = Self::init_initiator_ephemeral(root_key, dh_s_priv);
+        let (rk, ck_s, nhk_s) = kdf_rk_he(&root_key, &st.dh_s_pub);
+        let (hka, nhkb) = kdf_hk_init(&root_key);
+        st.rk = rk;
+        st.ck_s = ck_s;
+        st.he = Some(HeaderKeys {
+            hk_s: hka,
+            hk_r: [0u8; 32],
+            nhk_s,
+            nhk_r: nhkb,
+            prev_hk_r: VecDeque::new(),
+        });
+        st
+    }
+    /// [`RatchetState::init_responder_ephemeral`] en mode en-têtes chiffrés.
+    #[must_use]
+    pub fn init_responder_ephemeral_he(root_key: [u8; 32], dh_r_pub: [u8; 32]) -> Self {
+        let mut st = Self::init_responder_ephemeral(root_key, dh_r_pub);
+        let (rk, ck_r, nhk_r) = kdf_rk_he(&root_key, &dh_r_pub);
+        let (hka, nhkb) = kdf_hk_init(&root_key);
+        st.rk = rk;
+        st.ck_r = ck_r;
+        st.he = Some(HeaderKeys {
+            hk_s: [0u8; 32],
+            hk_r: hka,
+            nhk_s: nhkb,
+            nhk_r,
+            prev_hk_r: VecDeque::new(),
+        });
+        st
+    }
+    /// Initiateur en mode en-têtes chiffrés (`HKs = HKa`, `NHKr = NHKb`).
+    #[must_use]
+    pub fn init_initiator_he(root_key: [u8; 32], dh_s_priv: [u8; 32], dh_r_pub: [u8; 32]) -> Self {
+        let mut st = Self::init_initiator(root_key, dh_s_priv, dh_r_pub);
+        let dh_out = StaticSecret::from(dh_s_priv)
+            .diffie_hellman(&X25519Public::from(dh_r_pub))
+            .to_bytes();
+        let (rk, ck_s, nhk_s) = kdf_rk_he(&root_key, &dh_out);
+        let (hka, nhkb) = kdf_hk_init(&root_key);
+        st.rk = rk;
+        st.ck_s = ck_s;
+        st.he = Some(HeaderKeys {
+            hk_s: hka,
+            hk_r: [0u8; 32],
+            nhk_s,
+            nhk_r: nhkb,
+            prev_hk_r: VecDeque::new(),
+        });
+        st
+    }
+    /// Récepteur en mode en-têtes chiffrés (`HKr = HKa`, `NHKs = NHKb`).
+    #[must_use]
+    pub fn init_responder_he(root_key: [u8; 32], dh_s_priv: [u8; 32], dh_r_pub: [u8; 32]) -> Self {
+        let mut st = Self::init_responder(root_key, dh_s_priv, dh_r_pub);
+        let dh_out = StaticSecret::from(dh_s_priv)
+            .diffie_hellman(&X25519Public::from(dh_r_pub))
+            .to_bytes();
+        let (rk, ck_r, nhk_r) = kdf_rk_he(&root_key, &dh_out);
+        let (hka, nhkb) = kdf_hk_init(&root_key);
+        st.rk = rk;
+        st.ck_r = ck_r;
+        st.he = Some(HeaderKeys {
+            hk_s: [0u8; 32],
+            hk_r: hka,
+            nhk_s: nhkb,
+            nhk_r,
+            prev_hk_r: VecDeque::new(),
+        });
+        st
+    }
+    #[must_use]
+    pub fn header_encrypted(&self) -> bool {
+        self.he.is_some()
+    }
+    fn kdf_root_recv(&mut self, dh_out: &[u8; 32]) -> [u8; 32] {
+        if let Some(he) = self.he.as_mut() {
+            let (rk, ck, nhk) = kdf_rk_he(&self.rk, dh_out);
+            he.rotate_recv(nhk);
+            self.rk = rk;
+            ck
+        } else {
+            let (rk, ck) = kdf_rk(&self.rk, dh_out);
+            self.rk = rk;
+            ck
+        }
+    }
+    fn kdf_root_send(&mut self, dh_out: &[u8; 32]) -> [u8; 32] {
+        if let Some(he) = self.he.as_mut() {
+            let (rk, ck, nhk) = kdf_rk_he(&self.rk, dh_out);
+            he.rotate_send(nhk);
+            self.rk = rk;
+            ck
+        } else {
+            let (rk, ck) = kdf_rk(&self.rk, dh_out);
+            self.rk = rk;
+            ck
         }
     }
     #[must_use]
@@ -189,40 +480,82 @@ impl RatchetState {
         self.nr = self.nr.wrapping_add(1);
         mk
     }
-    pub fn try_skipped(&mut self, header: &Header) -> Option<[u8; 32]> {
-        if let Some(mk) = self.skipped.remove(&(header.dh_pub, header.n)) {
-            if let Some(pos) = self
-                .skipped_order
-                .iter()
-                .position(|k| *k == (header.dh_pub, header.n))
-            {
-                self.skipped_order.remove(pos);
-            }
-            return Some(mk);
+    /// Copie de travail pour un déchiffrement transactionnel : même état,
+    /// cache de clés sautées vide (seules les nouvelles clés y seront ajoutées).
+    #[must_use]
+    pub fn staged(&self) -> Self {
+        Self {
+            dh_s_priv: self.dh_s_priv,
+            dh_s_pub: self.dh_s_pub,
+            dh_r_pub: self.dh_r_pub,
+            rk: self.rk,
+            ck_s: self.ck_s,
+            ck_r: self.ck_r,
+            ns: self.ns,
+            nr: self.nr,
+            pn: self.pn,
+            skipped: SkippedKeys::default(),
+            delivered_win: self.delivered_win.clone(),
+            max_skip: self.max_skip,
+            he: self.he.clone(),
         }
-        None
+    }
+    /// Valide une copie issue de [`RatchetState::staged`] après authentification.
+    pub fn commit(&mut self, mut staged: Self) {
+        self.dh_s_priv = staged.dh_s_priv;
+        self.dh_s_pub = staged.dh_s_pub;
+        self.dh_r_pub = staged.dh_r_pub;
+        self.rk = staged.rk;
+        self.ck_s = staged.ck_s;
+        self.ck_r = staged.ck_r;
+        self.ns = staged.ns;
+        self.nr = staged.nr;
+        self.pn = staged.pn;
+        self.skipped.append(&mut staged.skipped);
+        self.delivered_win = staged.delivered_win.clone();
+        self.he = staged.he.take();
+    }
+    #[must_use]
+    pub fn peek_skipped(&self, header: &Header) -> Option<[u8; 32]> {
+        self.skipped.get(&(header.dh_pub, header.n))
+    }
+    pub fn try_skipped(&mut self, header: &Header) -> Option<[u8; 32]> {
+        self.skipped.take(&(header.dh_pub, header.n))
     }
     fn add_skipped(&mut self, key: ([u8; 32], u32), mk: [u8; 32]) {
-        const SKIPPED_MAX: usize = 2048;
-        if self.skipped.len() >= SKIPPED_MAX {
-            if let Some(old) = self.skipped_order.pop_front() {
-                self.skipped.remove(&old);
-            }
-        }
-        self.skipped_order.push_back(key);
         self.skipped.insert(key, mk);
     }
-    pub fn skip_recv_until(&mut self, target_n: u32) {
+    /// Limite le nombre de clés dérivées d'avance sur une même chaîne de réception.
+    pub fn set_max_skip(&mut self, max_skip: u32) {
+        self.max_skip = max_skip;
+    }
+    fn check_skip(&self, target_n: u32) -> Result<(), RatchetError> {
+        if target_n > self.nr && target_n - self.nr > self.max_skip {
+            return Err(RatchetError::TooManySkipped);
+        }
+        Ok(())
+    }
+    /// # Errors
+    /// `TooManySkipped` si `target_n` dépasse `nr + max_skip` (aucune clé dérivée).
+    pub fn skip_recv_until(&mut self, target_n: u32) -> Result<(), RatchetError> {
+        self.check_skip(target_n)?;
         while self.nr < target_n {
             let (ck_r_next, mk) = kdf_ck(&self.ck_r);
             self.ck_r = ck_r_next;
             self.add_skipped((self.dh_r_pub, self.nr), mk);
             self.nr = self.nr.wrapping_add(1);
         }
+        Ok(())
     }
-    pub fn maybe_step(&mut self, header: &Header) -> bool {
+    /// # Errors
+    /// `TooManySkipped` si `header.pn` ou `header.n` exigent plus de `max_skip` clés.
+    pub fn maybe_step(&mut self, header: &Header) -> Result<bool, RatchetError> {
         if header.dh_pub == self.dh_r_pub {
-            return false;
+            return Ok(false);
+        }
+        self.check_skip(header.pn)?;
+        if header.n > self.max_skip {
+            return Err(RatchetError::TooManySkipped);
         }
         while self.nr < header.pn {
             let (ck_r_next, mk) = kdf_ck(&self.ck_r);
@@ -236,16 +569,14 @@ impl RatchetState {
         self.dh_r_pub = header.dh_pub;
         self.delivered_win = DeliveredWindow::new(header.dh_pub);
         self.dh_ratchet_recv();
-        true
+        Ok(true)
     }
     fn dh_ratchet_recv(&mut self) {
         let sk = x25519_dalek::StaticSecret::from(self.dh_s_priv);
         let dh_out = sk
             .diffie_hellman(&x25519_dalek::PublicKey::from(self.dh_r_pub))
             .to_bytes();
-        let (rk_next, ck_r) = kdf_rk(&self.rk, &dh_out);
-        self.rk = rk_next;
-        self.ck_r = ck_r;
+        self.ck_r = self.kdf_root_recv(&dh_out);
         let rng = rand::rngs::OsRng;
         let new_s = x25519_dalek::StaticSecret::random_from_rng(rng);
         self.dh_s_priv = new_s.to_bytes();
@@ -253,9 +584,7 @@ impl RatchetState {
         let dh_out2 = new_s
             .diffie_hellman(&x25519_dalek::PublicKey::from(self.dh_r_pub))
             .to_bytes();
-        let (rk_next2, ck_s) = kdf_rk(&self.rk, &dh_out2);
-        self.rk = rk_next2;
-        self.ck_s = ck_s;
+        self.ck_s = self.kdf_root_send(&dh_out2);
     }
     fn dh_ratchet_send(&mut self) {
         let rng = rand::rngs::OsRng;
@@ -265,9 +594,7 @@ impl RatchetState {
         let dh_out = new_s
             .diffie_hellman(&x25519_dalek::PublicKey::from(self.dh_r_pub))
             .to_bytes();
-        let (rk_next, ck_s) = kdf_rk(&self.rk, &dh_out);
-        self.rk = rk_next;
-        self.ck_s = ck_s;
+        self.ck_s = self.kdf_root_send(&dh_out);
         self.pn = self.ns;
         self.ns = 0;
     }
@@ -0,0 +1,48 @@
+#![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+
+use crate::crypto::hpke_hybrid::{hpke_accept_with_binder_ctx, hpke_initiate_with_binder_ctx};
+use crate::crypto::keys::X25519KeyPair;
+use crate::identity::DeviceIdentity;
+use crate::ratchet::{self, state::RatchetState};
+use crate::suites;
+use crate::wire::handshake::{decode_init_v3, encode_init_v3};
+use crate::wire::WireError;
+
+const RATCHET_CTX: &[u8] = b"/ratchet:";
+
+pub(super) fn ratchet_ctx(ratchet_pub: &[u8; 32]) -> Vec<u8> {
+    [RATCHET_CTX, &ratchet_pub[..]].concat()
+}
+
+/// Démarre une session vers `pk_recipient` (clé X25519 d'identité du pair).
+///
+/// Tire une paire ratchet éphémère, la lie aux exports HPKE et la transporte
+/// dans la trame INIT v3. Renvoie `(frame, state)`.
+pub fn bootstrap_initiator(pk_recipient: &[u8; 32]) -> anyhow::Result<(Vec<u8>, RatchetState)> {
+    let eph = X25519KeyPair::generate();
+    let ratchet_pub = eph.public();
+    let (enc, okm, binder) =
+        hpke_initiate_with_binder_ctx(pk_recipient, suites::HL1_BASE, &ratchet_ctx(&ratchet_pub))?;
+    let frame = encode_init_v3(suites::HL1_BASE, &enc, &ratchet_pub, &binder);
+    let dh_s_priv = *eph.sk.expose();
+    Ok((frame, ratchet::init_initiator_ephemeral(okm, dh_s_priv)))
+}
+
+/// Accepte une trame INIT v3; la clé d'identité ne sert qu'au décapsulage HPKE.
+pub fn bootstrap_responder(
+    identity: &DeviceIdentity,
+    frame: &[u8],
+) -> anyhow::Result<RatchetState> {
+    let (suite, enc, ratchet_pub, binder) = decode_init_v3(frame)?;
+    if suite != suites::HL1_BASE {
+        return Err(WireError::UnsupportedSuite(suite).into());
+    }
+    let okm = hpke_accept_with_binder_ctx(
+        suite,
+        &identity.x25519.sk,
+        &enc,
+        &ratchet_ctx(&ratchet_pub),
+        &binder,
+    )?;
+    Ok(ratchet::init_responder_ephemeral(okm, ratchet_pub))
+}
@@ -0,0 +1,385 @@
+#![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+
+use super::bootstrap::ratchet_ctx;
+use super::Session;
+use crate::crypto::aeadx::{open_xchacha, seal_xchacha, XNONCE_LEN};
+use crate::crypto::hpke_hybrid::{
+    hpke_accept_confirm_ctx, hpke_initiate_confirm_ctx, identity_seal_key, key_confirmation_mac,
+};
+use crate::crypto::keys::X25519KeyPair;
+use crate::identity::DeviceIdentity;
+use crate::ratchet::{self, state::RatchetState};
+use crate::suites;
+use crate::wire::handshake::{
+    decode_init_hidden, decode_init_signed, decode_init_v3, decode_response, encode_init_hidden,
+    encode_init_signed, encode_init_v3, encode_response, HiddenInit, SealedIdentity, SignedInit,
+};
+use crate::wire::WireError;
+use ed25519_dalek::{Signature, VerifyingKey};
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+
+pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+
+const INIT_SIG_LABEL: &[u8] = b"hardlock/init-sig";
+
+/// Clé de scellement à usage unique (une par poignée de main) : nonce nul.
+const ID_SEAL_NONCE: [u8; XNONCE_LEN] = [0u8; XNONCE_LEN];
+
+/// Forme de la trame INIT émise par l'initiateur.
+#[derive(Clone, Copy)]
+pub enum InitKind<'a> {
+    /// INIT v3 anonyme.
+    Plain,
+    /// INIT signé par l'identité (voir [`Handshake::initiate_signed`]).
+    Signed(&'a DeviceIdentity),
+    /// INIT à identité scellée (voir [`Handshake::initiate_hidden`]).
+    Hidden(&'a DeviceIdentity),
+}
+
+/// Identité de l'initiateur authentifiée par la signature d'un INIT signé.
+///
+/// La liaison `user_id` ↔ `identity_ed25519` reste à vérifier côté annuaire (KT).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeerIdentity {
+    pub user_id: String,
+    pub device_id: String,
+    pub identity_ed25519: [u8; 32],
+    pub identity_x25519: [u8; 32],
+}
+
+/// Décode un INIT signé et vérifie sa signature Ed25519 (sans décapsuler HPKE).
+pub fn verify_signed_init(frame: &[u8]) -> anyhow::Result<(SignedInit, PeerIdentity)> {
+    let init = decode_init_signed(frame)?;
+    let vk = VerifyingKey::from_bytes(&init.identity_ed25519)
+        .map_err(|_| anyhow::anyhow!("bad identity key"))?;
+    vk.verify_strict(
+        &[INIT_SIG_LABEL, &init.signed_body()].concat(),
+        &Signature::from_bytes(&init.sig),
+    )
+    .map_err(|_| anyhow::anyhow!("bad init signature"))?;
+    let peer = PeerIdentity {
+        user_id: init.user_id.clone(),
+        device_id: init.device_id.clone(),
+        identity_ed25519: init.identity_ed25519,
+        identity_x25519: init.identity_x25519,
+    };
+    Ok((init, peer))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakeState {
+    AwaitingResponse,
+    Established,
+    Failed,
+}
+
+/// Poignée de main INIT v3 + RESPONSE avec confirmation de clé.
+///
+/// L'initiateur n'obtient une [`Session`] qu'après avoir vérifié le MAC de la
+/// trame RESPONSE; toute erreur ou expiration fait passer l'état à `Failed`.
+pub struct Handshake {
+    peer_id: String,
+    state: HandshakeState,
+    deadline: Instant,
+    expected_mac: [u8; 32],
+    suite: u8,
+    ratchet: Option<RatchetState>,
+}
+
+impl Handshake {
+    /// Côté initiateur : renvoie la machine (`AwaitingResponse`) et la trame INIT v3.
+    pub fn initiate(
+        peer_id: String,
+        pk_recipient: &[u8; 32],
+        timeout: Duration,
+    ) -> anyhow::Result<(Self, Vec<u8>)> {
+        Self::initiate_with(InitKind::Plain, peer_id, pk_recipient, timeout, false)
+    }
+
+    /// Comme [`Handshake::initiate`], mais émet un INIT signé par `identity`
+    /// (suite, `enc`, binder, clé ratchet, clés et identifiants d'identité).
+    pub fn initiate_signed(
+        identity: &DeviceIdentity,
+        peer_id: String,
+        pk_recipient: &[u8; 32],
+        timeout: Duration,
+    ) -> anyhow::Result<(Self, Vec<u8>)> {
+        Self::initiate_with(
+            InitKind::Signed(identity),
+            peer_id,
+            pk_recipient,
+            timeout,
+            false,
+        )
+    }
+
+    /// Comme [`Handshake::initiate_signed`], mais l'identité et la signature
+    /// sont scellées sous le contexte HPKE : le relais ne voit aucun identifiant
+    /// d'expéditeur, le répondeur ne l'apprend qu'après déchiffrement.
+    pub fn initiate_hidden(
+        identity: &DeviceIdentity,
+        peer_id: String,
+        pk_recipient: &[u8; 32],
+        timeout: Duration,
+    ) -> anyhow::Result<(Self, Vec<u8>)> {
+        Self::initiate_with(
+            InitKind::Hidden(identity),
+            peer_id,
+            pk_recipient,
+            timeout,
+            false,
+        )
+    }
+
+    /// Initiateur générique. `header_encryption` pose
+    /// [`suites::FLAG_HEADER_ENC`] dans la suite de l'INIT : les deux côtés
+    /// dérivent alors un ratchet à en-têtes chiffrés (`ratchet::encrypt_he`).
+    /// Le drapeau est lié par le binder HPKE et la confirmation de clé.
+    pub fn initiate_with(
+        kind: InitKind<'_>,
+        peer_id: String,
+        pk_recipient: &[u8; 32],
+        timeout: Duration,
+        header_encryption: bool,
+    ) -> anyhow::Result<(Self, Vec<u8>)> {
+        let suite = if header_encryption {
+            suites::HL1_BASE | suites::FLAG_HEADER_ENC
+        } else {
+            suites::HL1_BASE
+        };
+        let eph = X25519KeyPair::generate();
+        let ratchet_pub = eph.public();
+        let (enc, okm, binder, kc) =
+            hpke_initiate_confirm_ctx(pk_recipient, suite, &ratchet_ctx(&ratchet_pub))?;
+        let frame = match kind {
+            InitKind::Plain => encode_init_v3(suite, &enc, &ratchet_pub, &binder),
+            InitKind::Hidden(id) => {
+                let mut init = HiddenInit {
+                    suite,
+                    enc,
+                    ratchet_pub,
+                    binder,
+                    sealed: Vec::new(),
+                };
+                let header = init.header();
+                let mut who = SealedIdentity {
+                    identity_ed25519: id.verifying_key().to_bytes(),
+                    identity_x25519: id.x25519.public(),
+                    user_id: id.user_id.clone(),
+                    device_id: id.device_id.clone(),
+                    sig: [0u8; 64],
+                };
+                who.sig = id
+                    .sign(&[INIT_SIG_LABEL, &header, &who.signed_part()].concat())
+                    .to_bytes();
+                init.sealed = seal_xchacha(
+                    &identity_seal_key(&kc),
+                    &ID_SEAL_NONCE,
+                    &who.encode(),
+                    &header,
+                );
+                encode_init_hidden(&init)
+            }
+            InitKind::Signed(id) => {
+                let mut init = SignedInit {
+                    suite,
+                    enc,
+                    ratchet_pub,
+                    binder,
+                    identity_ed25519: id.verifying_key().to_bytes(),
+                    identity_x25519: id.x25519.public(),
+                    user_id: id.user_id.clone(),
+                    device_id: id.device_id.clone(),
+                    sig: [0u8; 64],
+                };
+                init.sig = id
+                    .sign(&[INIT_SIG_LABEL, &init.signed_body()].concat())
+                    .to_bytes();
+                encode_init_signed(&init)
+            }
+        };
+        let dh_s_priv = *eph.sk.expose();
+        let ratchet = if header_encryption {
+            ratchet::init_initiator_ephemeral_he(okm, dh_s_priv)
+        } else {
+            ratchet::init_initiator_ephemeral(okm, dh_s_priv)
+        };
+        let hs = Self {
+            peer_id,
+            state: HandshakeState::AwaitingResponse,
+            deadline: Instant::now() + timeout,
+            expected_mac: key_confirmation_mac(&kc, &frame),
+            suite,
+            ratchet: Some(ratchet),
+        };
+        Ok((hs, frame))
+    }
+
+    /// Côté répondeur : vérifie l'INIT v3 et renvoie la machine (`Established`)
+    /// et la trame RESPONSE à retourner à l'initiateur.
+    pub fn respond(
+        identity: &DeviceIdentity,
+        peer_id: String,
+        init_frame: &[u8],
+    ) -> anyhow::Result<(Self, Vec<u8>)> {
+        let (suite, enc, ratchet_pub, binder) = decode_init_v3(init_frame)?;
+        let (hs, resp, _kc) = Self::accept(
+            identity,
+            peer_id,
+            init_frame,
+            suite,
+            &enc,
+            ratchet_pub,
+            &binder,
+        )?;
+        Ok((hs, resp))
+    }
+
+    /// Côté répondeur d'un INIT signé : vérifie la signature puis procède comme
+    /// [`Handshake::respond`]. La session est nommée d'après le `user_id` authentifié.
+    pub fn respond_signed(
+        identity: &DeviceIdentity,
+        init_frame: &[u8],
+    ) -> anyhow::Result<(Self, Vec<u8>, PeerIdentity)> {
+        let (init, peer) = verify_signed_init(init_frame)?;
+        let (hs, resp, _kc) = Self::accept(
+            identity,
+            peer.user_id.clone(),
+            init_frame,
+            init.suite,
+            &init.enc,
+            init.ratchet_pub,
+            &init.binder,
+        )?;
+        Ok((hs, resp, peer))
+    }
+
+    /// Côté répondeur d'un INIT masqué : décapsule, descelle l'identité et
+    /// vérifie sa signature avant de remettre la RESPONSE.
+    pub fn respond_hidden(
+        identity: &DeviceIdentity,
+        init_frame: &[u8],
+    ) -> anyhow::Result<(Self, Vec<u8>, PeerIdentity)> {
+        let init = decode_init_hidden(init_frame)?;
+        let (mut hs, resp, kc) = Self::accept(
+            identity,
+            String::new(),
+            init_frame,
+            init.suite,
+            &init.enc,
+            init.ratchet_pub,
+            &init.binder,
+        )?;
+        let header = init.header();
+        let plain = open_xchacha(
+            &identity_seal_key(&kc),
+            &ID_SEAL_NONCE,
+            &init.sealed,
+            &header,
+        )
+        .ok_or_else(|| anyhow::anyhow!("sealed identity"))?;
+        let who = SealedIdentity::decode(&plain)?;
+        let vk = VerifyingKey::from_bytes(&who.identity_ed25519)
+            .map_err(|_| anyhow::anyhow!("bad identity key"))?;
+        vk.verify_strict(
+            &[INIT_SIG_LABEL, &header, &who.signed_part()].concat(),
+            &Signature::from_bytes(&who.sig),
+        )
+        .map_err(|_| anyhow::anyhow!("bad init signature"))?;
+        hs.peer_id.clone_from(&who.user_id);
+        let peer = PeerIdentity {
+            user_id: who.user_id,
+            device_id: who.device_id,
+            identity_ed25519: who.identity_ed25519,
+            identity_x25519: who.identity_x25519,
+        };
+        Ok((hs, resp, peer))
+    }
+
+    fn accept(
+        identity: &DeviceIdentity,
+        peer_id: String,
+        init_frame: &[u8],
+        suite: u8,
+        enc: &[u8],
+        ratchet_pub: [u8; 32],
+        binder: &[u8; 32],
+    ) -> anyhow::Result<(Self, Vec<u8>, [u8; 32])> {
+        if suites::base(suite) != suites::HL1_BASE {
+            return Err(WireError::UnsupportedSuite(suite).into());
+        }
+        let (okm, kc) = hpke_accept_confirm_ctx(
+            suite,
+            &identity.x25519.sk,
+            enc,
+            &ratchet_ctx(&ratchet_pub),
+            binder,
+        )?;
+        let mac = key_confirmation_mac(&kc, init_frame);
+        let ratchet = if suites::header_encrypted(suite) {
+            ratchet::init_responder_ephemeral_he(okm, ratchet_pub)
+        } else {
+            ratchet::init_responder_ephemeral(okm, ratchet_pub)
+        };
+        let hs = Self {
+            peer_id,
+            state: HandshakeState::Established,
+            deadline: Instant::now(),
+            expected_mac: mac,
+            suite,
+            ratchet: Some(ratchet),
+        };
+        Ok((hs, encode_response(suite, &mac), kc))
+    }
+
+    #[must_use]
+    pub fn state(&self) -> HandshakeState {
+        self.state
+    }
+
+    /// `true` si la session négociée chiffre les en-têtes du ratchet.
+    #[must_use]
+    pub fn header_encryption(&self) -> bool {
+        suites::header_encrypted(self.suite)
+    }
+
+    /// Applique l'échéance : `AwaitingResponse` expiré devient `Failed`.
+    pub fn poll_timeout(&mut self, now: Instant) -> HandshakeState {
+        if self.state == HandshakeState::AwaitingResponse && now > self.deadline {
+            self.fail();
+        }
+        self.state
+    }
+
+    /// Consomme la trame RESPONSE (initiateur uniquement).
+    pub fn on_response(&mut self, frame: &[u8], now: Instant) -> anyhow::Result<()> {
+        if self.poll_timeout(now) != HandshakeState::AwaitingResponse {
+            anyhow::bail!("unexpected response in state {:?}", self.state);
+        }
+        let ok = decode_response(frame).is_ok_and(|(suite, mac)| {
+            suite == self.suite && bool::from(mac.ct_eq(&self.expected_mac))
+        });
+        if !ok {
+            self.fail();
+            anyhow::bail!("key confirmation failed");
+        }
+        self.state = HandshakeState::Established;
+        Ok(())
+    }
+
+    /// Remet la [`Session`]; échoue tant que la clé n'est pas confirmée.
+    pub fn into_session(mut self) -> anyhow::Result<Session> {
+        match (self.state, self.ratchet.take()) {
+            (HandshakeState::Established, Some(state)) => {
+                Ok(Session::new(std::mem::take(&mut self.peer_id), state))
+            }
+            (s, _) => anyhow::bail!("handshake not established ({s:?})"),
+        }
+    }
+
+    fn fail(&mut self) {
+        self.state = HandshakeState::Failed;
+        self.ratchet = None;
+    }
+}
@@ -0,0 +1,56 @@
+//! Format de sauvegarde d'origine (avant l'enregistrement versionné).
+//!
+//! `Session` était sérialisée telle quelle, avec un `RatchetState` qui portait
+//! `skipped_index` (toujours vide : les clés sautées n'étaient pas persistées)
+//! et ni `max_skip` ni clés d'en-tête.
+
+use super::Session;
+use crate::ratchet::state::{DeliveredWindow, RatchetState, SkippedKeys, DEFAULT_MAX_SKIP};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct RatchetStateV0 {
+    dh_s_priv: [u8; 32],
+    dh_s_pub: [u8; 32],
+    dh_r_pub: [u8; 32],
+    rk: [u8; 32],
+    ck_s: [u8; 32],
+    ck_r: [u8; 32],
+    ns: u32,
+    nr: u32,
+    pn: u32,
+    #[serde(with = "serde_bytes")]
+    #[allow(dead_code)]
+    skipped_index: Vec<u8>,
+    delivered_win: DeliveredWindow,
+}
+
+#[derive(Deserialize)]
+pub(super) struct SessionV0 {
+    peer_id: String,
+    state: RatchetStateV0,
+}
+
+impl From<SessionV0> for Session {
+    fn from(v0: SessionV0) -> Self {
+        let s = v0.state;
+        Session::new(
+            v0.peer_id,
+            RatchetState {
+                dh_s_priv: s.dh_s_priv,
+                dh_s_pub: s.dh_s_pub,
+                dh_r_pub: s.dh_r_pub,
+                rk: s.rk,
+                ck_s: s.ck_s,
+                ck_r: s.ck_r,
+                ns: s.ns,
+                nr: s.nr,
+                pn: s.pn,
+                skipped: SkippedKeys::default(),
+                delivered_win: s.delivered_win,
+                max_skip: DEFAULT_MAX_SKIP,
+                he: None,
+            },
+        )
+    }
+}
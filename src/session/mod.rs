@@ -1,3 +1,13 @@
+pub mod bootstrap;
+pub mod handshake;
+mod legacy;
+
+pub use bootstrap::{bootstrap_initiator, bootstrap_responder};
+pub use handshake::{
+    verify_signed_init, Handshake, HandshakeState, InitKind, PeerIdentity,
+    DEFAULT_HANDSHAKE_TIMEOUT,
+};
+
 use crate::ratchet::state::RatchetState;
 use crate::store::fs::{ArgonProfile, FileStore};
 use crate::store::StateStore;
@@ -9,6 +19,33 @@ pub struct Session {
     pub peer_id: String,
     pub state: RatchetState,
 }
+
+/// En-tête de l'enregistrement sur disque; le format d'origine (sans en-tête)
+/// commence par la longueur `u64` de `peer_id` et ne peut donc pas l'imiter.
+const SESSION_MAGIC: [u8; 6] = *b"HLSESS";
+/// Version courante de l'enregistrement (`0` = format d'origine, voir `legacy`).
+pub const SESSION_FORMAT_VERSION: u16 = 2;
+
+#[derive(Serialize)]
+struct StoredSessionRef<'a> {
+    magic: [u8; 6],
+    version: u16,
+    session: &'a Session,
+}
+
+#[derive(Deserialize)]
+struct StoredSession {
+    _head: StoredHead,
+    session: Session,
+}
+/// En-tête seul : distingue un enregistrement versionné du format d'origine et
+/// lit sa version sans décoder la session.
+#[derive(Deserialize)]
+struct StoredHead {
+    magic: [u8; 6],
+    version: u16,
+}
+
 impl Session {
     #[must_use]
     pub fn new(peer_id: String, state: RatchetState) -> Self {
@@ -20,18 +57,37 @@ impl Session {
     /// Erreur si I/O/chiffrement échoue.
     pub fn save_fs(&self, dir: &str, pass: &str) -> anyhow::Result<()> {
         let mut fs = FileStore::open(PathBuf::from(dir), pass, ArgonProfile::BALANCED)?;
-        fs.save(&self.peer_id, self)
+        let rec = StoredSessionRef {
+            magic: SESSION_MAGIC,
+            version: SESSION_FORMAT_VERSION,
+            session: self,
+        };
+        fs.save(&self.peer_id, &rec)
     }
-    /// Charge depuis disque.
+    /// Charge depuis disque; une session au format d'origine est migrée
+    /// (clés sautées vides, `max_skip` par défaut, sans en-têtes chiffrés).
     ///
     /// # Errors
-    /// Erreur si non trouvé/déchiffrement échoue.
+    /// Erreur si non trouvé, I/O ou déchiffrement échoue, enregistrement
+    /// corrompu ou version inconnue; seul un enregistrement sans en-tête est
+    /// relu au format d'origine.
     pub fn load_fs(dir: &str, pass: &str, peer_id: &str) -> anyhow::Result<Self> {
         let mut fs = FileStore::open(PathBuf::from(dir), pass, ArgonProfile::BALANCED)?;
-        if let Some(s) = fs.load::<Session>(peer_id)? {
-            Ok(s)
-        } else {
-            anyhow::bail!("not found")
+        let Some(head) = fs.load::<StoredHead>(peer_id)? else {
+            anyhow::bail!("not found");
+        };
+        if head.magic != SESSION_MAGIC {
+            return match fs.load::<legacy::SessionV0>(peer_id)? {
+                Some(v0) => Ok(v0.into()),
+                None => anyhow::bail!("not found"),
+            };
+        }
+        if head.version != SESSION_FORMAT_VERSION {
+            anyhow::bail!("unsupported session format {}", head.version);
+        }
+        match fs.load::<StoredSession>(peer_id)? {
+            Some(rec) => Ok(rec.session),
+            None => anyhow::bail!("not found"),
         }
     }
 }
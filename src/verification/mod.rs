@@ -0,0 +1,142 @@
+#![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+
+pub mod qr;
+
+pub use qr::{MutualVerification, QrPayload, VerifiedAttestation};
+
+use crate::identity::DeviceIdentity;
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt::{self, Write as _};
+
+/// Version courante du format des numéros de sécurité.
+pub const SAFETY_NUMBER_VERSION: u8 = 1;
+
+const FINGERPRINT_LABEL: &[u8] = b"hardlock/fingerprint";
+const SAS_LABEL: &[u8] = b"hardlock/sas";
+const FINGERPRINT_ITERATIONS: usize = 1024;
+const FINGERPRINT_DIGITS: usize = 30;
+
+/// Clés publiques d'un contact, telles que comparées hors bande.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContactKeys {
+    pub user_id: String,
+    pub identity_ed25519: [u8; 32],
+    pub identity_x25519: [u8; 32],
+}
+impl ContactKeys {
+    #[must_use]
+    pub fn from_identity(id: &DeviceIdentity) -> Self {
+        Self {
+            user_id: id.user_id.clone(),
+            identity_ed25519: id.verifying_key().to_bytes(),
+            identity_x25519: id.x25519.public(),
+        }
+    }
+}
+
+/// Numéro de sécurité à 60 chiffres, identique des deux côtés.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SafetyNumber {
+    pub version: u8,
+    digits: String,
+}
+impl SafetyNumber {
+    #[must_use]
+    pub fn digits(&self) -> &str {
+        &self.digits
+    }
+}
+impl fmt::Display for SafetyNumber {
+    /// Groupes de 5 chiffres séparés par des espaces.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, chunk) in self.digits.as_bytes().chunks(5).enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_str(std::str::from_utf8(chunk).map_err(|_| fmt::Error)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Empreinte de 30 chiffres d'un contact : SHA-512 itéré sur
+/// `label || version || ed25519 || x25519 || uid_len:u16 || user_id`.
+fn fingerprint(version: u8, k: &ContactKeys) -> anyhow::Result<String> {
+    let uid_len = u16::try_from(k.user_id.len())
+        .map_err(|_| anyhow::anyhow!("user_id longer than {} bytes", u16::MAX))?;
+    let mut input = Vec::with_capacity(FINGERPRINT_LABEL.len() + 1 + 64 + 2 + k.user_id.len());
+    input.extend_from_slice(FINGERPRINT_LABEL);
+    input.push(version);
+    input.extend_from_slice(&k.identity_ed25519);
+    input.extend_from_slice(&k.identity_x25519);
+    input.extend_from_slice(&uid_len.to_le_bytes());
+    input.extend_from_slice(k.user_id.as_bytes());
+    let mut h = Sha512::digest(&input);
+    for _ in 0..FINGERPRINT_ITERATIONS {
+        let mut d = Sha512::new();
+        d.update(h);
+        d.update(k.identity_ed25519);
+        h = d.finalize();
+    }
+    let mut out = String::with_capacity(FINGERPRINT_DIGITS);
+    for c in h[..FINGERPRINT_DIGITS].chunks(5) {
+        let v = c.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b));
+        let _ = write!(out, "{:05}", v % 100_000);
+    }
+    Ok(out)
+}
+
+fn check_version(version: u8) -> anyhow::Result<()> {
+    if version != SAFETY_NUMBER_VERSION {
+        anyhow::bail!("unsupported safety number version {version}");
+    }
+    Ok(())
+}
+
+/// Empreintes des deux parties, dans un ordre indépendant de l'appelant.
+fn ordered(version: u8, a: &ContactKeys, b: &ContactKeys) -> anyhow::Result<(String, String)> {
+    let (fa, fb) = (fingerprint(version, a)?, fingerprint(version, b)?);
+    Ok(if fa <= fb { (fa, fb) } else { (fb, fa) })
+}
+
+/// Numéro de sécurité (version courante) ; `safety_number(a, b) == safety_number(b, a)`.
+/// Échoue si un `user_id` dépasse 65 535 octets (longueur codée sur `u16`).
+pub fn safety_number(a: &ContactKeys, b: &ContactKeys) -> anyhow::Result<SafetyNumber> {
+    safety_number_versioned(SAFETY_NUMBER_VERSION, a, b)
+}
+
+pub fn safety_number_versioned(
+    version: u8,
+    a: &ContactKeys,
+    b: &ContactKeys,
+) -> anyhow::Result<SafetyNumber> {
+    check_version(version)?;
+    let (lo, hi) = ordered(version, a, b)?;
+    Ok(SafetyNumber {
+        version,
+        digits: lo + &hi,
+    })
+}
+
+/// Chaîne d'authentification courte (6 chiffres, `"123 456"`) pour une
+/// comparaison vocale rapide. Mêmes erreurs que [`safety_number`].
+pub fn short_auth_string(a: &ContactKeys, b: &ContactKeys) -> anyhow::Result<String> {
+    short_auth_string_versioned(SAFETY_NUMBER_VERSION, a, b)
+}
+
+pub fn short_auth_string_versioned(
+    version: u8,
+    a: &ContactKeys,
+    b: &ContactKeys,
+) -> anyhow::Result<String> {
+    check_version(version)?;
+    let (lo, hi) = ordered(version, a, b)?;
+    let mut hasher = Sha256::new();
+    hasher.update(SAS_LABEL);
+    hasher.update([version]);
+    hasher.update(lo.as_bytes());
+    hasher.update(hi.as_bytes());
+    let digest = hasher.finalize();
+    let code = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) % 1_000_000;
+    Ok(format!("{:03} {:03}", code / 1000, code % 1000))
+}
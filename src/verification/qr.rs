@@ -0,0 +1,262 @@
+#![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+
+use super::ContactKeys;
+use crate::crypto::aeadx::XNONCE_LEN;
+use crate::identity::DeviceIdentity;
+use crate::ratchet::{self, state::Header, state::RatchetState};
+use ed25519_dalek::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// Version courante de la charge QR et des attestations.
+pub const QR_VERSION: u8 = 1;
+
+const QR_MAGIC: &[u8; 3] = b"HLV";
+const QR_FP_LABEL: &[u8] = b"hardlock/qr-fp";
+const ATTEST_LABEL: &[u8] = b"hardlock/verified";
+/// AD ratchet des messages portant une attestation.
+pub const ATTESTATION_AD: &[u8] = b"hardlock/verified";
+
+fn key_fp(key: &[u8; 32]) -> [u8; 32] {
+    let mut h = Sha256::new();
+    h.update(QR_FP_LABEL);
+    h.update(key);
+    h.finalize().into()
+}
+
+fn push_len16(out: &mut Vec<u8>, len: usize) -> anyhow::Result<()> {
+    let n =
+        u16::try_from(len).map_err(|_| anyhow::anyhow!("field longer than {} bytes", u16::MAX))?;
+    out.extend_from_slice(&n.to_le_bytes());
+    Ok(())
+}
+
+fn push_str16(out: &mut Vec<u8>, s: &str) -> anyhow::Result<()> {
+    push_len16(out, s.len())?;
+    out.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+fn read_str16(b: &[u8], off: &mut usize) -> anyhow::Result<String> {
+    if b.len() < *off + 2 {
+        anyhow::bail!("truncated");
+    }
+    let n = u16::from_le_bytes(b[*off..*off + 2].try_into().unwrap()) as usize;
+    *off += 2;
+    if b.len() < *off + n {
+        anyhow::bail!("truncated");
+    }
+    let s = String::from_utf8(b[*off..*off + n].to_vec())?;
+    *off += n;
+    Ok(s)
+}
+
+/// Charge affichée en QR :
+/// `"HLV" | version:u8 | uid_len:u16 | user_id | ed25519_fp:32 | x25519_fp:32`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QrPayload {
+    pub version: u8,
+    pub user_id: String,
+    pub ed25519_fp: [u8; 32],
+    pub x25519_fp: [u8; 32],
+}
+impl QrPayload {
+    #[must_use]
+    pub fn for_contact(k: &ContactKeys) -> Self {
+        Self {
+            version: QR_VERSION,
+            user_id: k.user_id.clone(),
+            ed25519_fp: key_fp(&k.identity_ed25519),
+            x25519_fp: key_fp(&k.identity_x25519),
+        }
+    }
+    /// Échoue si `user_id` dépasse 65 535 octets.
+    pub fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(3 + 1 + 2 + self.user_id.len() + 64);
+        out.extend_from_slice(QR_MAGIC);
+        out.push(self.version);
+        push_str16(&mut out, &self.user_id)?;
+        out.extend_from_slice(&self.ed25519_fp);
+        out.extend_from_slice(&self.x25519_fp);
+        Ok(out)
+    }
+    pub fn decode(b: &[u8]) -> anyhow::Result<Self> {
+        if b.len() < 4 || &b[..3] != QR_MAGIC {
+            anyhow::bail!("bad magic");
+        }
+        if b[3] != QR_VERSION {
+            anyhow::bail!("unsupported qr version {}", b[3]);
+        }
+        let mut off = 4;
+        let user_id = read_str16(b, &mut off)?;
+        if b.len() != off + 64 {
+            anyhow::bail!("bad length");
+        }
+        Ok(Self {
+            version: b[3],
+            user_id,
+            ed25519_fp: b[off..off + 32].try_into().unwrap(),
+            x25519_fp: b[off + 32..off + 64].try_into().unwrap(),
+        })
+    }
+    /// Texte alphanumérique base45 (mode QR alphanumérique).
+    pub fn to_base45(&self) -> anyhow::Result<String> {
+        Ok(base45::encode(self.encode()?))
+    }
+    pub fn from_base45(s: &str) -> anyhow::Result<Self> {
+        let raw = base45::decode(s).map_err(|e| anyhow::anyhow!("base45: {e:?}"))?;
+        Self::decode(&raw)
+    }
+    /// Compare (en temps constant) la charge scannée au contact stocké localement.
+    #[must_use]
+    pub fn matches(&self, stored: &ContactKeys) -> bool {
+        let expected = Self::for_contact(stored);
+        let keys =
+            self.ed25519_fp.ct_eq(&expected.ed25519_fp) & self.x25519_fp.ct_eq(&expected.x25519_fp);
+        bool::from(keys) && self.user_id == expected.user_id
+    }
+}
+
+/// Décode une charge scannée et la compare au contact stocké.
+pub fn verify_scanned(scanned: &str, stored: &ContactKeys) -> anyhow::Result<bool> {
+    Ok(QrPayload::from_base45(scanned)?.matches(stored))
+}
+
+/// Attestation « vérifié » signée par le vérificateur, envoyée au pair
+/// sur la session ratchet établie.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedAttestation {
+    pub version: u8,
+    pub verifier_user_id: String,
+    pub subject: QrPayload,
+    pub ts_ms: u64,
+    pub sig: [u8; 64],
+}
+impl VerifiedAttestation {
+    /// Signe l'attestation que `verifier` a vérifié `subject` hors bande.
+    /// Échoue si un identifiant est trop long pour [`VerifiedAttestation::encode`].
+    pub fn sign(
+        verifier: &DeviceIdentity,
+        subject: &ContactKeys,
+        ts_ms: u64,
+    ) -> anyhow::Result<Self> {
+        let mut att = Self {
+            version: QR_VERSION,
+            verifier_user_id: verifier.user_id.clone(),
+            subject: QrPayload::for_contact(subject),
+            ts_ms,
+            sig: [0u8; 64],
+        };
+        att.sig = verifier.sign(&att.signed_bytes()?).to_bytes();
+        Ok(att)
+    }
+    /// Signature sur `"hardlock/verified" || encode()` privé de `sig`.
+    fn signed_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let enc = self.encode()?;
+        Ok([ATTEST_LABEL, &enc[..enc.len() - 64]].concat())
+    }
+    /// `version:u8 | uid_len:u16 | verifier_user_id | subject_qr_len:u16 | subject_qr | ts_ms:u64 | sig:64`.
+    /// Échoue si `verifier_user_id` ou la charge QR encodée du sujet dépasse
+    /// 65 535 octets.
+    pub fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        let subject = self.subject.encode()?;
+        let mut out = Vec::new();
+        out.push(self.version);
+        push_str16(&mut out, &self.verifier_user_id)?;
+        push_len16(&mut out, subject.len())?;
+        out.extend_from_slice(&subject);
+        out.extend_from_slice(&self.ts_ms.to_le_bytes());
+        out.extend_from_slice(&self.sig);
+        Ok(out)
+    }
+    pub fn decode(b: &[u8]) -> anyhow::Result<Self> {
+        if b.is_empty() || b[0] != QR_VERSION {
+            anyhow::bail!("unsupported attestation version");
+        }
+        let mut off = 1;
+        let verifier_user_id = read_str16(b, &mut off)?;
+        if b.len() < off + 2 {
+            anyhow::bail!("truncated");
+        }
+        let n = u16::from_le_bytes(b[off..off + 2].try_into().unwrap()) as usize;
+        off += 2;
+        if b.len() != off + n + 8 + 64 {
+            anyhow::bail!("bad length");
+        }
+        let subject = QrPayload::decode(&b[off..off + n])?;
+        off += n;
+        Ok(Self {
+            version: b[0],
+            verifier_user_id,
+            subject,
+            ts_ms: u64::from_le_bytes(b[off..off + 8].try_into().unwrap()),
+            sig: b[off + 8..].try_into().unwrap(),
+        })
+    }
+    /// Vrai si l'attestation est signée par `verifier` et porte sur `me`.
+    #[must_use]
+    pub fn verify(&self, verifier: &ContactKeys, me: &ContactKeys) -> bool {
+        if self.verifier_user_id != verifier.user_id || !self.subject.matches(me) {
+            return false;
+        }
+        let (Ok(vk), Ok(msg)) = (
+            VerifyingKey::from_bytes(&verifier.identity_ed25519),
+            self.signed_bytes(),
+        ) else {
+            return false;
+        };
+        vk.verify_strict(&msg, &Signature::from_bytes(&self.sig))
+            .is_ok()
+    }
+}
+
+/// Chiffre une attestation sur la session ratchet (AD [`ATTESTATION_AD`]).
+pub fn seal_attestation(
+    state: &mut RatchetState,
+    att: &VerifiedAttestation,
+) -> anyhow::Result<(Header, [u8; XNONCE_LEN], Vec<u8>)> {
+    ratchet::encrypt(state, ATTESTATION_AD, &att.encode()?).map_err(|e| anyhow::anyhow!("{e:?}"))
+}
+
+/// Déchiffre une attestation reçue sur la session ratchet.
+pub fn open_attestation(
+    state: &mut RatchetState,
+    header: &Header,
+    nonce: &[u8; XNONCE_LEN],
+    ct: &[u8],
+) -> anyhow::Result<VerifiedAttestation> {
+    let pt = ratchet::decrypt(state, ATTESTATION_AD, header, nonce, ct)
+        .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+    VerifiedAttestation::decode(&pt)
+}
+
+/// État de vérification mutuelle d'un contact : le contact n'est marqué
+/// vérifié qu'après notre propre scan *et* l'attestation du pair.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MutualVerification {
+    pub scanned: bool,
+    pub attested_by_peer: bool,
+}
+impl MutualVerification {
+    /// Enregistre un scan local ; renvoie `Ok(false)` si la charge ne correspond pas.
+    pub fn on_scan(&mut self, scanned: &str, stored: &ContactKeys) -> anyhow::Result<bool> {
+        let ok = verify_scanned(scanned, stored)?;
+        self.scanned |= ok;
+        Ok(ok)
+    }
+    /// Enregistre l'attestation du pair (signée par `peer`, portant sur `me`).
+    pub fn on_attestation(
+        &mut self,
+        att: &VerifiedAttestation,
+        peer: &ContactKeys,
+        me: &ContactKeys,
+    ) -> bool {
+        let ok = att.verify(peer, me);
+        self.attested_by_peer |= ok;
+        ok
+    }
+    #[must_use]
+    pub fn is_verified(&self) -> bool {
+        self.scanned && self.attested_by_peer
+    }
+}
@@ -0,0 +1,266 @@
+//! Fragmentation pour liens à petite MTU (BLE...) (PROTOCOL.md §3quinquies).
+//!
+//! `fragment := ver:u8 = 1 | msg_id:u32 | index:u16 | count:u16 | chunk`
+//!
+//! Les fragments ne portent pas de MAC propre : la trame réassemblée est
+//! authentifiée par l'AEAD du ratchet, toute altération ou substitution
+//! d'un fragment se traduit par BAD_AEAD au déchiffrement.
+
+use super::WireError;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+pub const FRAGMENT_VERSION: u8 = 1;
+pub const FRAGMENT_HEADER_LEN: usize = 1 + 4 + 2 + 2;
+pub const DEFAULT_REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Coût d'un emplacement de fragment, compté dans `max_bytes` à l'ouverture
+/// d'un ensemble (`count` emplacements, jusqu'à ~1,5 Mio pour 65 535).
+const SLOT_COST: usize = std::mem::size_of::<Option<Vec<u8>>>();
+
+/// Fragment emprunté.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fragment<'a> {
+    pub msg_id: u32,
+    pub index: u16,
+    pub count: u16,
+    pub chunk: &'a [u8],
+}
+
+impl<'a> Fragment<'a> {
+    /// # Errors
+    /// `ShortHeader`, `BadVersion` ou `Malformed` (index hors bornes, `count` nul).
+    pub fn parse(b: &'a [u8]) -> Result<Self, WireError> {
+        let Some((head, chunk)) = b.split_at_checked(FRAGMENT_HEADER_LEN) else {
+            return Err(WireError::ShortHeader);
+        };
+        if head[0] != FRAGMENT_VERSION {
+            return Err(WireError::BadVersion(u16::from(head[0])));
+        }
+        let msg_id = u32::from_le_bytes([head[1], head[2], head[3], head[4]]);
+        let index = u16::from_le_bytes([head[5], head[6]]);
+        let count = u16::from_le_bytes([head[7], head[8]]);
+        if count == 0 || index >= count {
+            return Err(WireError::Malformed("fragment index out of range"));
+        }
+        Ok(Self {
+            msg_id,
+            index,
+            count,
+            chunk,
+        })
+    }
+}
+
+/// Découpe `frame` en fragments d'au plus `mtu` octets (en-tête compris).
+///
+/// `msg_id` doit être imprévisible et non réutilisé pendant la fenêtre de
+/// réassemblage (par ex. tiré au hasard).
+///
+/// # Errors
+/// `Malformed` si `mtu` ne laisse aucune place aux données; `FrameTooLarge`
+/// s'il faudrait plus de 65 535 fragments.
+pub fn fragment(frame: &[u8], msg_id: u32, mtu: usize) -> Result<Vec<Vec<u8>>, WireError> {
+    let room = mtu
+        .checked_sub(FRAGMENT_HEADER_LEN)
+        .filter(|&r| r > 0)
+        .ok_or(WireError::Malformed("mtu too small for fragment header"))?;
+    let n = frame.len().div_ceil(room).max(1);
+    let count = u16::try_from(n).map_err(|_| WireError::FrameTooLarge {
+        len: frame.len(),
+        max: room * usize::from(u16::MAX),
+    })?;
+    let mut chunks: Vec<&[u8]> = frame.chunks(room).collect();
+    if chunks.is_empty() {
+        chunks.push(&[]);
+    }
+    Ok(chunks
+        .into_iter()
+        .zip(0u16..)
+        .map(|(chunk, index)| {
+            let mut out = Vec::with_capacity(FRAGMENT_HEADER_LEN + chunk.len());
+            out.push(FRAGMENT_VERSION);
+            out.extend_from_slice(&msg_id.to_le_bytes());
+            out.extend_from_slice(&index.to_le_bytes());
+            out.extend_from_slice(&count.to_le_bytes());
+            out.extend_from_slice(chunk);
+            out
+        })
+        .collect())
+}
+
+/// Bornes du réassembleur.
+#[derive(Clone, Copy, Debug)]
+pub struct ReassemblyLimits {
+    /// Ensembles incomplets conservés simultanément.
+    pub max_pending: usize,
+    /// Octets conservés, tous ensembles confondus : fragments reçus et
+    /// emplacements réservés pour chaque ensemble ouvert.
+    pub max_bytes: usize,
+    /// Au-delà, un ensemble incomplet est abandonné.
+    pub timeout: Duration,
+}
+
+impl Default for ReassemblyLimits {
+    fn default() -> Self {
+        Self {
+            max_pending: 32,
+            max_bytes: 256 * 1024,
+            timeout: DEFAULT_REASSEMBLY_TIMEOUT,
+        }
+    }
+}
+
+struct Pending {
+    parts: Vec<Option<Vec<u8>>>,
+    received: usize,
+    bytes: usize,
+    first_seen: Instant,
+}
+
+/// Réassemble des fragments reçus dans le désordre.
+pub struct Reassembler {
+    limits: ReassemblyLimits,
+    pending: HashMap<u32, Pending>,
+    bytes: usize,
+}
+
+impl Reassembler {
+    #[must_use]
+    pub fn new(limits: ReassemblyLimits) -> Self {
+        Self {
+            limits,
+            pending: HashMap::new(),
+            bytes: 0,
+        }
+    }
+
+    /// Ajoute un fragment; renvoie la trame complète dès le dernier reçu.
+    ///
+    /// Les ensembles expirés sont abandonnés d'abord; si les bornes sont
+    /// dépassées, les ensembles les plus anciens sont abandonnés. Un doublon
+    /// est ignoré.
+    ///
+    /// # Errors
+    /// Erreurs de [`Fragment::parse`]; `Malformed` si `count` diffère des
+    /// fragments déjà reçus pour ce `msg_id` (le fragment est ignoré, l'ensemble
+    /// en cours est conservé); `FrameTooLarge` si le fragment seul, plus les
+    /// emplacements d'un nouvel ensemble, dépasse `max_bytes`, ou si l'ensemble
+    /// en cours ne peut plus tenir dans `max_bytes` (il est alors abandonné).
+    pub fn push(&mut self, b: &[u8], now: Instant) -> Result<Option<Vec<u8>>, WireError> {
+        let f = Fragment::parse(b)?;
+        self.expire(now);
+        if f.chunk.len() > self.limits.max_bytes {
+            return Err(WireError::FrameTooLarge {
+                len: f.chunk.len(),
+                max: self.limits.max_bytes,
+            });
+        }
+        if f.count == 1 {
+            return Ok(Some(f.chunk.to_vec()));
+        }
+        let slots = match self.pending.get(&f.msg_id) {
+            Some(p) if p.parts.len() != usize::from(f.count) => {
+                return Err(WireError::Malformed("fragment count mismatch"));
+            }
+            Some(p) if p.parts[usize::from(f.index)].is_some() => return Ok(None),
+            Some(_) => 0,
+            None => usize::from(f.count) * SLOT_COST,
+        };
+        let cost = slots + f.chunk.len();
+        if cost > self.limits.max_bytes {
+            return Err(WireError::FrameTooLarge {
+                len: cost,
+                max: self.limits.max_bytes,
+            });
+        }
+        self.make_room(f.msg_id, cost)?;
+        let p = self.pending.entry(f.msg_id).or_insert_with(|| Pending {
+            parts: vec![None; usize::from(f.count)],
+            received: 0,
+            bytes: slots,
+            first_seen: now,
+        });
+        p.parts[usize::from(f.index)] = Some(f.chunk.to_vec());
+        p.received += 1;
+        p.bytes += f.chunk.len();
+        self.bytes += cost;
+        if p.received < p.parts.len() {
+            return Ok(None);
+        }
+        let p = self.pending.remove(&f.msg_id).expect("present");
+        self.bytes -= p.bytes;
+        Ok(Some(p.parts.into_iter().flatten().flatten().collect()))
+    }
+
+    /// Abandonne les ensembles incomplets plus vieux que `timeout`; renvoie leur nombre.
+    pub fn expire(&mut self, now: Instant) -> usize {
+        let timeout = self.limits.timeout;
+        let stale: Vec<u32> = self
+            .pending
+            .iter()
+            .filter(|(_, p)| now.saturating_duration_since(p.first_seen) > timeout)
+            .map(|(&id, _)| id)
+            .collect();
+        for id in &stale {
+            self.drop_set(*id);
+        }
+        stale.len()
+    }
+
+    /// Nombre d'ensembles incomplets en attente.
+    #[must_use]
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+    /// Octets en attente (fragments et emplacements réservés).
+    #[must_use]
+    pub fn buffered_bytes(&self) -> usize {
+        self.bytes
+    }
+
+    fn drop_set(&mut self, id: u32) {
+        if let Some(p) = self.pending.remove(&id) {
+            self.bytes -= p.bytes;
+        }
+    }
+
+    /// Évince les ensembles les plus anciens (hors `keep`) jusqu'à pouvoir
+    /// accueillir `incoming` octets et, si `keep` est nouveau, un ensemble de plus.
+    /// Si `keep` seul dépasse encore `max_bytes`, il est abandonné et
+    /// `FrameTooLarge` renvoyé.
+    fn make_room(&mut self, keep: u32, incoming: usize) -> Result<(), WireError> {
+        let new_set = !self.pending.contains_key(&keep);
+        loop {
+            let too_many = new_set && self.pending.len() >= self.limits.max_pending;
+            let too_big = self.bytes + incoming > self.limits.max_bytes;
+            if !too_many && !too_big {
+                return Ok(());
+            }
+            let oldest = self
+                .pending
+                .iter()
+                .filter(|(&id, _)| id != keep)
+                .min_by_key(|(_, p)| p.first_seen)
+                .map(|(&id, _)| id);
+            match oldest {
+                Some(id) => self.drop_set(id),
+                None if too_big => {
+                    let len = self.bytes + incoming;
+                    self.drop_set(keep);
+                    return Err(WireError::FrameTooLarge {
+                        len,
+                        max: self.limits.max_bytes,
+                    });
+                }
+                None => return Ok(()),
+            }
+        }
+    }
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Self::new(ReassemblyLimits::default())
+    }
+}
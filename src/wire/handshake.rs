@@ -1,11 +1,20 @@
 #![allow(
     clippy::missing_panics_doc,
     clippy::missing_errors_doc,
-    clippy::doc_markdown
+    clippy::doc_markdown,
+    clippy::type_complexity
 )]
 
+use super::{InitFrame, WireError};
+
 pub const TYPE_INIT: u8 = 0x01;
 pub const TYPE_INIT_AUTH: u8 = 0x02;
+pub const TYPE_INIT_V3: u8 = 0x03;
+pub const TYPE_INIT_PSK: u8 = 0x04;
+pub const TYPE_INIT_AUTH_PSK: u8 = 0x05;
+pub const TYPE_RESPONSE: u8 = 0x06;
+pub const TYPE_INIT_SIGNED: u8 = 0x07;
+pub const TYPE_INIT_HIDDEN: u8 = 0x08;
 
 #[must_use]
 pub fn encode_init(enc: &[u8]) -> Vec<u8> {
@@ -15,66 +24,475 @@ pub fn encode_init(enc: &[u8]) -> Vec<u8> {
     out.extend_from_slice(enc);
     out
 }
-pub fn decode_init(b: &[u8]) -> anyhow::Result<Vec<u8>> {
+pub fn decode_init(b: &[u8]) -> Result<Vec<u8>, WireError> {
+    InitFrame::parse_v1_enc(b).map(<[u8]>::to_vec)
+}
+
+#[must_use]
+pub fn encode_init_v2(suite: u8, enc: &[u8], binder32: &[u8; 32]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + 1 + 4 + enc.len() + 32);
+    out.push(TYPE_INIT);
+    out.push(suite);
+    out.extend_from_slice(&u32::try_from(enc.len()).expect("len").to_le_bytes());
+    out.extend_from_slice(enc);
+    out.extend_from_slice(binder32);
+    out
+}
+pub fn decode_init_v2(b: &[u8]) -> Result<(u8, Vec<u8>, [u8; 32]), WireError> {
+    let f = InitFrame::parse(b)?;
+    Ok((f.suite, f.enc.to_vec(), *f.binder))
+}
+
+#[must_use]
+pub fn encode_init_auth(enc: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + 4 + enc.len());
+    out.push(TYPE_INIT_AUTH);
+    out.extend_from_slice(&u32::try_from(enc.len()).expect("len").to_le_bytes());
+    out.extend_from_slice(enc);
+    out
+}
+pub fn decode_init_auth(b: &[u8]) -> Result<Vec<u8>, WireError> {
     if b.len() < 5 {
-        anyhow::bail!("short");
+        return Err(WireError::ShortHeader);
     }
-    if b[0] != TYPE_INIT {
-        anyhow::bail!("bad type");
+    if b[0] != TYPE_INIT_AUTH {
+        return Err(WireError::BadType(b[0]));
     }
     let len = u32::from_le_bytes(b[1..5].try_into().unwrap()) as usize;
     if b.len() < 5 + len {
-        anyhow::bail!("truncated");
+        return Err(WireError::Malformed("truncated"));
     }
     Ok(b[5..5 + len].to_vec())
 }
 
 #[must_use]
-pub fn encode_init_v2(suite: u8, enc: &[u8], binder32: &[u8; 32]) -> Vec<u8> {
-    let mut out = Vec::with_capacity(1 + 1 + 4 + enc.len() + 32);
-    out.push(TYPE_INIT);
+pub fn encode_init_auth_v2(
+    suite: u8,
+    enc: &[u8],
+    tag32: &[u8; 32],
+    binder32: &[u8; 32],
+) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + 1 + 4 + enc.len() + 32 + 32);
+    out.push(TYPE_INIT_AUTH);
     out.push(suite);
     out.extend_from_slice(&u32::try_from(enc.len()).expect("len").to_le_bytes());
     out.extend_from_slice(enc);
+    out.extend_from_slice(tag32);
     out.extend_from_slice(binder32);
     out
 }
-pub fn decode_init_v2(b: &[u8]) -> anyhow::Result<(u8, Vec<u8>, [u8; 32])> {
-    if b.len() < 1 + 1 + 4 + 32 {
-        anyhow::bail!("short");
+pub fn decode_init_auth_v2(b: &[u8]) -> Result<(u8, Vec<u8>, [u8; 32], [u8; 32]), WireError> {
+    if b.len() < 1 + 1 + 4 + 32 + 32 {
+        return Err(WireError::ShortHeader);
     }
-    if b[0] != TYPE_INIT {
-        anyhow::bail!("bad type");
+    if b[0] != TYPE_INIT_AUTH {
+        return Err(WireError::BadType(b[0]));
     }
     let suite = b[1];
     let len = u32::from_le_bytes(b[2..6].try_into().unwrap()) as usize;
-    if b.len() < 6 + len + 32 {
-        anyhow::bail!("truncated");
+    if b.len() < 6 + len + 64 {
+        return Err(WireError::Malformed("truncated"));
+    }
+    if b.len() != 6 + len + 64 {
+        return Err(WireError::Malformed("trailing bytes"));
     }
     let enc = b[6..6 + len].to_vec();
+    let mut tag = [0u8; 32];
+    tag.copy_from_slice(&b[6 + len..6 + len + 32]);
+    let mut binder = [0u8; 32];
+    binder.copy_from_slice(&b[6 + len + 32..6 + len + 64]);
+    Ok((suite, enc, tag, binder))
+}
+
+fn push_psk_head(out: &mut Vec<u8>, ty: u8, suite: u8, psk_id: &[u8], enc: &[u8]) {
+    out.push(ty);
+    out.push(suite);
+    out.extend_from_slice(&u16::try_from(psk_id.len()).expect("psk_id").to_le_bytes());
+    out.extend_from_slice(psk_id);
+    out.extend_from_slice(&u32::try_from(enc.len()).expect("len").to_le_bytes());
+    out.extend_from_slice(enc);
+}
+
+/// Lit `suite | psk_id_len | psk_id | enc_len | enc` et renvoie l'offset suivant;
+/// la trame doit se terminer exactement après les `trailer` octets restants.
+fn read_psk_head(
+    b: &[u8],
+    ty: u8,
+    trailer: usize,
+) -> Result<(u8, Vec<u8>, Vec<u8>, usize), WireError> {
+    if b.len() < 1 + 1 + 2 + 4 + trailer {
+        return Err(WireError::ShortHeader);
+    }
+    if b[0] != ty {
+        return Err(WireError::BadType(b[0]));
+    }
+    let suite = b[1];
+    let id_len = u16::from_le_bytes(b[2..4].try_into().unwrap()) as usize;
+    if b.len() < 4 + id_len + 4 {
+        return Err(WireError::Malformed("truncated"));
+    }
+    let psk_id = b[4..4 + id_len].to_vec();
+    let off = 4 + id_len;
+    let len = u32::from_le_bytes(b[off..off + 4].try_into().unwrap()) as usize;
+    let off = off + 4;
+    if b.len() < off + len + trailer {
+        return Err(WireError::Malformed("truncated"));
+    }
+    if b.len() != off + len + trailer {
+        return Err(WireError::Malformed("trailing bytes"));
+    }
+    Ok((suite, psk_id, b[off..off + len].to_vec(), off + len))
+}
+
+/// INIT PSK : `0x04 | suite | psk_id_len:u16 | psk_id | enc_len:u32 | enc | binder:32`.
+#[must_use]
+pub fn encode_init_psk(suite: u8, psk_id: &[u8], enc: &[u8], binder32: &[u8; 32]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + 1 + 2 + psk_id.len() + 4 + enc.len() + 32);
+    push_psk_head(&mut out, TYPE_INIT_PSK, suite, psk_id, enc);
+    out.extend_from_slice(binder32);
+    out
+}
+pub fn decode_init_psk(b: &[u8]) -> Result<(u8, Vec<u8>, Vec<u8>, [u8; 32]), WireError> {
+    let (suite, psk_id, enc, off) = read_psk_head(b, TYPE_INIT_PSK, 32)?;
     let mut binder = [0u8; 32];
-    binder.copy_from_slice(&b[6 + len..6 + len + 32]);
-    Ok((suite, enc, binder))
+    binder.copy_from_slice(&b[off..off + 32]);
+    Ok((suite, psk_id, enc, binder))
 }
 
+/// INIT AuthPSK : comme INIT PSK avec `tag:32` avant le binder.
 #[must_use]
-pub fn encode_init_auth(enc: &[u8]) -> Vec<u8> {
-    let mut out = Vec::with_capacity(1 + 4 + enc.len());
-    out.push(TYPE_INIT_AUTH);
+pub fn encode_init_auth_psk(
+    suite: u8,
+    psk_id: &[u8],
+    enc: &[u8],
+    tag32: &[u8; 32],
+    binder32: &[u8; 32],
+) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + 1 + 2 + psk_id.len() + 4 + enc.len() + 64);
+    push_psk_head(&mut out, TYPE_INIT_AUTH_PSK, suite, psk_id, enc);
+    out.extend_from_slice(tag32);
+    out.extend_from_slice(binder32);
+    out
+}
+pub fn decode_init_auth_psk(
+    b: &[u8],
+) -> Result<(u8, Vec<u8>, Vec<u8>, [u8; 32], [u8; 32]), WireError> {
+    let (suite, psk_id, enc, off) = read_psk_head(b, TYPE_INIT_AUTH_PSK, 64)?;
+    let mut tag = [0u8; 32];
+    tag.copy_from_slice(&b[off..off + 32]);
+    let mut binder = [0u8; 32];
+    binder.copy_from_slice(&b[off + 32..off + 64]);
+    Ok((suite, psk_id, enc, tag, binder))
+}
+
+/// INIT v2 hybride (suite `HL1_HYB`) : porte `enc` HPKE et le chiffré ML-KEM.
+#[must_use]
+pub fn encode_init_v2_hybrid(enc: &[u8], kem_ct: &[u8], binder32: &[u8; 32]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + 1 + 4 + enc.len() + 4 + kem_ct.len() + 32);
+    out.push(TYPE_INIT);
+    out.push(crate::suites::HL1_HYB);
     out.extend_from_slice(&u32::try_from(enc.len()).expect("len").to_le_bytes());
     out.extend_from_slice(enc);
+    out.extend_from_slice(&u32::try_from(kem_ct.len()).expect("len").to_le_bytes());
+    out.extend_from_slice(kem_ct);
+    out.extend_from_slice(binder32);
     out
 }
-pub fn decode_init_auth(b: &[u8]) -> anyhow::Result<Vec<u8>> {
-    if b.len() < 5 {
-        anyhow::bail!("short");
+pub fn decode_init_v2_hybrid(b: &[u8]) -> Result<(Vec<u8>, Vec<u8>, [u8; 32]), WireError> {
+    if b.len() < 1 + 1 + 4 + 4 + 32 {
+        return Err(WireError::ShortHeader);
     }
-    if b[0] != TYPE_INIT_AUTH {
-        anyhow::bail!("bad type");
+    if b[0] != TYPE_INIT {
+        return Err(WireError::BadType(b[0]));
     }
-    let len = u32::from_le_bytes(b[1..5].try_into().unwrap()) as usize;
-    if b.len() < 5 + len {
-        anyhow::bail!("truncated");
+    if b[1] != crate::suites::HL1_HYB {
+        return Err(WireError::UnsupportedSuite(b[1]));
+    }
+    let enc_len = u32::from_le_bytes(b[2..6].try_into().unwrap()) as usize;
+    if b.len() < 6 + enc_len + 4 {
+        return Err(WireError::Malformed("truncated"));
+    }
+    let enc = b[6..6 + enc_len].to_vec();
+    let off = 6 + enc_len;
+    let ct_len = u32::from_le_bytes(b[off..off + 4].try_into().unwrap()) as usize;
+    let off = off + 4;
+    if b.len() < off + ct_len + 32 {
+        return Err(WireError::Malformed("truncated"));
+    }
+    let kem_ct = b[off..off + ct_len].to_vec();
+    let mut binder = [0u8; 32];
+    binder.copy_from_slice(&b[off + ct_len..off + ct_len + 32]);
+    Ok((enc, kem_ct, binder))
+}
+
+/// INIT v3 : INIT v2 + clé publique ratchet éphémère de l'initiateur.
+#[must_use]
+pub fn encode_init_v3(
+    suite: u8,
+    enc: &[u8],
+    ratchet_pub: &[u8; 32],
+    binder32: &[u8; 32],
+) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + 1 + 4 + enc.len() + 32 + 32);
+    out.push(TYPE_INIT_V3);
+    out.push(suite);
+    out.extend_from_slice(&u32::try_from(enc.len()).expect("len").to_le_bytes());
+    out.extend_from_slice(enc);
+    out.extend_from_slice(ratchet_pub);
+    out.extend_from_slice(binder32);
+    out
+}
+pub fn decode_init_v3(b: &[u8]) -> Result<(u8, Vec<u8>, [u8; 32], [u8; 32]), WireError> {
+    if b.len() < 1 + 1 + 4 + 32 + 32 {
+        return Err(WireError::ShortHeader);
+    }
+    if b[0] != TYPE_INIT_V3 {
+        return Err(WireError::BadType(b[0]));
+    }
+    let suite = b[1];
+    let len = u32::from_le_bytes(b[2..6].try_into().unwrap()) as usize;
+    if b.len() < 6 + len + 64 {
+        return Err(WireError::Malformed("truncated"));
+    }
+    if b.len() != 6 + len + 64 {
+        return Err(WireError::Malformed("trailing bytes"));
+    }
+    let enc = b[6..6 + len].to_vec();
+    let mut ratchet_pub = [0u8; 32];
+    ratchet_pub.copy_from_slice(&b[6 + len..6 + len + 32]);
+    let mut binder = [0u8; 32];
+    binder.copy_from_slice(&b[6 + len + 32..6 + len + 64]);
+    Ok((suite, enc, ratchet_pub, binder))
+}
+
+/// RESPONSE : `0x06 | suite | mac:32`, confirmation de clé du répondeur.
+#[must_use]
+pub fn encode_response(suite: u8, mac32: &[u8; 32]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + 1 + 32);
+    out.push(TYPE_RESPONSE);
+    out.push(suite);
+    out.extend_from_slice(mac32);
+    out
+}
+pub fn decode_response(b: &[u8]) -> Result<(u8, [u8; 32]), WireError> {
+    if b.len() != 1 + 1 + 32 {
+        return Err(WireError::Malformed("bad length"));
+    }
+    if b[0] != TYPE_RESPONSE {
+        return Err(WireError::BadType(b[0]));
+    }
+    let mut mac = [0u8; 32];
+    mac.copy_from_slice(&b[2..34]);
+    Ok((b[1], mac))
+}
+
+/// INIT signé : INIT v3 + clés d'identité et identifiants de l'initiateur,
+/// suivis d'une signature Ed25519 sur tout ce qui précède.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedInit {
+    pub suite: u8,
+    pub enc: Vec<u8>,
+    pub ratchet_pub: [u8; 32],
+    pub binder: [u8; 32],
+    pub identity_ed25519: [u8; 32],
+    pub identity_x25519: [u8; 32],
+    pub user_id: String,
+    pub device_id: String,
+    pub sig: [u8; 64],
+}
+impl SignedInit {
+    /// Octets couverts par la signature :
+    /// `0x07 | suite | enc_len:u32 | enc | ratchet_pub:32 | binder:32 | ed25519:32 | x25519:32 | uid_len:u16 | uid | did_len:u16 | did`.
+    #[must_use]
+    pub fn signed_body(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(
+            1 + 1 + 4 + self.enc.len() + 128 + 4 + self.user_id.len() + self.device_id.len(),
+        );
+        out.push(TYPE_INIT_SIGNED);
+        out.push(self.suite);
+        out.extend_from_slice(&u32::try_from(self.enc.len()).expect("len").to_le_bytes());
+        out.extend_from_slice(&self.enc);
+        out.extend_from_slice(&self.ratchet_pub);
+        out.extend_from_slice(&self.binder);
+        out.extend_from_slice(&self.identity_ed25519);
+        out.extend_from_slice(&self.identity_x25519);
+        for id in [&self.user_id, &self.device_id] {
+            out.extend_from_slice(&u16::try_from(id.len()).expect("len").to_le_bytes());
+            out.extend_from_slice(id.as_bytes());
+        }
+        out
+    }
+}
+
+#[must_use]
+pub fn encode_init_signed(init: &SignedInit) -> Vec<u8> {
+    let mut out = init.signed_body();
+    out.extend_from_slice(&init.sig);
+    out
+}
+pub fn decode_init_signed(b: &[u8]) -> Result<SignedInit, WireError> {
+    fn take<'a>(b: &'a [u8], off: &mut usize, n: usize) -> Result<&'a [u8], WireError> {
+        if b.len() < *off + n {
+            return Err(WireError::Malformed("truncated"));
+        }
+        let s = &b[*off..*off + n];
+        *off += n;
+        Ok(s)
+    }
+    if b.len() < 2 {
+        return Err(WireError::ShortHeader);
+    }
+    if b[0] != TYPE_INIT_SIGNED {
+        return Err(WireError::BadType(b[0]));
+    }
+    let mut off = 2;
+    let len = u32::from_le_bytes(take(b, &mut off, 4)?.try_into().unwrap()) as usize;
+    let enc = take(b, &mut off, len)?.to_vec();
+    let ratchet_pub: [u8; 32] = take(b, &mut off, 32)?.try_into().unwrap();
+    let binder: [u8; 32] = take(b, &mut off, 32)?.try_into().unwrap();
+    let identity_ed25519: [u8; 32] = take(b, &mut off, 32)?.try_into().unwrap();
+    let identity_x25519: [u8; 32] = take(b, &mut off, 32)?.try_into().unwrap();
+    let mut ids = [String::new(), String::new()];
+    for id in &mut ids {
+        let n = u16::from_le_bytes(take(b, &mut off, 2)?.try_into().unwrap()) as usize;
+        *id = String::from_utf8(take(b, &mut off, n)?.to_vec())
+            .map_err(|_| WireError::Malformed("utf8"))?;
+    }
+    let sig: [u8; 64] = take(b, &mut off, 64)?.try_into().unwrap();
+    if off != b.len() {
+        return Err(WireError::Malformed("trailing bytes"));
+    }
+    let [user_id, device_id] = ids;
+    Ok(SignedInit {
+        suite: b[1],
+        enc,
+        ratchet_pub,
+        binder,
+        identity_ed25519,
+        identity_x25519,
+        user_id,
+        device_id,
+        sig,
+    })
+}
+
+/// INIT à identité masquée : en-tête INIT v3 en clair, identité de
+/// l'initiateur scellée (`sealed`) sous une clé issue du contexte HPKE.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HiddenInit {
+    pub suite: u8,
+    pub enc: Vec<u8>,
+    pub ratchet_pub: [u8; 32],
+    pub binder: [u8; 32],
+    pub sealed: Vec<u8>,
+}
+impl HiddenInit {
+    /// En-tête clair, utilisé comme AAD du scellement :
+    /// `0x08 | suite | enc_len:u32 | enc | ratchet_pub:32 | binder:32`.
+    #[must_use]
+    pub fn header(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(1 + 1 + 4 + self.enc.len() + 64);
+        out.push(TYPE_INIT_HIDDEN);
+        out.push(self.suite);
+        out.extend_from_slice(&u32::try_from(self.enc.len()).expect("len").to_le_bytes());
+        out.extend_from_slice(&self.enc);
+        out.extend_from_slice(&self.ratchet_pub);
+        out.extend_from_slice(&self.binder);
+        out
+    }
+}
+
+/// `header | sealed_len:u32 | sealed`.
+#[must_use]
+pub fn encode_init_hidden(init: &HiddenInit) -> Vec<u8> {
+    let mut out = init.header();
+    out.extend_from_slice(&u32::try_from(init.sealed.len()).expect("len").to_le_bytes());
+    out.extend_from_slice(&init.sealed);
+    out
+}
+pub fn decode_init_hidden(b: &[u8]) -> Result<HiddenInit, WireError> {
+    if b.len() < 1 + 1 + 4 + 64 + 4 {
+        return Err(WireError::ShortHeader);
+    }
+    if b[0] != TYPE_INIT_HIDDEN {
+        return Err(WireError::BadType(b[0]));
+    }
+    let len = u32::from_le_bytes(b[2..6].try_into().unwrap()) as usize;
+    let off = 6 + len;
+    if b.len() < off + 64 + 4 {
+        return Err(WireError::Malformed("truncated"));
+    }
+    let slen = u32::from_le_bytes(b[off + 64..off + 68].try_into().unwrap()) as usize;
+    if b.len() != off + 68 + slen {
+        return Err(WireError::Malformed("truncated"));
+    }
+    Ok(HiddenInit {
+        suite: b[1],
+        enc: b[6..off].to_vec(),
+        ratchet_pub: b[off..off + 32].try_into().unwrap(),
+        binder: b[off + 32..off + 64].try_into().unwrap(),
+        sealed: b[off + 68..].to_vec(),
+    })
+}
+
+/// Identité scellée d'un INIT masqué :
+/// `ed25519:32 | x25519:32 | uid_len:u16 | uid | did_len:u16 | did | sig:64`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SealedIdentity {
+    pub identity_ed25519: [u8; 32],
+    pub identity_x25519: [u8; 32],
+    pub user_id: String,
+    pub device_id: String,
+    pub sig: [u8; 64],
+}
+impl SealedIdentity {
+    /// Octets d'identité couverts par la signature (tout sauf `sig`).
+    #[must_use]
+    pub fn signed_part(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(64 + 4 + self.user_id.len() + self.device_id.len());
+        out.extend_from_slice(&self.identity_ed25519);
+        out.extend_from_slice(&self.identity_x25519);
+        for id in [&self.user_id, &self.device_id] {
+            out.extend_from_slice(&u16::try_from(id.len()).expect("len").to_le_bytes());
+            out.extend_from_slice(id.as_bytes());
+        }
+        out
+    }
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = self.signed_part();
+        out.extend_from_slice(&self.sig);
+        out
+    }
+    pub fn decode(b: &[u8]) -> Result<Self, WireError> {
+        if b.len() < 64 + 4 + 64 {
+            return Err(WireError::ShortHeader);
+        }
+        let mut off = 64;
+        let mut ids = [String::new(), String::new()];
+        for id in &mut ids {
+            if b.len() < off + 2 {
+                return Err(WireError::Malformed("truncated"));
+            }
+            let n = u16::from_le_bytes(b[off..off + 2].try_into().unwrap()) as usize;
+            off += 2;
+            if b.len() < off + n {
+                return Err(WireError::Malformed("truncated"));
+            }
+            *id = String::from_utf8(b[off..off + n].to_vec())
+                .map_err(|_| WireError::Malformed("utf8"))?;
+            off += n;
+        }
+        if b.len() != off + 64 {
+            return Err(WireError::Malformed("bad length"));
+        }
+        let [user_id, device_id] = ids;
+        Ok(Self {
+            identity_ed25519: b[..32].try_into().unwrap(),
+            identity_x25519: b[32..64].try_into().unwrap(),
+            user_id,
+            device_id,
+            sig: b[off..].try_into().unwrap(),
+        })
     }
-    Ok(b[5..5 + len].to_vec())
 }
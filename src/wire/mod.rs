@@ -1,13 +1,56 @@
 #![allow(
     clippy::missing_panics_doc,
     clippy::missing_errors_doc,
-    clippy::doc_markdown
+    clippy::doc_markdown,
+    clippy::type_complexity
 )]
+use crate::error::ErrorCode;
 use crate::ratchet::state::Header;
+use thiserror::Error;
 
+pub mod fragment;
 pub mod handshake;
+pub mod stream;
+pub mod version;
+pub mod view;
+
+pub use view::{FrameHeaderRef, InitFrame, MessageFrame};
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum WireError {
+    #[error("bad version {0:#06x}")]
+    BadVersion(u16),
+    #[error("short header")]
+    ShortHeader,
+    #[error("truncated ciphertext")]
+    TruncatedCt,
+    #[error("bad frame type {0:#04x}")]
+    BadType(u8),
+    #[error("malformed frame: {0}")]
+    Malformed(&'static str),
+    #[error("unsupported suite {0:#04x}")]
+    UnsupportedSuite(u8),
+    #[error("frame of {len} bytes exceeds limit {max}")]
+    FrameTooLarge { len: usize, max: usize },
+}
+impl WireError {
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::BadVersion(_) => ErrorCode::BadVersion,
+            Self::ShortHeader => ErrorCode::ShortHeader,
+            Self::TruncatedCt => ErrorCode::TruncatedCt,
+            Self::BadType(_) => ErrorCode::BadFrameType,
+            Self::Malformed(_) => ErrorCode::MalformedFrame,
+            Self::UnsupportedSuite(_) => ErrorCode::UnsupportedSuite,
+            Self::FrameTooLarge { .. } => ErrorCode::FrameTooLarge,
+        }
+    }
+}
 
 pub const HEADER_LEN: usize = 32 + 4 + 4;
+/// En-tête chiffré : nonce(24) | XChaCha20-Poly1305(header) (40 + tag 16).
+pub const ENC_HEADER_LEN: usize = 24 + HEADER_LEN + 16;
 
 #[must_use]
 pub fn header_to_bytes(h: &Header) -> [u8; HEADER_LEN] {
@@ -23,9 +66,9 @@ pub fn header_to_bytes(h: &Header) -> [u8; HEADER_LEN] {
 ///
 /// # Panics
 /// Panique si conversions internes échouent (données corrompues).
-pub fn header_from_bytes(b: &[u8]) -> anyhow::Result<Header> {
+pub fn header_from_bytes(b: &[u8]) -> Result<Header, WireError> {
     if b.len() < HEADER_LEN {
-        anyhow::bail!("short header");
+        return Err(WireError::ShortHeader);
     }
     let mut dh = [0u8; 32];
     dh.copy_from_slice(&b[0..32]);
@@ -34,83 +77,92 @@ pub fn header_from_bytes(b: &[u8]) -> anyhow::Result<Header> {
     Ok(Header { dh_pub: dh, pn, n })
 }
 
-#[must_use]
+/// Corps commun des trames message : `nonce | ct_len | ct`, suivi en v1.1 de
+/// `pad_len:u32 = 0` (le bourrage est dans le clair chiffré, voir
+/// `envelope::pad_plaintext`).
+fn put_body(out: &mut Vec<u8>, nonce: &[u8; 24], ct: &[u8], legacy_trailer: bool) {
+    out.extend_from_slice(nonce);
+    out.extend_from_slice(
+        &u32::try_from(ct.len())
+            .expect("ct len fits u32")
+            .to_le_bytes(),
+    );
+    out.extend_from_slice(ct);
+    if legacy_trailer {
+        out.extend_from_slice(&0u32.to_le_bytes());
+    }
+}
+
+/// Trame message au format v1.1 (voir [`version::encode_message`] pour
+/// l'émission dans une autre version). `pad_len` vaut toujours 0.
+///
+/// # Errors
+/// `BadVersion` si `ver` ≠ v1.1 : la disposition écrite est toujours v1.1.
 pub fn pack_message(
     ver: u16,
     header: &Header,
     nonce: &[u8; 24],
     ct: &[u8],
-    pad_to: usize,
-) -> Vec<u8> {
+) -> Result<Vec<u8>, WireError> {
+    if ver != version::WIRE_V1_1 {
+        return Err(WireError::BadVersion(ver));
+    }
     let hb = header_to_bytes(header);
     let mut out = Vec::with_capacity(2 + hb.len() + 24 + 4 + ct.len() + 4);
     out.extend_from_slice(&ver.to_le_bytes());
     out.extend_from_slice(&hb);
-    out.extend_from_slice(nonce);
-    out.extend_from_slice(
-        &u32::try_from(ct.len())
-            .expect("ct len fits u32")
-            .to_le_bytes(),
-    );
-    out.extend_from_slice(ct);
-    let cur = out.len() + 4;
-    let pad_len = if pad_to == 0 {
-        0
-    } else {
-        (pad_to - (cur % pad_to)) % pad_to
-    };
-    out.extend_from_slice(
-        &u32::try_from(pad_len)
-            .expect("pad len fits u32")
-            .to_le_bytes(),
-    );
-    if pad_len > 0 {
-        let zeros = vec![0u8; pad_len];
-        out.extend_from_slice(&zeros);
-    }
-    out
+    put_body(&mut out, nonce, ct, true);
+    Ok(out)
 }
 
+/// Décode une trame v1.1; toute autre version est rejetée (`BAD_VERSION`).
+///
 /// # Errors
-/// Erreur si trame trop courte ou tronquée.
+/// Erreur si version inconnue, trame trop courte ou tronquée.
 ///
 /// # Panics
 /// Panique si conversions internes échouent (données corrompues).
-pub fn unpack_message(b: &[u8]) -> anyhow::Result<(u16, Header, [u8; 24], Vec<u8>)> {
-    if b.len() < 2 + HEADER_LEN + 24 + 4 {
-        anyhow::bail!("short frame");
-    }
-    let ver = u16::from_le_bytes(b[0..2].try_into().unwrap());
-    let hdr = header_from_bytes(&b[2..2 + HEADER_LEN])?;
-    let mut nonce = [0u8; 24];
-    nonce.copy_from_slice(&b[2 + HEADER_LEN..2 + HEADER_LEN + 24]);
-    let ct_len = u32::from_le_bytes(
-        b[2 + HEADER_LEN + 24..2 + HEADER_LEN + 24 + 4]
-            .try_into()
-            .unwrap(),
-    ) as usize;
-    let start = 2 + HEADER_LEN + 24 + 4;
-    if b.len() < start + ct_len + 4 {
-        anyhow::bail!("truncated ct");
-    }
-    let ct = b[start..start + ct_len].to_vec();
-    let pad_len =
-        u32::from_le_bytes(b[start + ct_len..start + ct_len + 4].try_into().unwrap()) as usize;
-    let end = start + ct_len + 4 + pad_len;
-    if b.len() < end {
-        anyhow::bail!("truncated pad");
-    }
-    Ok((ver, hdr, nonce, ct))
+pub fn unpack_message(b: &[u8]) -> Result<(u16, Header, [u8; 24], Vec<u8>), WireError> {
+    let f = MessageFrame::parse_v1_1(b, false)?;
+    let FrameHeaderRef::Clear(h) = f.header else {
+        unreachable!("clear-header layout")
+    };
+    Ok((f.version, h, *f.nonce, f.ct.to_vec()))
 }
 
-#[must_use]
-pub fn pack_with_padding(
+/// Trame message v1.1 en mode en-têtes chiffrés : `header` remplacé par `enc_header`.
+///
+/// # Errors
+/// `BadVersion` si `ver` ≠ v1.1.
+pub fn pack_message_he(
     ver: u16,
-    header: &crate::ratchet::state::Header,
+    enc_header: &[u8; ENC_HEADER_LEN],
     nonce: &[u8; 24],
     ct: &[u8],
-    profile: crate::envelope::PadProfile,
-) -> Vec<u8> {
-    let f = pack_message(ver, header, nonce, ct, 1);
-    crate::envelope::apply_padding(f, profile)
+) -> Result<Vec<u8>, WireError> {
+    if ver != version::WIRE_V1_1 {
+        return Err(WireError::BadVersion(ver));
+    }
+    let mut out = Vec::with_capacity(2 + ENC_HEADER_LEN + 24 + 4 + ct.len() + 4);
+    out.extend_from_slice(&ver.to_le_bytes());
+    out.extend_from_slice(enc_header);
+    put_body(&mut out, nonce, ct, true);
+    Ok(out)
+}
+
+/// Décode une trame v1.1 à en-tête chiffré; toute autre version est rejetée.
+///
+/// # Errors
+/// Erreur si version inconnue, trame trop courte ou tronquée.
+///
+/// # Panics
+/// Panique si conversions internes échouent (données corrompues).
+pub fn unpack_message_he(
+    b: &[u8],
+) -> Result<(u16, [u8; ENC_HEADER_LEN], [u8; 24], Vec<u8>), WireError> {
+    let f = MessageFrame::parse_v1_1(b, true)?;
+    let FrameHeaderRef::Encrypted(eh) = f.header else {
+        unreachable!("encrypted-header layout")
+    };
+    Ok((f.version, *eh, *f.nonce, f.ct.to_vec()))
 }
@@ -0,0 +1,251 @@
+//! Codec de flux : trames préfixées par leur longueur (PROTOCOL.md §3quater).
+//!
+//! `stream_frame := len:u32 | frame[len]`, `frame` étant une trame message
+//! (toute version de [`super::version`]) ou de handshake. Les lectures
+//! partielles sont reprises là où elles se sont arrêtées (y compris après
+//! `WouldBlock`); toute trame annoncée au-delà de `max_frame` est refusée
+//! avant allocation.
+
+use super::version::DecodedMessage;
+use super::{MessageFrame, WireError};
+use std::io::{self, Read, Write};
+
+/// Taille maximale par défaut d'une trame (hors préfixe).
+pub const DEFAULT_MAX_FRAME: usize = 1 << 20;
+/// Taille du préfixe de longueur.
+pub const LEN_PREFIX: usize = 4;
+
+fn invalid(e: WireError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Lit le préfixe et vérifie la borne; `None` si moins de 4 octets.
+fn frame_len(prefix: &[u8], max_frame: usize) -> io::Result<Option<usize>> {
+    let Some(b) = prefix.get(..LEN_PREFIX) else {
+        return Ok(None);
+    };
+    let len = u32::from_le_bytes(b.try_into().unwrap()) as usize;
+    if len > max_frame {
+        return Err(invalid(WireError::FrameTooLarge {
+            len,
+            max: max_frame,
+        }));
+    }
+    Ok(Some(len))
+}
+
+/// Préfixe d'une trame sortante; `InvalidInput` si elle dépasse la borne.
+fn len_prefix(len: usize, max_frame: usize) -> io::Result<[u8; LEN_PREFIX]> {
+    u32::try_from(len)
+        .ok()
+        .filter(|_| len <= max_frame)
+        .map(u32::to_le_bytes)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                WireError::FrameTooLarge {
+                    len,
+                    max: max_frame,
+                },
+            )
+        })
+}
+
+/// Lecteur de trames sur un `Read` (socket TCP/Unix, pipe...).
+///
+/// Lit exactement les octets nécessaires : envelopper `inner` dans un
+/// `BufReader` pour limiter les appels système.
+pub struct FrameReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    filled: usize,
+    consumed: usize,
+    max_frame: usize,
+}
+
+impl<R: Read> FrameReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self::with_max_frame(inner, DEFAULT_MAX_FRAME)
+    }
+    pub fn with_max_frame(inner: R, max_frame: usize) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+            filled: 0,
+            consumed: 0,
+            max_frame,
+        }
+    }
+
+    /// Remplit `buf[..n]`; `false` si fin de flux avant `n` octets.
+    fn fill_to(&mut self, n: usize) -> io::Result<bool> {
+        if self.buf.len() < n {
+            self.buf.resize(n, 0);
+        }
+        while self.filled < n {
+            match self.inner.read(&mut self.buf[self.filled..n]) {
+                Ok(0) => return Ok(false),
+                Ok(k) => self.filled += k,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Trame suivante, empruntée au tampon interne jusqu'au prochain appel.
+    ///
+    /// `Ok(None)` en fin de flux propre (entre deux trames).
+    ///
+    /// # Errors
+    /// `UnexpectedEof` si le flux s'arrête au milieu d'une trame; `InvalidData`
+    /// (`WireError::FrameTooLarge`) si la longueur annoncée dépasse la borne;
+    /// erreurs de `inner` (un `WouldBlock` peut être repris par un nouvel appel).
+    pub fn next_frame(&mut self) -> io::Result<Option<&[u8]>> {
+        if self.consumed > 0 {
+            self.buf.copy_within(self.consumed..self.filled, 0);
+            self.filled -= self.consumed;
+            self.consumed = 0;
+        }
+        if !self.fill_to(LEN_PREFIX)? {
+            if self.filled == 0 {
+                return Ok(None);
+            }
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let len = frame_len(&self.buf, self.max_frame)?.unwrap_or_default();
+        if !self.fill_to(LEN_PREFIX + len)? {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.consumed = LEN_PREFIX + len;
+        Ok(Some(&self.buf[LEN_PREFIX..LEN_PREFIX + len]))
+    }
+
+    /// Trame message suivante, décodée (propriétaire) pour `ratchet::decrypt`.
+    /// `encrypted_header` est le mode de la session (voir `version::decode_message`).
+    ///
+    /// # Errors
+    /// Celles de [`Self::next_frame`]; `InvalidData` portant la `WireError`
+    /// si la trame est invalide (version inconnue, tronquée...).
+    pub fn next_message(&mut self, encrypted_header: bool) -> io::Result<Option<DecodedMessage>> {
+        Ok(self
+            .next_message_ref(encrypted_header)?
+            .map(MessageFrame::into_owned))
+    }
+
+    /// Comme [`Self::next_message`], sans copie : vue sur le tampon interne.
+    ///
+    /// # Errors
+    /// Voir [`Self::next_message`].
+    pub fn next_message_ref(
+        &mut self,
+        encrypted_header: bool,
+    ) -> io::Result<Option<MessageFrame<'_>>> {
+        match self.next_frame()? {
+            Some(f) => MessageFrame::parse(f, encrypted_header)
+                .map(Some)
+                .map_err(invalid),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// Écrivain de trames sur un `Write`.
+pub struct FrameWriter<W> {
+    inner: W,
+    max_frame: usize,
+}
+
+impl<W: Write> FrameWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self::with_max_frame(inner, DEFAULT_MAX_FRAME)
+    }
+    pub fn with_max_frame(inner: W, max_frame: usize) -> Self {
+        Self { inner, max_frame }
+    }
+
+    /// Écrit `len:u32 | frame`.
+    ///
+    /// # Errors
+    /// `InvalidInput` (`WireError::FrameTooLarge`) si `frame` dépasse la borne;
+    /// erreurs de `inner`.
+    pub fn write_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+        let prefix = len_prefix(frame.len(), self.max_frame)?;
+        self.inner.write_all(&prefix)?;
+        self.inner.write_all(frame)
+    }
+
+    /// # Errors
+    /// Erreurs de `inner`.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Codec `tokio_util` équivalent (feature `tokio`) : décode en `BytesMut`
+/// (découpé du tampon de lecture, sans copie), encode depuis `&[u8]`.
+#[cfg(feature = "tokio")]
+#[derive(Clone, Copy, Debug)]
+pub struct FrameCodec {
+    max_frame: usize,
+}
+
+#[cfg(feature = "tokio")]
+impl FrameCodec {
+    #[must_use]
+    pub fn new(max_frame: usize) -> Self {
+        Self { max_frame }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Default for FrameCodec {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_FRAME)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio_util::codec::Decoder for FrameCodec {
+    type Item = bytes::BytesMut;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut bytes::BytesMut) -> io::Result<Option<bytes::BytesMut>> {
+        use bytes::Buf;
+        let Some(len) = frame_len(src, self.max_frame)? else {
+            return Ok(None);
+        };
+        if src.len() < LEN_PREFIX + len {
+            src.reserve(LEN_PREFIX + len - src.len());
+            return Ok(None);
+        }
+        src.advance(LEN_PREFIX);
+        Ok(Some(src.split_to(len)))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio_util::codec::Encoder<&[u8]> for FrameCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, frame: &[u8], dst: &mut bytes::BytesMut) -> io::Result<()> {
+        let prefix = len_prefix(frame.len(), self.max_frame)?;
+        dst.reserve(LEN_PREFIX + frame.len());
+        dst.extend_from_slice(&prefix);
+        dst.extend_from_slice(frame);
+        Ok(())
+    }
+}
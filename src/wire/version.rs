@@ -0,0 +1,143 @@
+//! Registre des versions de trame message (PROTOCOL.md §3ter).
+//!
+//! Le décodage lit `ver:u16`, choisit la disposition correspondante et
+//! rejette toute version inconnue (`BAD_VERSION`). L'émission se fait dans la
+//! version demandée, ce qui permet de lire des trames v1.1 tout en émettant
+//! une version plus récente pendant un déploiement mixte.
+
+use super::{header_to_bytes, put_body, MessageFrame, WireError, ENC_HEADER_LEN};
+use crate::ratchet::state::Header;
+
+/// Trame v1.1 : `ver | header | nonce | ct_len | ct | pad_len | pad` (§3, §3bis).
+pub const WIRE_V1_1: u16 = crate::HL_VERSION;
+/// Trame v1.2 : octet `flags` après `ver`, sans `pad_len | pad` final (§3ter).
+pub const WIRE_V1_2: u16 = 0x0120;
+/// Version émise par défaut.
+pub const WIRE_CURRENT: u16 = WIRE_V1_2;
+
+/// Bit de `flags` (v1.2) : l'en-tête est chiffré (80 o au lieu de 40 o).
+pub const FLAG_ENC_HEADER: u8 = 0x01;
+
+/// Disposition d'une version de trame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameLayout {
+    pub version: u16,
+    pub name: &'static str,
+    /// Octet `flags` présent après `ver`.
+    pub has_flags: bool,
+    /// `pad_len | pad` après `ct` (hérité, toujours émis à 0).
+    pub padding_trailer: bool,
+}
+
+/// Versions acceptées en réception, de la plus ancienne à la plus récente.
+pub const LAYOUTS: [FrameLayout; 2] = [
+    FrameLayout {
+        version: WIRE_V1_1,
+        name: "v1.1",
+        has_flags: false,
+        padding_trailer: true,
+    },
+    FrameLayout {
+        version: WIRE_V1_2,
+        name: "v1.2",
+        has_flags: true,
+        padding_trailer: false,
+    },
+];
+
+/// # Errors
+/// `BadVersion` si la version n'est pas dans [`LAYOUTS`].
+pub fn layout(version: u16) -> Result<&'static FrameLayout, WireError> {
+    LAYOUTS
+        .iter()
+        .find(|l| l.version == version)
+        .ok_or(WireError::BadVersion(version))
+}
+
+#[must_use]
+pub fn is_supported(version: u16) -> bool {
+    layout(version).is_ok()
+}
+
+/// Lit `ver:u16` sans valider la version.
+///
+/// # Errors
+/// `ShortHeader` si la trame fait moins de 2 octets.
+pub fn peek_version(b: &[u8]) -> Result<u16, WireError> {
+    match b {
+        [lo, hi, ..] => Ok(u16::from_le_bytes([*lo, *hi])),
+        _ => Err(WireError::ShortHeader),
+    }
+}
+
+/// En-tête de trame, en clair ou chiffré.
+#[derive(Clone, Debug)]
+pub enum FrameHeader {
+    Clear(Header),
+    Encrypted([u8; ENC_HEADER_LEN]),
+}
+
+/// Trame message décodée, quelle que soit sa version.
+#[derive(Clone, Debug)]
+pub struct DecodedMessage {
+    pub version: u16,
+    pub header: FrameHeader,
+    pub nonce: [u8; 24],
+    pub ct: Vec<u8>,
+}
+
+/// Encode une trame message dans la version `version`.
+///
+/// # Errors
+/// `BadVersion` si la version est inconnue; `Malformed` si un en-tête
+/// chiffré est demandé en v1.1 (la disposition ne permet pas de le signaler,
+/// utiliser `pack_message_he`).
+pub fn encode_message(
+    version: u16,
+    header: &FrameHeader,
+    nonce: &[u8; 24],
+    ct: &[u8],
+) -> Result<Vec<u8>, WireError> {
+    let l = layout(version)?;
+    let mut out = Vec::with_capacity(3 + ENC_HEADER_LEN + 24 + 4 + ct.len() + 4);
+    out.extend_from_slice(&version.to_le_bytes());
+    match header {
+        FrameHeader::Clear(h) => {
+            if l.has_flags {
+                out.push(0);
+            }
+            out.extend_from_slice(&header_to_bytes(h));
+        }
+        FrameHeader::Encrypted(eh) => {
+            if !l.has_flags {
+                return Err(WireError::Malformed("v1.1 cannot flag encrypted header"));
+            }
+            out.push(FLAG_ENC_HEADER);
+            out.extend_from_slice(eh);
+        }
+    }
+    put_body(&mut out, nonce, ct, l.padding_trailer);
+    Ok(out)
+}
+
+/// Décode une trame message en choisissant la disposition selon `ver`.
+///
+/// `encrypted_header` est le mode négocié pour la session : une trame v1.1 ne
+/// le signale pas, une trame v1.2 dont l'octet `flags` le contredit est rejetée.
+///
+/// # Errors
+/// `BadVersion` si version inconnue; `Malformed` si drapeaux inconnus ou
+/// contraires au mode, ou octets après `ct` (v1.2);
+/// `ShortHeader`/`TruncatedCt` si trame incomplète.
+pub fn decode_message(b: &[u8], encrypted_header: bool) -> Result<DecodedMessage, WireError> {
+    MessageFrame::parse(b, encrypted_header).map(MessageFrame::into_owned)
+}
+
+/// Réémet une trame reçue (toute version connue) dans la version `to`.
+///
+/// # Errors
+/// Voir [`decode_message`] et [`encode_message`].
+pub fn transcode(b: &[u8], encrypted_header: bool, to: u16) -> Result<Vec<u8>, WireError> {
+    let m = decode_message(b, encrypted_header)?;
+    encode_message(to, &m.header, &m.nonce, &m.ct)
+}
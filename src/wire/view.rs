@@ -0,0 +1,201 @@
+//! Vues empruntées sur les trames : bornes vérifiées une seule fois, champs
+//! exposés comme tranches du tampon d'entrée (aucune allocation).
+//!
+//! Les API propriétaires (`unpack_message`, `decode_message`, `decode_init`...)
+//! sont construites au-dessus.
+
+use super::handshake::TYPE_INIT;
+use super::version::{layout, peek_version, DecodedMessage, FrameHeader, FLAG_ENC_HEADER};
+use super::{header_from_bytes, WireError, ENC_HEADER_LEN, HEADER_LEN};
+use crate::ratchet::state::Header;
+
+const KNOWN_FLAGS: u8 = FLAG_ENC_HEADER;
+
+/// En-tête d'une [`MessageFrame`] : décodé (40 o, sans allocation) ou chiffré.
+#[derive(Clone, Debug)]
+pub enum FrameHeaderRef<'a> {
+    Clear(Header),
+    Encrypted(&'a [u8; ENC_HEADER_LEN]),
+}
+
+/// Trame message empruntée, toute version de [`super::version::LAYOUTS`].
+#[derive(Clone, Debug)]
+pub struct MessageFrame<'a> {
+    pub version: u16,
+    pub header: FrameHeaderRef<'a>,
+    pub nonce: &'a [u8; 24],
+    pub ct: &'a [u8],
+}
+
+/// Découpe `header(hdr_len) | nonce | ct_len | ct` (sans le préfixe de version).
+///
+/// Avec `legacy_trailer` (v1.1), lit ensuite `pad_len | pad` : le bourrage
+/// externe d'anciens émetteurs est ignoré. Sinon aucun octet ne doit suivre `ct`.
+fn split_body(
+    b: &[u8],
+    hdr_len: usize,
+    legacy_trailer: bool,
+) -> Result<(&[u8], &[u8; 24], &[u8]), WireError> {
+    if b.len() < hdr_len + 24 + 4 {
+        return Err(WireError::ShortHeader);
+    }
+    let nonce = b[hdr_len..hdr_len + 24].try_into().unwrap();
+    let start = hdr_len + 24 + 4;
+    let ct_len = u32::from_le_bytes(b[start - 4..start].try_into().unwrap()) as usize;
+    let ct_end = start.checked_add(ct_len).ok_or(WireError::TruncatedCt)?;
+    if b.len() < ct_end {
+        return Err(WireError::TruncatedCt);
+    }
+    if legacy_trailer {
+        if b.len() < ct_end + 4 {
+            return Err(WireError::TruncatedCt);
+        }
+        let pad_len = u32::from_le_bytes(b[ct_end..ct_end + 4].try_into().unwrap()) as usize;
+        if b.len() - (ct_end + 4) < pad_len {
+            return Err(WireError::TruncatedCt);
+        }
+    } else if b.len() != ct_end {
+        return Err(WireError::Malformed("trailing bytes after ciphertext"));
+    }
+    Ok((&b[..hdr_len], nonce, &b[start..ct_end]))
+}
+
+fn split_header(
+    body: &[u8],
+    encrypted: bool,
+    legacy_trailer: bool,
+) -> Result<(FrameHeaderRef<'_>, &[u8; 24], &[u8]), WireError> {
+    if encrypted {
+        let (eh, nonce, ct) = split_body(body, ENC_HEADER_LEN, legacy_trailer)?;
+        Ok((FrameHeaderRef::Encrypted(eh.try_into().unwrap()), nonce, ct))
+    } else {
+        let (hb, nonce, ct) = split_body(body, HEADER_LEN, legacy_trailer)?;
+        Ok((FrameHeaderRef::Clear(header_from_bytes(hb)?), nonce, ct))
+    }
+}
+
+impl<'a> MessageFrame<'a> {
+    /// Choisit la disposition selon `ver`. `encrypted_header` est le mode de la
+    /// session : il fixe la disposition v1.1 (rien ne le signale dans la trame)
+    /// et doit correspondre à l'octet `flags` en v1.2.
+    ///
+    /// # Errors
+    /// `BadVersion` si version inconnue; `Malformed` si drapeaux inconnus ou
+    /// contraires au mode, ou octets après `ct` (v1.2);
+    /// `ShortHeader`/`TruncatedCt` si trame incomplète.
+    pub fn parse(b: &'a [u8], encrypted_header: bool) -> Result<Self, WireError> {
+        let version = peek_version(b)?;
+        let l = layout(version)?;
+        let (flags, body) = if l.has_flags {
+            match b.get(2) {
+                Some(&f) => (f, &b[3..]),
+                None => return Err(WireError::ShortHeader),
+            }
+        } else if encrypted_header {
+            (FLAG_ENC_HEADER, &b[2..])
+        } else {
+            (0, &b[2..])
+        };
+        if flags & !KNOWN_FLAGS != 0 {
+            return Err(WireError::Malformed("unknown frame flags"));
+        }
+        if (flags & FLAG_ENC_HEADER != 0) != encrypted_header {
+            return Err(WireError::Malformed("header encryption mode mismatch"));
+        }
+        let (header, nonce, ct) =
+            split_header(body, flags & FLAG_ENC_HEADER != 0, l.padding_trailer)?;
+        Ok(Self {
+            version,
+            header,
+            nonce,
+            ct,
+        })
+    }
+
+    /// Trame v1.1 uniquement, en-tête chiffré ou non selon la session (§3/§3bis).
+    ///
+    /// # Errors
+    /// `ShortHeader` si trop courte; `BadVersion` si `ver` ≠ v1.1; `TruncatedCt`.
+    pub fn parse_v1_1(b: &'a [u8], encrypted_header: bool) -> Result<Self, WireError> {
+        let hdr_len = if encrypted_header {
+            ENC_HEADER_LEN
+        } else {
+            HEADER_LEN
+        };
+        if b.len() < 2 + hdr_len + 24 + 4 {
+            return Err(WireError::ShortHeader);
+        }
+        let version = peek_version(b)?;
+        if version != super::version::WIRE_V1_1 {
+            return Err(WireError::BadVersion(version));
+        }
+        let (header, nonce, ct) = split_header(&b[2..], encrypted_header, true)?;
+        Ok(Self {
+            version,
+            header,
+            nonce,
+            ct,
+        })
+    }
+
+    #[must_use]
+    pub fn into_owned(self) -> DecodedMessage {
+        DecodedMessage {
+            version: self.version,
+            header: match self.header {
+                FrameHeaderRef::Clear(h) => FrameHeader::Clear(h),
+                FrameHeaderRef::Encrypted(eh) => FrameHeader::Encrypted(*eh),
+            },
+            nonce: *self.nonce,
+            ct: self.ct.to_vec(),
+        }
+    }
+}
+
+/// Trame INIT v2 empruntée : `0x01 | suite | enc_len:u32 | enc | binder32`.
+#[derive(Clone, Copy, Debug)]
+pub struct InitFrame<'a> {
+    pub suite: u8,
+    pub enc: &'a [u8],
+    pub binder: &'a [u8; 32],
+}
+
+impl<'a> InitFrame<'a> {
+    /// # Errors
+    /// `ShortHeader`, `BadType` ou `Malformed("truncated")`.
+    pub fn parse(b: &'a [u8]) -> Result<Self, WireError> {
+        if b.len() < 1 + 1 + 4 + 32 {
+            return Err(WireError::ShortHeader);
+        }
+        if b[0] != TYPE_INIT {
+            return Err(WireError::BadType(b[0]));
+        }
+        let len = u32::from_le_bytes(b[2..6].try_into().unwrap()) as usize;
+        if b.len() - 6 - 32 < len {
+            return Err(WireError::Malformed("truncated"));
+        }
+        Ok(Self {
+            suite: b[1],
+            enc: &b[6..6 + len],
+            binder: b[6 + len..6 + len + 32].try_into().unwrap(),
+        })
+    }
+
+    /// INIT v1 (`0x01 | enc_len:u32 | enc`) : seule `enc` est transportée.
+    ///
+    /// # Errors
+    /// `ShortHeader`, `BadType` ou `Malformed("truncated")`.
+    pub fn parse_v1_enc(b: &'a [u8]) -> Result<&'a [u8], WireError> {
+        if b.len() < 5 {
+            return Err(WireError::ShortHeader);
+        }
+        if b[0] != TYPE_INIT {
+            return Err(WireError::BadType(b[0]));
+        }
+        let len = u32::from_le_bytes(b[1..5].try_into().unwrap()) as usize;
+        if b.len() - 5 < len {
+            return Err(WireError::Malformed("truncated"));
+        }
+        Ok(&b[5..5 + len])
+    }
+}
@@ -0,0 +1,41 @@
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::ratchet;
+use hardlock_snc::session::{bootstrap_initiator, bootstrap_responder};
+use hardlock_snc::wire::handshake::decode_init_v3;
+use hardlock_snc::wire::WireError;
+
+#[test]
+fn bootstrap_uses_ephemeral_ratchet_keys() {
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let (frame, mut ra) = bootstrap_initiator(&bob.x25519.public()).unwrap();
+    let (_, _, ratchet_pub, _) = decode_init_v3(&frame).unwrap();
+    assert_eq!(ra.dh_s_pub, ratchet_pub);
+    assert_ne!(ratchet_pub, bob.x25519.public());
+    let mut rb = bootstrap_responder(&bob, &frame).unwrap();
+    assert_eq!(rb.dh_s_pub, [0u8; 32]);
+
+    let ad = b"boot";
+    for i in 0..3u8 {
+        let (h, n, ct) = ratchet::encrypt(&mut ra, ad, &[i]).unwrap();
+        assert_eq!(ratchet::decrypt(&mut rb, ad, &h, &n, &ct).unwrap(), [i]);
+        let (h, n, ct) = ratchet::encrypt(&mut rb, ad, &[i, i]).unwrap();
+        assert_ne!(h.dh_pub, [0u8; 32]);
+        assert_ne!(h.dh_pub, bob.x25519.public());
+        assert_eq!(ratchet::decrypt(&mut ra, ad, &h, &n, &ct).unwrap(), [i, i]);
+    }
+}
+
+#[test]
+fn bootstrap_rejects_swapped_ratchet_key() {
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let (mut frame, _ra) = bootstrap_initiator(&bob.x25519.public()).unwrap();
+    let off = frame.len() - 64;
+    frame[off] ^= 1;
+    assert!(bootstrap_responder(&bob, &frame).is_err());
+    frame[off] ^= 1;
+    frame.push(0);
+    assert_eq!(
+        decode_init_v3(&frame),
+        Err(WireError::Malformed("trailing bytes"))
+    );
+}
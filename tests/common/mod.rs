@@ -0,0 +1,37 @@
+//! Paires ratchet partagées par les tests d'intégration, établies par la
+//! poignée de main : clés ratchet éphémères, l'identité du répondeur ne sert
+//! qu'au décapsulage HPKE.
+#![allow(dead_code)]
+
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::ratchet::state::RatchetState;
+use hardlock_snc::session::{Handshake, InitKind, DEFAULT_HANDSHAKE_TIMEOUT};
+use std::time::Instant;
+
+fn established(header_encryption: bool) -> (RatchetState, RatchetState) {
+    let b = DeviceIdentity::generate("b".into(), "d2".into());
+    let (mut hs_a, init) = Handshake::initiate_with(
+        InitKind::Plain,
+        "b".into(),
+        &b.x25519.public(),
+        DEFAULT_HANDSHAKE_TIMEOUT,
+        header_encryption,
+    )
+    .unwrap();
+    let (hs_b, resp) = Handshake::respond(&b, "a".into(), &init).unwrap();
+    hs_a.on_response(&resp, Instant::now()).unwrap();
+    (
+        hs_a.into_session().unwrap().state,
+        hs_b.into_session().unwrap().state,
+    )
+}
+
+/// `(initiateur, répondeur)` à en-têtes en clair.
+pub fn pair() -> (RatchetState, RatchetState) {
+    established(false)
+}
+
+/// `(initiateur, répondeur)` à en-têtes chiffrés.
+pub fn pair_he() -> (RatchetState, RatchetState) {
+    established(true)
+}
@@ -28,10 +28,66 @@ fn token_expired() {
 #[test]
 fn padding_buckets() {
     let v = vec![1u8; 300];
-    let s = apply_padding(v.clone(), PadProfile::Stealth);
+    let s = pad_plaintext(&v, PadProfile::Stealth);
     assert_eq!(s.len(), 512);
-    let b = apply_padding(v.clone(), PadProfile::Balanced);
+    let b = pad_plaintext(&v, PadProfile::Balanced);
     assert_eq!(b.len(), 512);
-    let t = apply_padding(v, PadProfile::Throughput);
+    let t = pad_plaintext(&v, PadProfile::Throughput);
     assert_eq!(t.len(), 1024);
+    assert_eq!(unpad_plaintext(&t), Some(&v[..]));
+}
+
+#[test]
+fn unpadding_is_unambiguous() {
+    for pt in [&b""[..], b"\x00\x00", b"\x80", b"abc\x80\x00"] {
+        let p = pad_plaintext(pt, PadProfile::Stealth);
+        assert_eq!(unpad_plaintext(&p), Some(pt));
+    }
+    // Bucket exactement rempli : le marqueur passe au seau suivant.
+    assert_eq!(pad_plaintext(&[7u8; 256], PadProfile::Stealth).len(), 512);
+    assert_eq!(unpad_plaintext(&[0u8; 16]), None);
+    assert_eq!(unpad_plaintext(b"abc\x01\x00"), None);
+    assert_eq!(unpad_plaintext(b""), None);
+}
+
+#[test]
+fn padme_buckets() {
+    assert_eq!(padme(0), 0);
+    assert_eq!(padme(100), 104);
+    assert_eq!(padme(1000), 1024);
+    assert_eq!(padme(1025), 1088);
+    for len in (2..200_000).step_by(97) {
+        let p = padme(len);
+        assert!(p >= len && (p - len) * 100 <= len * 12, "len {len} -> {p}");
+        assert_eq!(padme(p), p);
+    }
+    let p = pad_plaintext(&[3u8; 5000], PadProfile::Padme);
+    assert_eq!(p.len(), padme(5001));
+    assert_eq!(unpad_plaintext(&p).map(<[u8]>::len), Some(5000));
+}
+
+#[test]
+fn custom_buckets_and_stats() {
+    assert!(PadBuckets::new(&[]).is_none());
+    assert!(PadBuckets::new(&[64, 64]).is_none());
+    assert!(PadBuckets::new(&[1; 9]).is_none());
+    let b = PadBuckets::new(&[64, 160, 640]).unwrap();
+    let prof = PadProfile::Custom(b);
+    assert_eq!(pad_bucket_for(10, prof), 64);
+    assert_eq!(pad_bucket_for(161, prof), 640);
+    // Au-delà du dernier seau : Padmé plutôt qu'un multiple fixe.
+    assert_eq!(pad_bucket_for(100_000, prof), padme(100_000));
+
+    let lens = [10usize, 150, 600, 3000, 70_000];
+    let padme_st = PadStats::simulate(PadProfile::Padme, lens);
+    let tp_st = PadStats::simulate(PadProfile::Throughput, lens);
+    assert_eq!(padme_st.messages, 5);
+    assert_eq!(padme_st.payload_bytes, lens.iter().sum::<usize>() as u64);
+    assert!(padme_st.overhead_ratio() < tp_st.overhead_ratio());
+    assert!(tp_st.max_overhead_ratio > 100.0);
+
+    let mut st = PadStats::default();
+    st.record(100, 128);
+    assert_eq!(st.overhead_bytes(), 28);
+    assert!((st.overhead_ratio() - 0.28).abs() < 1e-9);
 }
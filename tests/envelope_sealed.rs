@@ -0,0 +1,169 @@
+use hardlock_snc::envelope::transport::decode_transport_header_ref;
+use hardlock_snc::envelope::{
+    derive_k_s, open_envelope, seal_envelope, EnvelopeError, PadProfile, SenderClaims,
+    ENVELOPE_MAX_SKEW_S,
+};
+use hardlock_snc::error::{Error, ErrorCode};
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::ratchet;
+use hardlock_snc::session::{bootstrap_initiator, bootstrap_responder};
+use hardlock_snc::suites;
+use hardlock_snc::wire::{pack_message, unpack_message, WireError};
+use hardlock_snc::HL_VERSION;
+
+const NOW: u64 = 1_700_000_000;
+
+fn claims(scope: &[u8]) -> SenderClaims<'_> {
+    SenderClaims {
+        sender_pub: [0xA1; 32],
+        scope,
+        expiry_unix_s: NOW + 3600,
+    }
+}
+
+#[test]
+fn envelope_roundtrip_carries_ratchet_frame() {
+    let bob = DeviceIdentity::generate("bob".into(), "d".into());
+    let (init, mut ra) = bootstrap_initiator(&bob.x25519.public()).unwrap();
+    let mut rb = bootstrap_responder(&bob, &init).unwrap();
+    let k_s = derive_k_s(b"master", b"salt");
+
+    let (h, n, ct) =
+        ratchet::encrypt_padded(&mut ra, b"ad", b"hello", PadProfile::Balanced).unwrap();
+    let frame = pack_message(HL_VERSION, &h, &n, &ct).unwrap();
+    let env = seal_envelope(
+        &k_s,
+        suites::HL1_BASE,
+        NOW,
+        PadProfile::Balanced,
+        &claims(b"chat"),
+        &frame,
+    )
+    .unwrap();
+
+    let (th, token) = decode_transport_header_ref(&env).unwrap();
+    assert_eq!(th.token_len as usize, token.len());
+
+    let o = open_envelope(&k_s, &env, NOW).unwrap();
+    assert_eq!(o.header, th);
+    assert_eq!(o.header.suite, suites::HL1_BASE);
+    assert_eq!(o.header.ts_unix_s, NOW);
+    assert_eq!(o.sender_pub, [0xA1; 32]);
+    assert_eq!(o.scope, b"chat");
+    assert_eq!(o.expiry_unix_s, NOW + 3600);
+    assert_eq!(o.frame, &frame[..]);
+
+    let (_, hh, nn, cc) = unpack_message(o.frame).unwrap();
+    assert_eq!(
+        ratchet::decrypt_padded(&mut rb, b"ad", &hh, &nn, &cc).unwrap(),
+        b"hello"
+    );
+}
+
+#[test]
+fn header_and_frame_are_authenticated() {
+    let k_s = derive_k_s(b"master", b"salt");
+    let env = seal_envelope(
+        &k_s,
+        suites::HL1_HYB,
+        NOW,
+        PadProfile::Padme,
+        &claims(b""),
+        b"frame",
+    )
+    .unwrap();
+    let (_, token) = decode_transport_header_ref(&env).unwrap();
+    let token_at = env.len() - b"frame".len() - token.len();
+
+    // ts, nonce, profil, jeton, trame : tout octet altéré invalide le jeton.
+    for i in [1, 9, token_at - 1, token_at, env.len() - 1] {
+        let mut bad = env.clone();
+        bad[i] ^= 1;
+        assert_eq!(
+            open_envelope(&k_s, &bad, NOW).unwrap_err(),
+            EnvelopeError::BadToken
+        );
+    }
+    let mut swapped = env.clone();
+    swapped[0] = suites::HL1_BASE;
+    assert_eq!(
+        open_envelope(&k_s, &swapped, NOW).unwrap_err(),
+        EnvelopeError::BadToken
+    );
+    let mut longer = env.clone();
+    longer.push(0);
+    assert!(open_envelope(&k_s, &longer, NOW).is_err());
+
+    let other = derive_k_s(b"master", b"other-salt");
+    assert_eq!(
+        open_envelope(&other, &env, NOW).unwrap_err().code(),
+        ErrorCode::BadAead
+    );
+}
+
+#[test]
+fn expiry_suite_and_truncation() {
+    let k_s = derive_k_s(b"master", b"salt");
+    let env = seal_envelope(
+        &k_s,
+        suites::HL1_AUTH,
+        NOW + 3600,
+        PadProfile::Stealth,
+        &claims(b"s"),
+        b"f",
+    )
+    .unwrap();
+    assert!(open_envelope(&k_s, &env, NOW + 3600).is_ok());
+    let e = open_envelope(&k_s, &env, NOW + 3601).unwrap_err();
+    assert_eq!(e, EnvelopeError::Expired);
+    assert_eq!(Error::from(e).code(), ErrorCode::TokenExpired);
+
+    let mut unknown = env.clone();
+    unknown[0] = 0x7f;
+    assert_eq!(
+        open_envelope(&k_s, &unknown, NOW).unwrap_err(),
+        EnvelopeError::Wire(WireError::UnsupportedSuite(0x7f))
+    );
+    let (_, token) = decode_transport_header_ref(&env).unwrap();
+    // Jeton tronqué.
+    assert!(matches!(
+        open_envelope(&k_s, &env[..env.len() - token.len()], NOW),
+        Err(EnvelopeError::Wire(WireError::Malformed(_)))
+    ));
+}
+
+#[test]
+fn stale_envelopes_and_long_scopes_are_rejected() {
+    let k_s = derive_k_s(b"master", b"salt");
+    let env = seal_envelope(
+        &k_s,
+        suites::HL1_BASE,
+        NOW,
+        PadProfile::Balanced,
+        &claims(b"s"),
+        b"f",
+    )
+    .unwrap();
+    assert!(open_envelope(&k_s, &env, NOW + ENVELOPE_MAX_SKEW_S).is_ok());
+    assert!(open_envelope(&k_s, &env, NOW - ENVELOPE_MAX_SKEW_S).is_ok());
+    let e = open_envelope(&k_s, &env, NOW + ENVELOPE_MAX_SKEW_S + 1).unwrap_err();
+    assert_eq!(e, EnvelopeError::Stale);
+    assert_eq!(e.code(), ErrorCode::StaleEnvelope);
+    assert_eq!(
+        open_envelope(&k_s, &env, NOW - ENVELOPE_MAX_SKEW_S - 1).unwrap_err(),
+        EnvelopeError::Stale
+    );
+
+    let long = vec![0u8; usize::from(u16::MAX) + 1];
+    let e = seal_envelope(
+        &k_s,
+        suites::HL1_BASE,
+        NOW,
+        PadProfile::Balanced,
+        &claims(&long),
+        b"f",
+    )
+    .unwrap_err();
+    assert_eq!(e, EnvelopeError::ScopeTooLong);
+    assert_eq!(e.code(), ErrorCode::LengthOverflow);
+}
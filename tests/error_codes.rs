@@ -0,0 +1,97 @@
+use hardlock_snc::crypto::hpke_hybrid::{
+    hpke_accept_with_binder, hpke_initiate_with_binder, HpkeError,
+};
+use hardlock_snc::error::{Error, ErrorCode};
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::ratchet::{self, RatchetError};
+use hardlock_snc::session::bootstrap_initiator;
+use hardlock_snc::wire::handshake::decode_init_v3;
+use hardlock_snc::wire::{pack_message, unpack_message, WireError};
+use hardlock_snc::{suites, HL_VERSION};
+use std::collections::HashSet;
+
+#[test]
+fn codes_are_unique_and_roundtrip_through_ffi() {
+    let mut seen = HashSet::new();
+    for c in ErrorCode::ALL {
+        assert!(seen.insert(c as i32));
+        assert!(c.as_ffi() < 0);
+        assert_eq!(ErrorCode::from_ffi(c.as_ffi()), Some(c));
+    }
+    assert_eq!(ErrorCode::BadVersion as i32, 1);
+    assert_eq!(ErrorCode::BadAead.name(), "BAD_AEAD");
+    assert_eq!(ErrorCode::from_ffi(0), None);
+}
+
+#[test]
+fn layers_map_to_protocol_codes() {
+    assert_eq!(
+        unpack_message(&[0u8; 4]).unwrap_err(),
+        WireError::ShortHeader
+    );
+    let bob = DeviceIdentity::generate("bob".into(), "d".into());
+    let (_frame, mut ra) = bootstrap_initiator(&bob.x25519.public()).unwrap();
+    let (h, n, ct) = ratchet::encrypt(&mut ra, b"ad", b"hello").unwrap();
+    let frame = pack_message(HL_VERSION, &h, &n, &ct).unwrap();
+    let err = unpack_message(&frame[..frame.len() - 8]).unwrap_err();
+    assert_eq!(Error::from(err).code(), ErrorCode::TruncatedCt);
+    assert_eq!(
+        decode_init_v3(&frame).unwrap_err().code(),
+        ErrorCode::BadFrameType
+    );
+
+    let (enc, _okm, mut binder) =
+        hpke_initiate_with_binder(&bob.x25519.public(), suites::HL1_BASE).unwrap();
+    binder[0] ^= 1;
+    let err = hpke_accept_with_binder(suites::HL1_BASE, &bob.x25519.sk, &enc, &binder).unwrap_err();
+    assert_eq!(err, HpkeError::BinderMismatch);
+    assert_eq!(Error::from(err).code(), ErrorCode::BinderMismatch);
+
+    assert_eq!(RatchetError::Decrypt.code(), ErrorCode::BadAead);
+    assert_eq!(RatchetError::TooManySkipped.code(), ErrorCode::Desync);
+    assert_eq!(ErrorCode::from_ffi(-7), None);
+}
+
+#[test]
+fn ffi_returns_protocol_codes() {
+    use hardlock_snc::ffi::{hardlock_hpke_accept, hardlock_hpke_initiate};
+    let mut enc = [0u8; 32];
+    let mut okm = [0u8; 32];
+    let rc =
+        unsafe { hardlock_hpke_initiate(std::ptr::null(), enc.as_mut_ptr(), 32, okm.as_mut_ptr()) };
+    assert_eq!(ErrorCode::from_ffi(rc), Some(ErrorCode::InvalidArgument));
+
+    let bob = DeviceIdentity::generate("bob".into(), "d".into());
+    let pk = bob.x25519.public();
+    let rc = unsafe { hardlock_hpke_initiate(pk.as_ptr(), enc.as_mut_ptr(), 8, okm.as_mut_ptr()) };
+    assert_eq!(ErrorCode::from_ffi(rc), Some(ErrorCode::BufferTooSmall));
+
+    let sk = bob.x25519.sk.expose();
+    let rc = unsafe { hardlock_hpke_accept(sk.as_ptr(), enc.as_ptr(), 3, okm.as_mut_ptr()) };
+    assert_eq!(ErrorCode::from_ffi(rc), Some(ErrorCode::BadKey));
+}
+
+#[test]
+#[allow(deprecated)]
+fn legacy_apply_padding_symbol_is_kept() {
+    use hardlock_snc::ffi::hardlock_apply_padding;
+    use hardlock_snc::ratchet::state::Header;
+    let h = Header {
+        dh_pub: [1u8; 32],
+        pn: 0,
+        n: 0,
+    };
+    let frame = pack_message(HL_VERSION, &h, &[2u8; 24], b"ct").unwrap();
+    let mut out = [0xAAu8; 4096];
+    let rc = unsafe {
+        hardlock_apply_padding(frame.as_ptr(), frame.len(), 1, out.as_mut_ptr(), out.len())
+    };
+    let n = usize::try_from(rc).unwrap();
+    assert!(n >= frame.len());
+    assert_eq!(&out[..frame.len()], &frame[..]);
+    assert!(out[frame.len()..n].iter().all(|&b| b == 0));
+    assert_eq!(unpack_message(&out[..n]).unwrap().3, b"ct");
+
+    let rc = unsafe { hardlock_apply_padding(frame.as_ptr(), frame.len(), 1, out.as_mut_ptr(), 8) };
+    assert_eq!(ErrorCode::from_ffi(rc), Some(ErrorCode::BufferTooSmall));
+}
@@ -0,0 +1,54 @@
+use hardlock_snc::crypto::hpke_hybrid::{
+    hpke_accept_auth_with_binder, hpke_initiate_auth_with_binder,
+};
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::suites;
+use hardlock_snc::wire::handshake::{decode_init_auth_v2, encode_init_auth_v2};
+use hardlock_snc::wire::WireError;
+
+#[test]
+fn init_auth_v2_roundtrip() {
+    let a = DeviceIdentity::generate("a".into(), "d1".into());
+    let b = DeviceIdentity::generate("b".into(), "d2".into());
+    let (enc, okm_a, tag, binder) =
+        hpke_initiate_auth_with_binder(&a.x25519.sk, &b.x25519.public(), suites::HL1_AUTH).unwrap();
+    let frame = encode_init_auth_v2(suites::HL1_AUTH, &enc, &tag, &binder);
+    let (suite, enc2, tag2, binder2) = decode_init_auth_v2(&frame).unwrap();
+    let okm_b = hpke_accept_auth_with_binder(
+        suite,
+        &a.x25519.public(),
+        &b.x25519.sk,
+        &enc2,
+        &tag2,
+        &binder2,
+    )
+    .unwrap();
+    assert_eq!(okm_a, okm_b);
+
+    let mut long = frame.clone();
+    long.push(0);
+    assert_eq!(
+        decode_init_auth_v2(&long),
+        Err(WireError::Malformed("trailing bytes"))
+    );
+}
+
+#[test]
+fn init_auth_v2_suite_downgrade_rejected() {
+    let a = DeviceIdentity::generate("a".into(), "d1".into());
+    let b = DeviceIdentity::generate("b".into(), "d2".into());
+    let (enc, _okm, tag, binder) =
+        hpke_initiate_auth_with_binder(&a.x25519.sk, &b.x25519.public(), suites::HL1_AUTH).unwrap();
+    let mut frame = encode_init_auth_v2(suites::HL1_AUTH, &enc, &tag, &binder);
+    frame[1] = suites::HL1_BASE;
+    let (suite, enc2, tag2, binder2) = decode_init_auth_v2(&frame).unwrap();
+    let bad = hpke_accept_auth_with_binder(
+        suite,
+        &a.x25519.public(),
+        &b.x25519.sk,
+        &enc2,
+        &tag2,
+        &binder2,
+    );
+    assert!(bad.is_err());
+}
@@ -0,0 +1,118 @@
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::ratchet;
+use hardlock_snc::ratchet::RatchetError;
+use hardlock_snc::session::{Handshake, HandshakeState, InitKind};
+use hardlock_snc::suites;
+use hardlock_snc::wire::handshake::{decode_response, encode_response, TYPE_RESPONSE};
+use std::time::{Duration, Instant};
+
+#[test]
+fn handshake_confirms_then_hands_out_sessions() {
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let (mut hs_a, init) =
+        Handshake::initiate("bob".into(), &bob.x25519.public(), Duration::from_secs(5)).unwrap();
+    assert_eq!(hs_a.state(), HandshakeState::AwaitingResponse);
+
+    let (hs_b, resp) = Handshake::respond(&bob, "alice".into(), &init).unwrap();
+    assert_eq!(resp[0], TYPE_RESPONSE);
+    assert_eq!(hs_b.state(), HandshakeState::Established);
+
+    hs_a.on_response(&resp, Instant::now()).unwrap();
+    assert_eq!(hs_a.state(), HandshakeState::Established);
+
+    let mut sa = hs_a.into_session().unwrap();
+    let mut sb = hs_b.into_session().unwrap();
+    assert_eq!(sa.peer_id, "bob");
+    let (h, n, ct) = ratchet::encrypt(&mut sa.state, b"hs", b"hello").unwrap();
+    assert_eq!(
+        ratchet::decrypt(&mut sb.state, b"hs", &h, &n, &ct).unwrap(),
+        b"hello"
+    );
+}
+
+#[test]
+fn no_session_before_confirmation() {
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let (hs_a, _init) =
+        Handshake::initiate("bob".into(), &bob.x25519.public(), Duration::from_secs(5)).unwrap();
+    assert!(hs_a.into_session().is_err());
+}
+
+#[test]
+fn bad_confirmation_mac_fails_handshake() {
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let (mut hs_a, init) =
+        Handshake::initiate("bob".into(), &bob.x25519.public(), Duration::from_secs(5)).unwrap();
+    let (_hs_b, resp) = Handshake::respond(&bob, "alice".into(), &init).unwrap();
+    let (_, mut mac) = decode_response(&resp).unwrap();
+    mac[0] ^= 1;
+    let forged = encode_response(suites::HL1_BASE, &mac);
+    assert!(hs_a.on_response(&forged, Instant::now()).is_err());
+    assert_eq!(hs_a.state(), HandshakeState::Failed);
+    assert!(hs_a.on_response(&resp, Instant::now()).is_err());
+    assert!(hs_a.into_session().is_err());
+}
+
+#[test]
+fn response_after_timeout_is_rejected() {
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let (mut hs_a, init) = Handshake::initiate(
+        "bob".into(),
+        &bob.x25519.public(),
+        Duration::from_millis(10),
+    )
+    .unwrap();
+    let (_hs_b, resp) = Handshake::respond(&bob, "alice".into(), &init).unwrap();
+    let late = Instant::now() + Duration::from_secs(1);
+    assert_eq!(hs_a.poll_timeout(late), HandshakeState::Failed);
+    assert!(hs_a.on_response(&resp, late).is_err());
+}
+
+#[test]
+fn header_encryption_is_negotiated_in_init() {
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let (mut hs_a, init) = Handshake::initiate_with(
+        InitKind::Plain,
+        "bob".into(),
+        &bob.x25519.public(),
+        Duration::from_secs(5),
+        true,
+    )
+    .unwrap();
+    assert!(suites::header_encrypted(init[1]));
+    let (hs_b, resp) = Handshake::respond(&bob, "alice".into(), &init).unwrap();
+    assert!(hs_b.header_encryption());
+    hs_a.on_response(&resp, Instant::now()).unwrap();
+
+    let mut sa = hs_a.into_session().unwrap();
+    let mut sb = hs_b.into_session().unwrap();
+    assert!(matches!(
+        ratchet::encrypt(&mut sa.state, b"hs", b"clear"),
+        Err(RatchetError::HeaderModeMismatch)
+    ));
+    let (eh, n, ct) = ratchet::encrypt_he(&mut sa.state, b"hs", b"hello").unwrap();
+    assert_eq!(
+        ratchet::decrypt_he(&mut sb.state, b"hs", &eh, &n, &ct).unwrap(),
+        b"hello"
+    );
+    let (eh, n, ct) = ratchet::encrypt_he(&mut sb.state, b"hs", b"back").unwrap();
+    assert_eq!(
+        ratchet::decrypt_he(&mut sa.state, b"hs", &eh, &n, &ct).unwrap(),
+        b"back"
+    );
+}
+
+#[test]
+fn stripped_header_encryption_flag_is_rejected() {
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let (_hs_a, mut init) = Handshake::initiate_with(
+        InitKind::Plain,
+        "bob".into(),
+        &bob.x25519.public(),
+        Duration::from_secs(5),
+        true,
+    )
+    .unwrap();
+    init[1] = suites::base(init[1]);
+    assert!(Handshake::respond(&bob, "alice".into(), &init).is_err());
+}
@@ -0,0 +1,55 @@
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::session::Handshake;
+use hardlock_snc::wire::handshake::{decode_init_hidden, encode_init_hidden};
+use std::time::{Duration, Instant};
+
+fn contains(hay: &[u8], needle: &[u8]) -> bool {
+    hay.windows(needle.len()).any(|w| w == needle)
+}
+
+#[test]
+fn hidden_init_reveals_sender_only_to_responder() {
+    let alice = DeviceIdentity::generate("alice@example".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let (mut hs_a, init) = Handshake::initiate_hidden(
+        &alice,
+        "bob".into(),
+        &bob.x25519.public(),
+        Duration::from_secs(5),
+    )
+    .unwrap();
+    assert!(!contains(&init, alice.user_id.as_bytes()));
+    assert!(!contains(&init, &alice.verifying_key().to_bytes()));
+    assert!(!contains(&init, &alice.x25519.public()));
+
+    let (hs_b, resp, peer) = Handshake::respond_hidden(&bob, &init).unwrap();
+    assert_eq!(peer.user_id, "alice@example");
+    assert_eq!(peer.device_id, "phone");
+    assert_eq!(peer.identity_ed25519, alice.verifying_key().to_bytes());
+    hs_a.on_response(&resp, Instant::now()).unwrap();
+    assert_eq!(hs_b.into_session().unwrap().peer_id, "alice@example");
+}
+
+#[test]
+fn hidden_init_rejects_wrong_recipient_and_tampering() {
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let eve = DeviceIdentity::generate("eve".into(), "pc".into());
+    let (_hs, init) = Handshake::initiate_hidden(
+        &alice,
+        "bob".into(),
+        &bob.x25519.public(),
+        Duration::from_secs(5),
+    )
+    .unwrap();
+    assert!(Handshake::respond_hidden(&eve, &init).is_err());
+
+    let mut sealed = decode_init_hidden(&init).unwrap();
+    let last = sealed.sealed.len() - 1;
+    sealed.sealed[last] ^= 1;
+    assert!(Handshake::respond_hidden(&bob, &encode_init_hidden(&sealed)).is_err());
+
+    let mut header = decode_init_hidden(&init).unwrap();
+    header.ratchet_pub[0] ^= 1;
+    assert!(Handshake::respond_hidden(&bob, &encode_init_hidden(&header)).is_err());
+}
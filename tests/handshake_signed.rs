@@ -0,0 +1,52 @@
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::session::{verify_signed_init, Handshake};
+use hardlock_snc::wire::handshake::{decode_init_signed, encode_init_signed};
+use std::time::{Duration, Instant};
+
+#[test]
+fn signed_init_authenticates_initiator() {
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let (mut hs_a, init) = Handshake::initiate_signed(
+        &alice,
+        "bob".into(),
+        &bob.x25519.public(),
+        Duration::from_secs(5),
+    )
+    .unwrap();
+    let (hs_b, resp, peer) = Handshake::respond_signed(&bob, &init).unwrap();
+    assert_eq!(peer.user_id, "alice");
+    assert_eq!(peer.device_id, "phone");
+    assert_eq!(peer.identity_ed25519, alice.verifying_key().to_bytes());
+    assert_eq!(peer.identity_x25519, alice.x25519.public());
+
+    hs_a.on_response(&resp, Instant::now()).unwrap();
+    assert_eq!(hs_b.into_session().unwrap().peer_id, "alice");
+    assert_eq!(hs_a.into_session().unwrap().peer_id, "bob");
+}
+
+#[test]
+fn signed_init_rejects_tampering() {
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let mallory = DeviceIdentity::generate("mallory".into(), "pc".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let (_hs, init) = Handshake::initiate_signed(
+        &alice,
+        "bob".into(),
+        &bob.x25519.public(),
+        Duration::from_secs(5),
+    )
+    .unwrap();
+
+    let mut renamed = decode_init_signed(&init).unwrap();
+    renamed.user_id = "mallory".into();
+    assert!(verify_signed_init(&encode_init_signed(&renamed)).is_err());
+
+    let mut rekeyed = decode_init_signed(&init).unwrap();
+    rekeyed.identity_ed25519 = mallory.verifying_key().to_bytes();
+    assert!(Handshake::respond_signed(&bob, &encode_init_signed(&rekeyed)).is_err());
+
+    let mut swapped = decode_init_signed(&init).unwrap();
+    swapped.ratchet_pub[0] ^= 1;
+    assert!(verify_signed_init(&encode_init_signed(&swapped)).is_err());
+}
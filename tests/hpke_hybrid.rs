@@ -0,0 +1,52 @@
+use hardlock_snc::crypto::hpke_hybrid::{hpke_accept_hybrid, hpke_initiate_hybrid, HpkeError};
+use hardlock_snc::crypto::keys::MlKem768KeyPair;
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::wire::handshake::{decode_init_v2_hybrid, encode_init_v2_hybrid};
+
+#[test]
+fn hybrid_wire_roundtrip() {
+    let b = DeviceIdentity::generate("b".into(), "d2".into());
+    let kem = MlKem768KeyPair::generate();
+    let (enc, kem_ct, okm_a, binder) =
+        hpke_initiate_hybrid(&b.x25519.public(), &kem.public()).unwrap();
+    let frame = encode_init_v2_hybrid(&enc, &kem_ct, &binder);
+    let (enc2, kem_ct2, binder2) = decode_init_v2_hybrid(&frame).unwrap();
+    let okm_b = hpke_accept_hybrid(&b.x25519.sk, &kem.dk, &enc2, &kem_ct2, &binder2).unwrap();
+    assert_eq!(okm_a, okm_b);
+}
+
+#[test]
+fn hybrid_tampered_kem_ct_rejected() {
+    let b = DeviceIdentity::generate("b".into(), "d2".into());
+    let kem = MlKem768KeyPair::generate();
+    let (enc, mut kem_ct, _okm, binder) =
+        hpke_initiate_hybrid(&b.x25519.public(), &kem.public()).unwrap();
+    kem_ct[0] ^= 1;
+    let bad = hpke_accept_hybrid(&b.x25519.sk, &kem.dk, &enc, &kem_ct, &binder);
+    assert!(bad.is_err());
+}
+
+#[test]
+fn hybrid_rejects_non_canonical_ek() {
+    let b = DeviceIdentity::generate("b".into(), "d2".into());
+    let kem = MlKem768KeyPair::generate();
+    let mut ek = kem.public();
+    // Premier coefficient = 0xfff ≥ q : refusé (FIPS 203 §7.2).
+    ek[0] = 0xff;
+    ek[1] |= 0x0f;
+    assert_eq!(
+        hpke_initiate_hybrid(&b.x25519.public(), &ek).unwrap_err(),
+        HpkeError::BadKey
+    );
+    assert_eq!(
+        hpke_initiate_hybrid(&b.x25519.public(), &ek[..1183]).unwrap_err(),
+        HpkeError::BadKey
+    );
+    // `rho` n'est pas soumis au contrôle.
+    let mut ek = kem.public();
+    ek[1183] ^= 0xff;
+    assert!(hpke_initiate_hybrid(&b.x25519.public(), &ek).is_ok());
+    // Le secret de décapsulation n'apparaît pas en Debug.
+    let dbg = format!("{kem:?}");
+    assert!(!dbg.contains(&hex::encode(&kem.dk.expose()[..16])));
+}
@@ -0,0 +1,118 @@
+use hardlock_snc::crypto::hpke_hybrid::{
+    hpke_accept_auth_psk, hpke_accept_psk, hpke_initiate_auth_psk, hpke_initiate_psk,
+    hpke_initiate_psk_deterministic,
+};
+use hardlock_snc::crypto::keys::X25519KeyPair;
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::suites;
+use hardlock_snc::wire::handshake::{
+    decode_init_auth_psk, decode_init_psk, encode_init_auth_psk, encode_init_psk,
+};
+use hardlock_snc::wire::WireError;
+
+fn unhex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn init_psk_roundtrip_and_wrong_psk() {
+    let b = DeviceIdentity::generate("b".into(), "d2".into());
+    let sk_b = &b.x25519.sk;
+    let psk = [7u8; 32];
+    let (enc, okm_a, binder) = hpke_initiate_psk(&b.x25519.public(), &psk, b"id-1").unwrap();
+    let frame = encode_init_psk(suites::HL1_PSK, b"id-1", &enc, &binder);
+    let (suite, psk_id, enc2, binder2) = decode_init_psk(&frame).unwrap();
+    assert_eq!(suite, suites::HL1_PSK);
+    let okm_b = hpke_accept_psk(sk_b, &psk, &psk_id, &enc2, &binder2).unwrap();
+    assert_eq!(okm_a, okm_b);
+
+    assert!(hpke_accept_psk(sk_b, &[8u8; 32], &psk_id, &enc2, &binder2).is_err());
+    assert!(hpke_accept_psk(sk_b, &psk, b"id-2", &enc2, &binder2).is_err());
+
+    let mut long = frame.clone();
+    long.push(0);
+    assert_eq!(
+        decode_init_psk(&long),
+        Err(WireError::Malformed("trailing bytes"))
+    );
+}
+
+#[test]
+fn init_auth_psk_roundtrip_and_wrong_sender() {
+    let a = DeviceIdentity::generate("a".into(), "d1".into());
+    let b = DeviceIdentity::generate("b".into(), "d2".into());
+    let c = DeviceIdentity::generate("c".into(), "d3".into());
+    let sk_b = &b.x25519.sk;
+    let psk = [9u8; 32];
+    let (enc, okm_a, tag, binder) =
+        hpke_initiate_auth_psk(&a.x25519.sk, &b.x25519.public(), &psk, b"id-1").unwrap();
+    let frame = encode_init_auth_psk(suites::HL1_AUTH_PSK, b"id-1", &enc, &tag, &binder);
+    let (suite, psk_id, enc2, tag2, binder2) = decode_init_auth_psk(&frame).unwrap();
+    assert_eq!(suite, suites::HL1_AUTH_PSK);
+    let mut long = frame.clone();
+    long.extend_from_slice(&[0; 32]);
+    assert_eq!(
+        decode_init_auth_psk(&long),
+        Err(WireError::Malformed("trailing bytes"))
+    );
+    let okm_b = hpke_accept_auth_psk(
+        &a.x25519.public(),
+        sk_b,
+        &psk,
+        &psk_id,
+        &enc2,
+        &tag2,
+        &binder2,
+    )
+    .unwrap();
+    assert_eq!(okm_a, okm_b);
+
+    let bad = hpke_accept_auth_psk(
+        &c.x25519.public(),
+        sk_b,
+        &psk,
+        &psk_id,
+        &enc2,
+        &tag2,
+        &binder2,
+    );
+    assert!(bad.is_err());
+}
+
+#[test]
+fn hpke_psk_kat() {
+    let raw = std::fs::read("KATS/hpke_psk.json").unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&raw).unwrap();
+    let field = |k: &str| unhex(v[k].as_str().unwrap());
+    let seed: [u8; 32] = field("seed").try_into().unwrap();
+    let rcp = X25519KeyPair::from_seed(field("recipient_seed").try_into().unwrap());
+    let (enc, okm, binder) =
+        hpke_initiate_psk_deterministic(&rcp.public(), &field("psk"), &field("psk_id"), seed)
+            .unwrap();
+    assert_eq!(enc, field("enc"));
+    assert_eq!(okm.to_vec(), field("okm"));
+    assert_eq!(binder.to_vec(), field("binder"));
+}
+
+#[test]
+fn hpke_auth_psk_kat() {
+    let raw = std::fs::read("KATS/hpke_auth_psk.json").unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&raw).unwrap();
+    let field = |k: &str| unhex(v[k].as_str().unwrap());
+    let rcp = X25519KeyPair::from_seed(field("recipient_seed").try_into().unwrap());
+    let snd = X25519KeyPair::from_seed(field("sender_seed").try_into().unwrap());
+    let okm = hpke_accept_auth_psk(
+        &snd.public(),
+        &rcp.sk,
+        &field("psk"),
+        &field("psk_id"),
+        &field("enc"),
+        &field("tag").try_into().unwrap(),
+        &field("binder").try_into().unwrap(),
+    )
+    .unwrap();
+    assert_eq!(okm.to_vec(), field("okm"));
+}
@@ -0,0 +1,114 @@
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::prekeys::{
+    x3dh_accept, x3dh_accept_first, x3dh_ad, x3dh_initiate, PreKeyInit, PreKeyPool,
+};
+use hardlock_snc::ratchet;
+
+#[test]
+fn prekey_session_roundtrip() {
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let mut pool = PreKeyPool::generate(&bob, 1, 4);
+    let bundle = pool.bundle(&bob);
+    assert!(bundle.verify());
+    let bytes = bincode::serialize(&bundle).unwrap();
+    let bundle = bincode::deserialize(&bytes).unwrap();
+
+    let (init, mut sa) = x3dh_initiate(&alice, &bundle).unwrap();
+    assert_eq!(sa.ad, x3dh_ad(&alice.x25519.public(), &bob.x25519.public()));
+    let (h, n, ct) = ratchet::encrypt(&mut sa.state, &sa.ad, b"hi bob").unwrap();
+    let (mut sb, pt) = x3dh_accept_first(&bob, &mut pool, &init, &h, &n, &ct).unwrap();
+    assert_eq!(pt, b"hi bob");
+    assert_eq!(sb.ad, sa.ad);
+    assert_eq!(pool.one_time.len(), 3);
+
+    let (h, n, ct) = ratchet::encrypt(&mut sb.state, &sb.ad, b"hi alice").unwrap();
+    assert_eq!(
+        ratchet::decrypt(&mut sa.state, &sa.ad, &h, &n, &ct).unwrap(),
+        b"hi alice"
+    );
+
+    assert!(x3dh_accept(&bob, &pool, &init).is_err());
+}
+
+#[test]
+fn prekey_bundle_without_one_time_key() {
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let mut pool = PreKeyPool::generate(&bob, 7, 0);
+    let bundle = pool.bundle(&bob);
+    assert!(bundle.one_time.is_none());
+    let (init, mut sa) = x3dh_initiate(&alice, &bundle).unwrap();
+    let (h, n, ct) = ratchet::encrypt(&mut sa.state, &sa.ad, b"m").unwrap();
+    let (_sb, pt) = x3dh_accept_first(&bob, &mut pool, &init, &h, &n, &ct).unwrap();
+    assert_eq!(pt, b"m");
+}
+
+#[test]
+fn prekey_bad_signature_rejected() {
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let mallory = DeviceIdentity::generate("mallory".into(), "x".into());
+    let pool = PreKeyPool::generate(&bob, 1, 1);
+    let mut bundle = pool.bundle(&bob);
+    bundle.spk_pub = PreKeyPool::generate(&mallory, 1, 0).spk.kp.public();
+    assert!(!bundle.verify());
+    assert!(x3dh_initiate(&alice, &bundle).is_err());
+
+    // Identité de faible ordre : (R = identité, s = 0) vérifie pour tout
+    // message en mode non strict.
+    let mut neutral = [0u8; 32];
+    neutral[0] = 1;
+    bundle.identity_ed25519 = neutral;
+    bundle.spk_sig = [neutral, [0u8; 32]].concat();
+    assert!(!bundle.verify());
+}
+
+#[test]
+fn forged_init_does_not_drain_one_time_keys() {
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let mallory = DeviceIdentity::generate("mallory".into(), "x".into());
+    let mut pool = PreKeyPool::generate(&bob, 1, 2);
+    let bundle = pool.bundle(&bob);
+
+    let (init, mut sa) = x3dh_initiate(&alice, &bundle).unwrap();
+    let (h, n, ct) = ratchet::encrypt(&mut sa.state, &sa.ad, b"hi").unwrap();
+    let forged = PreKeyInit {
+        identity_x25519: mallory.x25519.public(),
+        ..init.clone()
+    };
+    assert!(x3dh_accept_first(&bob, &mut pool, &forged, &h, &n, &ct).is_err());
+    assert_eq!(pool.one_time.len(), 2);
+
+    assert!(x3dh_accept_first(&bob, &mut pool, &init, &h, &n, &ct).is_ok());
+    assert_eq!(pool.one_time.len(), 1);
+}
+
+#[test]
+fn previous_signed_prekey_accepted_until_next_rotation() {
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let mut pool = PreKeyPool::generate(&bob, 1, 2);
+    let bundle = pool.bundle(&bob);
+    let (init, mut sa) = x3dh_initiate(&alice, &bundle).unwrap();
+    let (h, n, ct) = ratchet::encrypt(&mut sa.state, &sa.ad, b"in flight").unwrap();
+
+    pool.rotate_signed(&bob, 2);
+    let (_sb, pt) = x3dh_accept_first(&bob, &mut pool, &init, &h, &n, &ct).unwrap();
+    assert_eq!(pt, b"in flight");
+
+    let mut stale = bundle.clone();
+    stale.one_time = None;
+    let (init, _sa) = x3dh_initiate(&alice, &stale).unwrap();
+    assert!(x3dh_accept(&bob, &pool, &init).is_ok());
+    pool.rotate_signed(&bob, 3);
+    assert!(x3dh_accept(&bob, &pool, &init).is_err());
+
+    let mut pool = PreKeyPool::generate(&bob, 1, 0);
+    let bundle = pool.bundle(&bob);
+    let (init, _sa) = x3dh_initiate(&alice, &bundle).unwrap();
+    pool.rotate_signed(&bob, 2);
+    pool.retire_previous();
+    assert!(x3dh_accept(&bob, &pool, &init).is_err());
+}
@@ -14,11 +14,11 @@ proptest! {
     let a = DeviceIdentity::generate("a".into(), "d1".into());
     let b = DeviceIdentity::generate("b".into(), "d2".into());
     let (enc, s_a) = hpke_initiate(&b.x25519.public()).unwrap();
-    let s_b = hardlock_snc::crypto::hpke_hybrid::hpke_accept(&b.x25519.sk.clone().try_into().unwrap(), &enc).unwrap();
-    let mut ra = ratchet::init_initiator(s_a, a.x25519.sk.clone().try_into().unwrap(), b.x25519.public());
-    let mut rb = ratchet::init_responder(s_b, b.x25519.sk.clone().try_into().unwrap(), a.x25519.public());
+    let s_b = hardlock_snc::crypto::hpke_hybrid::hpke_accept(&b.x25519.sk, &enc).unwrap();
+    let mut ra = ratchet::init_initiator(s_a, *a.x25519.sk.expose(), b.x25519.public());
+    let mut rb = ratchet::init_responder(s_b, *b.x25519.sk.expose(), a.x25519.public());
     let ad = b"ad";
-    let (h, n, ct) = ratchet::encrypt(&mut ra, ad, &msg);
+    let (h, n, ct) = ratchet::encrypt(&mut ra, ad, &msg).unwrap();
     let pt = ratchet::decrypt(&mut rb, ad, &h, &n, &ct).unwrap();
     prop_assert_eq!(pt, msg);
   }
@@ -31,13 +31,13 @@ proptest! {
     let a = DeviceIdentity::generate("a".into(), "d1".into());
     let b = DeviceIdentity::generate("b".into(), "d2".into());
     let (enc, s_a) = hpke_initiate(&b.x25519.public()).unwrap();
-    let s_b = hardlock_snc::crypto::hpke_hybrid::hpke_accept(&b.x25519.sk.clone().try_into().unwrap(), &enc).unwrap();
-    let mut ra = ratchet::init_initiator(s_a, a.x25519.sk.clone().try_into().unwrap(), b.x25519.public());
-    let mut rb = ratchet::init_responder(s_b, b.x25519.sk.clone().try_into().unwrap(), a.x25519.public());
+    let s_b = hardlock_snc::crypto::hpke_hybrid::hpke_accept(&b.x25519.sk, &enc).unwrap();
+    let mut ra = ratchet::init_initiator(s_a, *a.x25519.sk.expose(), b.x25519.public());
+    let mut rb = ratchet::init_responder(s_b, *b.x25519.sk.expose(), a.x25519.public());
     let ad = b"ad";
     let mut bufs = Vec::new();
     for m in &msgs {
-      bufs.push(ratchet::encrypt(&mut ra, ad, m));
+      bufs.push(ratchet::encrypt(&mut ra, ad, m).unwrap());
     }
     // On livre dans l'ordre inverse
     for (i, (h,n,ct)) in bufs.into_iter().rev().enumerate() {
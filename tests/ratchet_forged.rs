@@ -0,0 +1,27 @@
+mod common;
+
+use hardlock_snc::ratchet;
+use hardlock_snc::ratchet::state::Header;
+
+#[test]
+fn forged_frame_leaves_state_untouched() {
+    let (mut ra, mut rb) = common::pair();
+    let ad = b"ad";
+    let (h1, n1, c1) = ratchet::encrypt(&mut ra, ad, b"m1").unwrap();
+    let (h2, n2, c2) = ratchet::encrypt(&mut ra, ad, b"m2").unwrap();
+
+    let forged = Header {
+        dh_pub: [9u8; 32],
+        pn: 5,
+        n: 3,
+    };
+    assert!(ratchet::decrypt(&mut rb, ad, &forged, &n1, &c1).is_err());
+    let mut bad = c2.clone();
+    bad[0] ^= 1;
+    assert!(ratchet::decrypt(&mut rb, ad, &h2, &n2, &bad).is_err());
+    assert_eq!(rb.nr, 0);
+    assert!(rb.skipped.is_empty());
+
+    assert_eq!(ratchet::decrypt(&mut rb, ad, &h2, &n2, &c2).unwrap(), b"m2");
+    assert_eq!(ratchet::decrypt(&mut rb, ad, &h1, &n1, &c1).unwrap(), b"m1");
+}
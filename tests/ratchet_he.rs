@@ -0,0 +1,68 @@
+mod common;
+
+use common::pair_he;
+use hardlock_snc::ratchet;
+use hardlock_snc::wire::{pack_message_he, unpack_message_he, ENC_HEADER_LEN};
+use hardlock_snc::HL_VERSION;
+
+#[test]
+fn he_frame_roundtrip() {
+    let (mut ra, mut rb) = pair_he();
+    let ad = b"ad";
+    let (eh, n, ct) = ratchet::encrypt_he(&mut ra, ad, b"hello").unwrap();
+    let eh: [u8; ENC_HEADER_LEN] = eh.try_into().unwrap();
+    let frame = pack_message_he(HL_VERSION, &eh, &n, &ct).unwrap();
+    let (ver, eh2, n2, ct2) = unpack_message_he(&frame).unwrap();
+    assert_eq!(ver, HL_VERSION);
+    let pt = ratchet::decrypt_he(&mut rb, ad, &eh2, &n2, &ct2).unwrap();
+    assert_eq!(&pt, b"hello");
+    assert!(ratchet::decrypt_he(&mut rb, ad, &eh2, &n2, &ct2).is_err());
+}
+
+#[test]
+fn he_ping_pong_with_late_message() {
+    let (mut ra, mut rb) = pair_he();
+    let ad = b"ad";
+    let late = ratchet::encrypt_he(&mut ra, ad, b"late").unwrap();
+    for i in 0..4u8 {
+        let (eh, n, ct) = ratchet::encrypt_he(&mut ra, ad, &[i]).unwrap();
+        assert_eq!(ratchet::decrypt_he(&mut rb, ad, &eh, &n, &ct).unwrap(), [i]);
+        let (eh, n, ct) = ratchet::encrypt_he(&mut rb, ad, &[i, i]).unwrap();
+        assert_eq!(
+            ratchet::decrypt_he(&mut ra, ad, &eh, &n, &ct).unwrap(),
+            [i, i]
+        );
+    }
+    let (eh, n, ct) = late;
+    assert_eq!(
+        ratchet::decrypt_he(&mut rb, ad, &eh, &n, &ct).unwrap(),
+        b"late"
+    );
+}
+
+#[test]
+fn he_tampered_header_rejected() {
+    let (mut ra, mut rb) = pair_he();
+    let ad = b"ad";
+    let (mut eh, n, ct) = ratchet::encrypt_he(&mut ra, ad, b"m").unwrap();
+    eh[30] ^= 1;
+    assert!(ratchet::decrypt_he(&mut rb, ad, &eh, &n, &ct).is_err());
+    assert_eq!(rb.nr, 0);
+}
+
+#[test]
+fn header_mode_is_enforced() {
+    let (mut ra, mut rb) = pair_he();
+    assert!(matches!(
+        ratchet::encrypt(&mut ra, b"ad", b"x"),
+        Err(ratchet::RatchetError::HeaderModeMismatch)
+    ));
+    let (eh, n, ct) = ratchet::encrypt_he(&mut ra, b"ad", b"x").unwrap();
+    assert!(ratchet::decrypt_he(&mut rb, b"ad", &eh, &n, &ct).is_ok());
+
+    let (mut plain, _) = common::pair();
+    assert!(matches!(
+        ratchet::encrypt_he(&mut plain, b"ad", b"x"),
+        Err(ratchet::RatchetError::HeaderModeMismatch)
+    ));
+}
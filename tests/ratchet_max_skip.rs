@@ -0,0 +1,57 @@
+mod common;
+
+use common::pair;
+use hardlock_snc::ratchet::state::{Header, RatchetState};
+use hardlock_snc::ratchet::{self, RatchetError};
+
+#[test]
+fn absurd_counters_rejected_without_derivation() {
+    let (mut ra, mut rb) = pair();
+    let ad = b"ad";
+    let (h, n, ct) = ratchet::encrypt(&mut ra, ad, b"m0").unwrap();
+    let same_chain = Header {
+        dh_pub: h.dh_pub,
+        pn: 0,
+        n: u32::MAX,
+    };
+    let r = ratchet::decrypt(&mut rb, ad, &same_chain, &n, &ct);
+    assert!(matches!(r, Err(RatchetError::TooManySkipped)));
+    let new_chain = Header {
+        dh_pub: [3u8; 32],
+        pn: u32::MAX,
+        n: 0,
+    };
+    let r = ratchet::decrypt(&mut rb, ad, &new_chain, &n, &ct);
+    assert!(matches!(r, Err(RatchetError::TooManySkipped)));
+    assert_eq!(ratchet::decrypt(&mut rb, ad, &h, &n, &ct).unwrap(), b"m0");
+}
+
+#[test]
+fn configurable_max_skip() {
+    let (mut ra, mut rb) = pair();
+    rb.set_max_skip(2);
+    let ad = b"ad";
+    let msgs: Vec<_> = (0..4u8)
+        .map(|i| ratchet::encrypt(&mut ra, ad, &[i]).unwrap())
+        .collect();
+    let (h3, n3, c3) = &msgs[3];
+    let r = ratchet::decrypt(&mut rb, ad, h3, n3, c3);
+    assert!(matches!(r, Err(RatchetError::TooManySkipped)));
+    let (h2, n2, c2) = &msgs[2];
+    assert_eq!(ratchet::decrypt(&mut rb, ad, h2, n2, c2).unwrap(), [2]);
+    assert_eq!(ratchet::decrypt(&mut rb, ad, h3, n3, c3).unwrap(), [3]);
+}
+
+#[test]
+fn max_skip_defaults_when_missing_from_saved_state() {
+    let (mut ra, _) = pair();
+    ra.set_max_skip(7);
+    let mut v = serde_json::to_value(&ra).unwrap();
+    assert_eq!(v["max_skip"], 7);
+    v.as_object_mut().unwrap().remove("max_skip");
+    let back: RatchetState = serde_json::from_value(v).unwrap();
+    assert_eq!(
+        back.max_skip,
+        hardlock_snc::ratchet::state::DEFAULT_MAX_SKIP
+    );
+}
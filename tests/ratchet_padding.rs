@@ -0,0 +1,49 @@
+use hardlock_snc::envelope::PadProfile;
+use hardlock_snc::error::ErrorCode;
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::ratchet::{self, RatchetError};
+use hardlock_snc::session::{bootstrap_initiator, bootstrap_responder};
+use hardlock_snc::wire::{pack_message, unpack_message};
+use hardlock_snc::HL_VERSION;
+
+#[test]
+fn padded_messages_hide_length_and_roundtrip() {
+    let bob = DeviceIdentity::generate("bob".into(), "d".into());
+    let (init, mut ra) = bootstrap_initiator(&bob.x25519.public()).unwrap();
+    let mut rb = bootstrap_responder(&bob, &init).unwrap();
+    let ad = b"ad";
+
+    let (h1, n1, c1) = ratchet::encrypt_padded(&mut ra, ad, b"hi", PadProfile::Stealth).unwrap();
+    let (h2, n2, c2) =
+        ratchet::encrypt_padded(&mut ra, ad, &[1u8; 200], PadProfile::Stealth).unwrap();
+    assert_eq!(c1.len(), c2.len());
+
+    // La trame ne porte plus de bourrage externe : rien à retirer pour le relais.
+    let f1 = pack_message(HL_VERSION, &h1, &n1, &c1).unwrap();
+    assert_eq!(&f1[f1.len() - 4..], &[0u8; 4]);
+    let (_, h, n, c) = unpack_message(&f1).unwrap();
+    assert_eq!(
+        ratchet::decrypt_padded(&mut rb, ad, &h, &n, &c).unwrap(),
+        b"hi"
+    );
+
+    let mut cut = c2.clone();
+    cut.truncate(c2.len() - 32);
+    let err = ratchet::decrypt_padded(&mut rb, ad, &h2, &n2, &cut).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::BadAead);
+    assert_eq!(
+        ratchet::decrypt_padded(&mut rb, ad, &h2, &n2, &c2).unwrap(),
+        vec![1u8; 200]
+    );
+}
+
+#[test]
+fn unpadded_plaintext_is_rejected() {
+    let bob = DeviceIdentity::generate("bob".into(), "d".into());
+    let (init, mut ra) = bootstrap_initiator(&bob.x25519.public()).unwrap();
+    let mut rb = bootstrap_responder(&bob, &init).unwrap();
+    let (h, n, c) = ratchet::encrypt(&mut ra, b"ad", b"no marker").unwrap();
+    let err = ratchet::decrypt_padded(&mut rb, b"ad", &h, &n, &c).unwrap_err();
+    assert!(matches!(err, RatchetError::BadPadding));
+    assert_eq!(err.code().name(), "BAD_PADDING");
+}
@@ -0,0 +1,241 @@
+use hardlock_snc::crypto::hpke_hybrid::{hpke_seal_auth, hpke_seal_base};
+use hardlock_snc::crypto::keys::Ed25519Identity;
+use hardlock_snc::envelope::{
+    open_sealed_sender, seal_sealed_sender, EnvelopeError, SenderCertificate, SEALED_SENDER_VERSION,
+};
+use hardlock_snc::error::ErrorCode;
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::ratchet;
+use hardlock_snc::session::{bootstrap_initiator, bootstrap_responder};
+use hardlock_snc::wire::{pack_message, unpack_message, WireError};
+use hardlock_snc::HL_VERSION;
+
+const NOW: u64 = 1_700_000_000;
+
+fn contains(hay: &[u8], needle: &[u8]) -> bool {
+    hay.windows(needle.len()).any(|w| w == needle)
+}
+
+#[test]
+fn only_the_recipient_learns_the_sender() {
+    let server = Ed25519Identity::generate();
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let (init, mut ra) = bootstrap_initiator(&bob.x25519.public()).unwrap();
+    let mut rb = bootstrap_responder(&bob, &init).unwrap();
+
+    let cert = SenderCertificate::issue(
+        &server,
+        alice.ed25519.pk,
+        alice.x25519.public(),
+        &alice.device_id,
+        NOW + 86_400,
+    )
+    .unwrap();
+    let (h, n, ct) = ratchet::encrypt(&mut ra, b"ad", b"psst").unwrap();
+    let frame = pack_message(HL_VERSION, &h, &n, &ct).unwrap();
+    let sealed = seal_sealed_sender(&alice.x25519.sk, &bob.x25519.public(), &cert, &frame).unwrap();
+
+    // Le relais ne voit ni l'identité, ni l'appareil, ni la trame.
+    assert!(!contains(&sealed, &alice.ed25519.pk));
+    assert!(!contains(&sealed, &alice.x25519.public()));
+    assert!(!contains(&sealed, b"phone"));
+    assert!(!contains(&sealed, &ct));
+
+    let o = open_sealed_sender(&bob.x25519.sk, &server.pk, &sealed, NOW).unwrap();
+    assert_eq!(o.certificate, cert);
+    assert_eq!(o.certificate.identity_ed25519, alice.ed25519.pk);
+    assert_eq!(o.certificate.device_id, "phone");
+    let (_, hh, nn, cc) = unpack_message(&o.frame).unwrap();
+    assert_eq!(
+        ratchet::decrypt(&mut rb, b"ad", &hh, &nn, &cc).unwrap(),
+        b"psst"
+    );
+
+    // Un tiers (ou le relais) ne peut pas ouvrir.
+    let eve = DeviceIdentity::generate("eve".into(), "x".into());
+    assert_eq!(
+        open_sealed_sender(&eve.x25519.sk, &server.pk, &sealed, NOW)
+            .unwrap_err()
+            .code(),
+        ErrorCode::HpkeFailure
+    );
+    let mut bad = sealed.clone();
+    *bad.last_mut().unwrap() ^= 1;
+    assert!(matches!(
+        open_sealed_sender(&bob.x25519.sk, &server.pk, &bad, NOW),
+        Err(EnvelopeError::Hpke(_))
+    ));
+}
+
+#[test]
+fn certificates_are_checked() {
+    let server = Ed25519Identity::generate();
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let cert = SenderCertificate::issue(
+        &server,
+        alice.ed25519.pk,
+        alice.x25519.public(),
+        "phone",
+        NOW + 60,
+    )
+    .unwrap();
+    assert_eq!(
+        SenderCertificate::from_bytes(&cert.to_bytes().unwrap()).unwrap(),
+        cert
+    );
+    assert!(cert.verify(&server.pk, NOW + 60).is_ok());
+
+    let e = cert.verify(&server.pk, NOW + 61).unwrap_err();
+    assert_eq!(e, EnvelopeError::CertificateExpired);
+    assert_eq!(e.code(), ErrorCode::TokenExpired);
+
+    let rogue = Ed25519Identity::generate();
+    assert_eq!(
+        cert.verify(&rogue.pk, NOW).unwrap_err().code(),
+        ErrorCode::BadCertificate
+    );
+    let mut forged = cert.clone();
+    forged.identity_ed25519 = bob.ed25519.pk;
+    assert_eq!(
+        forged.verify(&server.pk, NOW).unwrap_err(),
+        EnvelopeError::BadCertificate
+    );
+    let mut renamed = cert.clone();
+    renamed.device_id = "tablet".into();
+    let sealed =
+        seal_sealed_sender(&alice.x25519.sk, &bob.x25519.public(), &renamed, b"frame").unwrap();
+    assert_eq!(
+        open_sealed_sender(&bob.x25519.sk, &server.pk, &sealed, NOW).unwrap_err(),
+        EnvelopeError::BadCertificate
+    );
+    let sealed =
+        seal_sealed_sender(&alice.x25519.sk, &bob.x25519.public(), &cert, b"frame").unwrap();
+    assert_eq!(
+        open_sealed_sender(&bob.x25519.sk, &server.pk, &sealed, NOW + 61).unwrap_err(),
+        EnvelopeError::CertificateExpired
+    );
+}
+
+#[test]
+fn malformed_inputs() {
+    let server = Ed25519Identity::generate();
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let cert =
+        SenderCertificate::issue(&server, [1u8; 32], alice.x25519.public(), "", NOW).unwrap();
+    let bytes = cert.to_bytes().unwrap();
+    assert_eq!(
+        SenderCertificate::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(WireError::ShortHeader)
+    );
+    let mut v2 = bytes.clone();
+    v2[0] = 2;
+    assert_eq!(
+        SenderCertificate::from_bytes(&v2),
+        Err(WireError::BadVersion(2))
+    );
+    let mut long = bytes.clone();
+    long.push(0);
+    assert!(matches!(
+        SenderCertificate::from_bytes(&long),
+        Err(WireError::Malformed(_))
+    ));
+
+    let sealed = seal_sealed_sender(&alice.x25519.sk, &bob.x25519.public(), &cert, b"").unwrap();
+    assert_eq!(
+        open_sealed_sender(&bob.x25519.sk, &server.pk, &sealed[..10], NOW).unwrap_err(),
+        EnvelopeError::Wire(WireError::ShortHeader)
+    );
+    let mut other = sealed.clone();
+    other[0] = 9;
+    assert_eq!(
+        open_sealed_sender(&bob.x25519.sk, &server.pk, &other, NOW).unwrap_err(),
+        EnvelopeError::Wire(WireError::BadVersion(9))
+    );
+    // Clé publique de faible ordre : l'encapsulation échoue.
+    assert!(matches!(
+        seal_sealed_sender(&alice.x25519.sk, &[0u8; 32], &cert, b""),
+        Err(EnvelopeError::Hpke(_))
+    ));
+
+    // `device_id` trop long : erreur à l'émission, à l'encodage et à la vérification.
+    let long_id = "d".repeat(256);
+    let e = SenderCertificate::issue(&server, [1u8; 32], alice.x25519.public(), &long_id, NOW)
+        .unwrap_err();
+    assert_eq!(e, EnvelopeError::DeviceIdTooLong);
+    assert_eq!(e.code(), ErrorCode::LengthOverflow);
+    let mut oversized = cert.clone();
+    oversized.device_id = long_id;
+    assert_eq!(oversized.to_bytes(), Err(EnvelopeError::DeviceIdTooLong));
+    assert_eq!(
+        oversized.verify(&server.pk, NOW),
+        Err(EnvelopeError::BadCertificate)
+    );
+    assert_eq!(
+        seal_sealed_sender(&alice.x25519.sk, &bob.x25519.public(), &oversized, b""),
+        Err(EnvelopeError::DeviceIdTooLong)
+    );
+
+    // Clé serveur de faible ordre : (R = identité, s = 0) n'est accepté qu'en
+    // vérification non stricte.
+    let mut neutral = [0u8; 32];
+    neutral[0] = 1;
+    let mut weak = cert.clone();
+    weak.signature = [neutral, [0u8; 32]].concat().try_into().unwrap();
+    assert_eq!(
+        weak.verify(&neutral, NOW),
+        Err(EnvelopeError::BadCertificate)
+    );
+}
+
+#[test]
+fn certificate_is_bound_to_the_authenticated_sender() {
+    let server = Ed25519Identity::generate();
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let mallory = DeviceIdentity::generate("mallory".into(), "x".into());
+    let cert = SenderCertificate::issue(
+        &server,
+        alice.ed25519.pk,
+        alice.x25519.public(),
+        "phone",
+        NOW + 60,
+    )
+    .unwrap();
+    assert_eq!(
+        seal_sealed_sender(&mallory.x25519.sk, &bob.x25519.public(), &cert, b"frame").unwrap_err(),
+        EnvelopeError::BadCertificate
+    );
+
+    // Mallory rejoue le certificat d'Alice sous sa propre clé.
+    let (enc_e, ct_e) = hpke_seal_base(
+        &bob.x25519.public(),
+        b"hardlock/sealed-sender/v2/eph",
+        &mallory.x25519.public(),
+        &[SEALED_SENDER_VERSION],
+    )
+    .unwrap();
+    let mut sealed = vec![SEALED_SENDER_VERSION];
+    sealed.extend_from_slice(&enc_e);
+    sealed.extend_from_slice(&ct_e);
+    let c = cert.to_bytes().unwrap();
+    let mut pt = u16::try_from(c.len()).unwrap().to_le_bytes().to_vec();
+    pt.extend_from_slice(&c);
+    pt.extend_from_slice(b"frame");
+    let (enc_s, ct_s) = hpke_seal_auth(
+        &mallory.x25519.sk,
+        &bob.x25519.public(),
+        b"hardlock/sealed-sender/v2",
+        &pt,
+        &sealed,
+    )
+    .unwrap();
+    sealed.extend_from_slice(&enc_s);
+    sealed.extend_from_slice(&ct_s);
+    assert_eq!(
+        open_sealed_sender(&bob.x25519.sk, &server.pk, &sealed, NOW).unwrap_err(),
+        EnvelopeError::BadCertificate
+    );
+}
@@ -0,0 +1,52 @@
+use hardlock_snc::crypto::keys::{MlKem768KeyPair, SecretKey32, X25519KeyPair};
+use hardlock_snc::identity::DeviceIdentity;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct LegacyX25519 {
+    #[serde(with = "serde_bytes")]
+    sk: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    pk: Vec<u8>,
+}
+
+#[test]
+fn debug_output_redacts_secrets() {
+    let id = DeviceIdentity::generate("alice".into(), "d1".into());
+    let dbg = format!("{id:?}");
+    assert!(!dbg.contains(&hex::encode(id.x25519.sk.expose())));
+    assert!(!dbg.contains(&hex::encode(id.ed25519.sk.expose())));
+    assert!(!dbg.contains(&format!("{:?}", id.x25519.sk.expose())));
+    assert!(dbg.contains("<redacted>"));
+    assert!(dbg.contains(&hex::encode(id.x25519.pk)));
+
+    let kem = MlKem768KeyPair::generate();
+    assert!(format!("{kem:?}").contains("redacted"));
+}
+
+#[test]
+fn secret_equality_and_length_checks() {
+    let a = SecretKey32::from_bytes([7u8; 32]);
+    assert_eq!(a, SecretKey32::from_slice(&[7u8; 32]).unwrap());
+    assert_ne!(a, SecretKey32::from_bytes([8u8; 32]));
+    assert!(SecretKey32::from_slice(&[7u8; 31]).is_err());
+}
+
+#[test]
+fn serialization_stays_compatible() {
+    let kp = X25519KeyPair::generate();
+    let legacy = LegacyX25519 {
+        sk: kp.sk.expose().to_vec(),
+        pk: kp.pk.to_vec(),
+    };
+    let old = bincode::serialize(&legacy).unwrap();
+    assert_eq!(bincode::serialize(&kp).unwrap(), old);
+    let back: X25519KeyPair = bincode::deserialize(&old).unwrap();
+    assert_eq!(back, kp);
+
+    let id = DeviceIdentity::generate("bob".into(), "d2".into());
+    let json = serde_json::to_string(&id).unwrap();
+    let back: DeviceIdentity = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.x25519, id.x25519);
+    assert_eq!(back.ed25519, id.ed25519);
+}
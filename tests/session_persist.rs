@@ -0,0 +1,104 @@
+mod common;
+
+use hardlock_snc::ratchet;
+use hardlock_snc::session::Session;
+
+#[test]
+fn skipped_key_survives_save_load() {
+    let (mut ra, mut rb) = common::pair();
+    let ad = b"ad";
+    let (h1, n1, c1) = ratchet::encrypt(&mut ra, ad, b"m1").unwrap();
+    let (h2, n2, c2) = ratchet::encrypt(&mut ra, ad, b"m2").unwrap();
+    let p2 = ratchet::decrypt(&mut rb, ad, &h2, &n2, &c2).unwrap();
+    assert_eq!(&p2, b"m2");
+
+    let dir = std::env::temp_dir().join(format!("hl_persist_{}", std::process::id()));
+    let dir_s = dir.to_str().unwrap();
+    Session::new("b->a".into(), rb)
+        .save_fs(dir_s, "pass")
+        .unwrap();
+    let mut sb = Session::load_fs(dir_s, "pass", "b->a").unwrap();
+    // Mauvaise phrase de passe : l'erreur de déchiffrement remonte telle quelle.
+    let e = Session::load_fs(dir_s, "wrong", "b->a").err().unwrap();
+    assert_eq!(e.to_string(), "decrypt");
+    std::fs::remove_dir_all(&dir).ok();
+
+    let p1 = ratchet::decrypt(&mut sb.state, ad, &h1, &n1, &c1).unwrap();
+    assert_eq!(&p1, b"m1");
+}
+
+/// Disposition de `Session` avant l'enregistrement versionné.
+#[derive(serde::Serialize)]
+struct LegacyState {
+    dh_s_priv: [u8; 32],
+    dh_s_pub: [u8; 32],
+    dh_r_pub: [u8; 32],
+    rk: [u8; 32],
+    ck_s: [u8; 32],
+    ck_r: [u8; 32],
+    ns: u32,
+    nr: u32,
+    pn: u32,
+    #[serde(with = "serde_bytes")]
+    skipped_index: Vec<u8>,
+    delivered_win: hardlock_snc::ratchet::state::DeliveredWindow,
+}
+#[derive(serde::Serialize)]
+struct LegacySession {
+    peer_id: String,
+    state: LegacyState,
+}
+
+#[test]
+fn legacy_session_is_migrated() {
+    use hardlock_snc::ratchet::state::DEFAULT_MAX_SKIP;
+    use hardlock_snc::store::fs::{ArgonProfile, FileStore};
+    use hardlock_snc::store::StateStore;
+
+    let (mut ra, mut rb) = common::pair();
+    let (h1, n1, c1) = ratchet::encrypt(&mut ra, b"ad", b"m1").unwrap();
+    ratchet::decrypt(&mut rb, b"ad", &h1, &n1, &c1).unwrap();
+
+    let legacy = LegacySession {
+        peer_id: "b->a".into(),
+        state: LegacyState {
+            dh_s_priv: rb.dh_s_priv,
+            dh_s_pub: rb.dh_s_pub,
+            dh_r_pub: rb.dh_r_pub,
+            rk: rb.rk,
+            ck_s: rb.ck_s,
+            ck_r: rb.ck_r,
+            ns: rb.ns,
+            nr: rb.nr,
+            pn: rb.pn,
+            skipped_index: Vec::new(),
+            delivered_win: rb.delivered_win.clone(),
+        },
+    };
+    let dir = std::env::temp_dir().join(format!("hl_legacy_{}", std::process::id()));
+    let dir_s = dir.to_str().unwrap();
+    FileStore::open(dir.clone(), "pass", ArgonProfile::BALANCED)
+        .unwrap()
+        .save("b->a", &legacy)
+        .unwrap();
+    let mut sb = Session::load_fs(dir_s, "pass", "b->a").unwrap();
+    assert_eq!(sb.peer_id, "b->a");
+    assert_eq!(sb.state.max_skip, DEFAULT_MAX_SKIP);
+    assert!(sb.state.he.is_none());
+
+    let (h2, n2, c2) = ratchet::encrypt(&mut ra, b"ad", b"m2").unwrap();
+    assert_eq!(
+        ratchet::decrypt(&mut sb.state, b"ad", &h2, &n2, &c2).unwrap(),
+        b"m2"
+    );
+    // Rejeu de m1 toujours détecté après migration.
+    assert!(ratchet::decrypt(&mut sb.state, b"ad", &h1, &n1, &c1).is_err());
+
+    // Réécrit au format versionné, puis relu.
+    sb.save_fs(dir_s, "pass").unwrap();
+    assert_eq!(
+        Session::load_fs(dir_s, "pass", "b->a").unwrap().state.nr,
+        sb.state.nr
+    );
+    std::fs::remove_dir_all(&dir).ok();
+}
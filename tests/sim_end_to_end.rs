@@ -14,31 +14,17 @@ fn sim_end_to_end_reorder() {
         hpke_initiate_with_binder(&bob.x25519.public(), suites::HL1_BASE).unwrap();
     let frame = encode_init_v2(suites::HL1_BASE, &enc, &binder);
     let (suite, enc2, binder2) = decode_init_v2(&frame).unwrap();
-    let s_b = hpke_accept_with_binder(
-        suite,
-        &bob.x25519.sk.clone().try_into().unwrap(),
-        &enc2,
-        &binder2,
-    )
-    .unwrap();
+    let s_b = hpke_accept_with_binder(suite, &bob.x25519.sk, &enc2, &binder2).unwrap();
     assert_eq!(s_a, s_b);
 
-    let mut ra = ratchet::init_initiator(
-        s_a,
-        alice.x25519.sk.clone().try_into().unwrap(),
-        bob.x25519.public(),
-    );
-    let mut rb = ratchet::init_responder(
-        s_b,
-        bob.x25519.sk.clone().try_into().unwrap(),
-        alice.x25519.public(),
-    );
+    let mut ra = ratchet::init_initiator(s_a, *alice.x25519.sk.expose(), bob.x25519.public());
+    let mut rb = ratchet::init_responder(s_b, *bob.x25519.sk.expose(), alice.x25519.public());
 
     let ad = b"sim/e2e";
     let mut msgs = Vec::new();
     for i in 0..64u32 {
         let m = format!("m{}", i);
-        let (h, n, ct) = ratchet::encrypt(&mut ra, ad, m.as_bytes());
+        let (h, n, ct) = ratchet::encrypt(&mut ra, ad, m.as_bytes()).unwrap();
         msgs.push((h, n, ct));
     }
     msgs.shuffle(&mut rng);
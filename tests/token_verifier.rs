@@ -0,0 +1,185 @@
+use hardlock_snc::envelope::{
+    derive_k_s, seal_envelope, token_build, token_verify, EnvelopeError, PadProfile, ReplayCache,
+    SenderClaims, TokenVerifier,
+};
+use hardlock_snc::error::ErrorCode;
+use hardlock_snc::suites;
+use std::cell::Cell;
+use std::rc::Rc;
+
+const T0: u64 = 1_700_000_000;
+const ALICE: [u8; 32] = [0xA1; 32];
+const MALLORY: [u8; 32] = [0x66; 32];
+
+fn verifier(k_s: [u8; 32], capacity: usize) -> (TokenVerifier<impl Fn() -> u64>, Rc<Cell<u64>>) {
+    let now = Rc::new(Cell::new(T0));
+    let t = Rc::clone(&now);
+    (
+        TokenVerifier::with_clock(k_s, move || t.get(), capacity),
+        now,
+    )
+}
+
+#[test]
+fn tokens_are_single_use() {
+    let k_s = derive_k_s(b"master", b"salt");
+    let (mut v, _) = verifier(k_s, 16);
+    let tok = token_build(&k_s, T0 + 60, &ALICE, b"chat");
+
+    let s = v.verify(&tok, b"chat").unwrap();
+    assert_eq!(
+        (s.sender_pub, &s.scope[..], s.expiry_unix_s),
+        (ALICE, &b"chat"[..], T0 + 60)
+    );
+    // `token_verify` seul accepte le rejeu; le vérificateur non.
+    assert!(token_verify(&k_s, &tok, T0).is_some());
+    let e = v.verify(&tok, b"chat").unwrap_err();
+    assert_eq!(e, EnvelopeError::Replayed);
+    assert_eq!(e.code(), ErrorCode::TokenReplayed);
+
+    // Un jeton refusé (mauvaise portée) n'est pas consommé.
+    let other = token_build(&k_s, T0 + 60, &ALICE, b"chat");
+    assert_eq!(
+        v.verify(&other, b"files").unwrap_err().code(),
+        ErrorCode::ScopeMismatch
+    );
+    assert!(v.verify(&other, b"chat").is_ok());
+    assert_eq!(v.replay_cache().len(), 2);
+
+    let wrong = derive_k_s(b"master", b"other");
+    assert_eq!(
+        v.verify(&token_build(&wrong, T0 + 60, &ALICE, b"chat"), b"chat")
+            .unwrap_err(),
+        EnvelopeError::BadToken
+    );
+}
+
+#[test]
+fn clock_drives_expiry() {
+    let k_s = derive_k_s(b"master", b"salt");
+    let (mut v, now) = verifier(k_s, 16);
+    let a = token_build(&k_s, T0 + 10, &ALICE, b"s");
+    let b = token_build(&k_s, T0 + 10, &ALICE, b"s");
+    assert!(v.verify(&a, b"s").is_ok());
+    now.set(T0 + 11);
+    assert_eq!(v.verify(&b, b"s").unwrap_err(), EnvelopeError::Expired);
+    assert_eq!(
+        v.verify(&b, b"s").unwrap_err().code(),
+        ErrorCode::TokenExpired
+    );
+}
+
+#[test]
+fn revocation_by_sender_and_scope() {
+    let k_s = derive_k_s(b"master", b"salt");
+    let (mut v, _) = verifier(k_s, 16);
+    v.revocations_mut().revoke(MALLORY, b"chat");
+    assert!(v.revocations().is_revoked(&MALLORY, b"chat"));
+    assert!(!v.revocations().is_revoked(&MALLORY, b"files"));
+
+    let e = v
+        .verify(&token_build(&k_s, T0 + 60, &MALLORY, b"chat"), b"chat")
+        .unwrap_err();
+    assert_eq!(e, EnvelopeError::Revoked);
+    assert_eq!(e.code(), ErrorCode::SenderRevoked);
+    assert!(v
+        .verify(&token_build(&k_s, T0 + 60, &MALLORY, b"files"), b"files")
+        .is_ok());
+    assert!(v
+        .verify(&token_build(&k_s, T0 + 60, &ALICE, b"chat"), b"chat")
+        .is_ok());
+
+    v.revocations_mut().revoke_sender(MALLORY);
+    assert_eq!(
+        v.verify(&token_build(&k_s, T0 + 60, &MALLORY, b"files"), b"files")
+            .unwrap_err(),
+        EnvelopeError::Revoked
+    );
+    v.revocations_mut().reinstate(&MALLORY);
+    assert!(v
+        .verify(&token_build(&k_s, T0 + 60, &MALLORY, b"chat"), b"chat")
+        .is_ok());
+}
+
+#[test]
+fn envelopes_are_checked_too() {
+    let k_s = derive_k_s(b"master", b"salt");
+    let (mut v, _) = verifier(k_s, 16);
+    let claims = SenderClaims {
+        sender_pub: ALICE,
+        scope: b"chat",
+        expiry_unix_s: T0 + 60,
+    };
+    let env = seal_envelope(
+        &k_s,
+        suites::HL1_BASE,
+        T0,
+        PadProfile::Padme,
+        &claims,
+        b"frame",
+    )
+    .unwrap();
+    assert_eq!(v.verify_envelope(&env, b"chat").unwrap().frame, b"frame");
+    assert_eq!(
+        v.verify_envelope(&env, b"chat").unwrap_err(),
+        EnvelopeError::Replayed
+    );
+    v.revocations_mut().revoke(ALICE, b"chat");
+    let env2 = seal_envelope(
+        &k_s,
+        suites::HL1_BASE,
+        T0,
+        PadProfile::Padme,
+        &claims,
+        b"frame",
+    )
+    .unwrap();
+    assert_eq!(
+        v.verify_envelope(&env2, b"chat").unwrap_err(),
+        EnvelopeError::Revoked
+    );
+}
+
+#[test]
+fn replay_cache_is_bounded() {
+    let mut c = ReplayCache::new(3);
+    assert!(c.insert([1; 24], T0 + 5, T0).is_ok());
+    assert!(c.insert([2; 24], T0 + 100, T0).is_ok());
+    assert!(c.insert([3; 24], T0 + 100, T0).is_ok());
+    assert_eq!(
+        c.insert([2; 24], T0 + 100, T0).unwrap_err(),
+        EnvelopeError::Replayed
+    );
+    // Plein : le nonce expiré part en premier.
+    assert!(c.insert([4; 24], T0 + 100, T0 + 6).is_ok());
+    assert!(!c.contains(&[1; 24]) && c.contains(&[2; 24]));
+    // Plus rien d'expiré : refus, aucun nonce valide n'est oublié.
+    let e = c.insert([5; 24], T0 + 100, T0 + 6).unwrap_err();
+    assert_eq!(e, EnvelopeError::ReplayCacheFull);
+    assert_eq!(e.code(), ErrorCode::ReplayCacheFull);
+    assert!(c.contains(&[2; 24]) && !c.contains(&[5; 24]));
+    assert_eq!(c.len(), 3);
+    assert_eq!(c.purge_expired(T0 + 101), 3);
+    assert!(c.is_empty());
+}
+
+#[test]
+fn full_replay_cache_fails_closed() {
+    let k_s = derive_k_s(b"master", b"salt");
+    let (mut v, now) = verifier(k_s, 2);
+    let a = token_build(&k_s, T0 + 10, &ALICE, b"s");
+    assert!(v.verify(&a, b"s").is_ok());
+    assert!(v
+        .verify(&token_build(&k_s, T0 + 60, &ALICE, b"s"), b"s")
+        .is_ok());
+    let c = token_build(&k_s, T0 + 60, &ALICE, b"s");
+    assert_eq!(
+        v.verify(&c, b"s").unwrap_err(),
+        EnvelopeError::ReplayCacheFull
+    );
+    // `a` reste connu tant qu'il est valide : son rejeu est détecté.
+    assert_eq!(v.verify(&a, b"s").unwrap_err(), EnvelopeError::Replayed);
+    now.set(T0 + 11);
+    assert!(v.verify(&c, b"s").is_ok());
+    assert!(!v.replay_cache().contains(&a.nonce));
+}
@@ -1,20 +1,54 @@
 use hardlock_snc::envelope::transport::{
     decode_transport_header, encode_transport_header, TransportHeader,
 };
-use hardlock_snc::envelope::PadProfile;
+use hardlock_snc::envelope::{PadBuckets, PadProfile};
 
 #[test]
 fn transport_header_roundtrip() {
     let h = TransportHeader {
+        suite: 0x01,
         ts_unix_s: 4102444800,
+        nonce: [9u8; 24],
         token_len: 5,
         profile: PadProfile::Balanced,
     };
     let tok = vec![1, 2, 3, 4, 5];
     let buf = encode_transport_header(&h, &tok);
     let (h2, tok2) = decode_transport_header(&buf).expect("decode");
+    assert_eq!(h.suite, h2.suite);
     assert_eq!(h.ts_unix_s, h2.ts_unix_s);
+    assert_eq!(h.nonce, h2.nonce);
     assert_eq!(h.token_len, h2.token_len);
-    assert_eq!(h.profile as u8, h2.profile as u8);
+    assert_eq!(h.profile, h2.profile);
     assert_eq!(tok, tok2);
 }
+
+#[test]
+fn transport_header_carries_padme_and_custom() {
+    let buckets = PadBuckets::new(&[128, 1024, 65_536]).unwrap();
+    for profile in [PadProfile::Padme, PadProfile::Custom(buckets)] {
+        let h = TransportHeader {
+            suite: 0x11,
+            ts_unix_s: 1,
+            nonce: [2u8; 24],
+            token_len: 3,
+            profile,
+        };
+        let buf = encode_transport_header(&h, b"tok");
+        let (h2, tok) = decode_transport_header(&buf).expect("decode");
+        assert_eq!(h2, h);
+        assert_eq!(tok, b"tok");
+    }
+
+    let h = TransportHeader {
+        suite: 0x01,
+        ts_unix_s: 1,
+        nonce: [0u8; 24],
+        token_len: 0,
+        profile: PadProfile::Custom(buckets),
+    };
+    let mut buf = encode_transport_header(&h, b"");
+    buf[39] = 0; // premier seau nul
+    assert!(decode_transport_header(&buf).is_none());
+    assert!(decode_transport_header(&buf[..40]).is_none());
+}
@@ -0,0 +1,58 @@
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::verification::{
+    safety_number, safety_number_versioned, short_auth_string, ContactKeys, SAFETY_NUMBER_VERSION,
+};
+
+#[test]
+fn safety_number_is_symmetric_and_stable() {
+    let a = ContactKeys::from_identity(&DeviceIdentity::generate("alice".into(), "d1".into()));
+    let b = ContactKeys::from_identity(&DeviceIdentity::generate("bob".into(), "d2".into()));
+    let ab = safety_number(&a, &b).unwrap();
+    assert_eq!(ab, safety_number(&b, &a).unwrap());
+    assert_eq!(ab, safety_number(&a, &b).unwrap());
+    assert_eq!(ab.version, SAFETY_NUMBER_VERSION);
+    assert_eq!(ab.digits().len(), 60);
+    assert!(ab.digits().bytes().all(|c| c.is_ascii_digit()));
+    assert_eq!(ab.to_string().split(' ').count(), 12);
+
+    let sas = short_auth_string(&a, &b).unwrap();
+    assert_eq!(sas, short_auth_string(&b, &a).unwrap());
+    assert_eq!(sas.len(), 7);
+}
+
+#[test]
+fn safety_number_changes_with_any_key_or_user_id() {
+    let a = ContactKeys::from_identity(&DeviceIdentity::generate("alice".into(), "d1".into()));
+    let b = ContactKeys::from_identity(&DeviceIdentity::generate("bob".into(), "d2".into()));
+    let base = safety_number(&a, &b).unwrap();
+
+    let mut renamed = b.clone();
+    renamed.user_id = "mallory".into();
+    assert_ne!(base, safety_number(&a, &renamed).unwrap());
+
+    let mut rekeyed = b.clone();
+    rekeyed.identity_x25519[0] ^= 1;
+    assert_ne!(base, safety_number(&a, &rekeyed).unwrap());
+
+    let mut resigned = b;
+    resigned.identity_ed25519[0] ^= 1;
+    assert_ne!(base, safety_number(&a, &resigned).unwrap());
+}
+
+#[test]
+fn unknown_version_rejected() {
+    let a = ContactKeys::from_identity(&DeviceIdentity::generate("alice".into(), "d1".into()));
+    let b = ContactKeys::from_identity(&DeviceIdentity::generate("bob".into(), "d2".into()));
+    assert!(safety_number_versioned(SAFETY_NUMBER_VERSION + 1, &a, &b).is_err());
+}
+
+#[test]
+fn oversized_user_id_rejected() {
+    let a = ContactKeys::from_identity(&DeviceIdentity::generate("alice".into(), "d1".into()));
+    let mut b = a.clone();
+    b.user_id = "x".repeat(usize::from(u16::MAX));
+    assert!(safety_number(&a, &b).is_ok());
+    b.user_id.push('x');
+    assert!(safety_number(&a, &b).is_err());
+    assert!(short_auth_string(&a, &b).is_err());
+}
@@ -0,0 +1,110 @@
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::session::Handshake;
+use hardlock_snc::verification::qr::{open_attestation, seal_attestation, verify_scanned};
+use hardlock_snc::verification::{ContactKeys, MutualVerification, QrPayload, VerifiedAttestation};
+use std::time::{Duration, Instant};
+
+#[test]
+fn qr_payload_roundtrip_and_compare() {
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let keys = ContactKeys::from_identity(&bob);
+    let text = QrPayload::for_contact(&keys).to_base45().unwrap();
+    assert!(text
+        .bytes()
+        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || b" $%*+-./:".contains(&c)));
+    assert_eq!(
+        QrPayload::from_base45(&text).unwrap(),
+        QrPayload::for_contact(&keys)
+    );
+    assert!(verify_scanned(&text, &keys).unwrap());
+
+    let mut swapped = keys.clone();
+    swapped.identity_x25519[0] ^= 1;
+    assert!(!verify_scanned(&text, &swapped).unwrap());
+    assert!(QrPayload::from_base45("NOT A PAYLOAD").is_err());
+}
+
+#[test]
+fn mutual_scan_and_attestation_over_session() {
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let (ka, kb) = (
+        ContactKeys::from_identity(&alice),
+        ContactKeys::from_identity(&bob),
+    );
+    let (mut hs_a, init) =
+        Handshake::initiate("bob".into(), &bob.x25519.public(), Duration::from_secs(5)).unwrap();
+    let (hs_b, resp) = Handshake::respond(&bob, "alice".into(), &init).unwrap();
+    hs_a.on_response(&resp, Instant::now()).unwrap();
+    let (mut sa, mut sb) = (hs_a.into_session().unwrap(), hs_b.into_session().unwrap());
+
+    // Alice scanne le QR de Bob et lui envoie son attestation.
+    let mut alice_view = MutualVerification::default();
+    assert!(alice_view
+        .on_scan(&QrPayload::for_contact(&kb).to_base45().unwrap(), &kb)
+        .unwrap());
+    let att = VerifiedAttestation::sign(&alice, &kb, 1_700_000_000_000).unwrap();
+    let (h, n, ct) = seal_attestation(&mut sa.state, &att).unwrap();
+    let received = open_attestation(&mut sb.state, &h, &n, &ct).unwrap();
+
+    // Bob scanne le QR d'Alice, puis reçoit l'attestation d'Alice.
+    let mut bob_view = MutualVerification::default();
+    assert!(bob_view
+        .on_scan(&QrPayload::for_contact(&ka).to_base45().unwrap(), &ka)
+        .unwrap());
+    assert!(!bob_view.is_verified());
+    assert!(bob_view.on_attestation(&received, &ka, &kb));
+    assert!(bob_view.is_verified());
+
+    let back = VerifiedAttestation::sign(&bob, &ka, 1_700_000_000_001).unwrap();
+    let (h, n, ct) = seal_attestation(&mut sb.state, &back).unwrap();
+    let received = open_attestation(&mut sa.state, &h, &n, &ct).unwrap();
+    assert!(alice_view.on_attestation(&received, &kb, &ka));
+    assert!(alice_view.is_verified());
+}
+
+#[test]
+fn forged_attestation_rejected() {
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let bob = DeviceIdentity::generate("bob".into(), "laptop".into());
+    let mallory = DeviceIdentity::generate("alice".into(), "evil".into());
+    let (ka, kb) = (
+        ContactKeys::from_identity(&alice),
+        ContactKeys::from_identity(&bob),
+    );
+    let forged = VerifiedAttestation::sign(&mallory, &kb, 1).unwrap();
+    assert!(!forged.verify(&ka, &kb));
+
+    let mut att = VerifiedAttestation::sign(&alice, &kb, 1).unwrap();
+    att.ts_ms += 1;
+    assert!(!att.verify(&ka, &kb));
+
+    let other = VerifiedAttestation::sign(&alice, &ka, 1).unwrap();
+    assert!(!other.verify(&ka, &kb));
+    assert_eq!(
+        VerifiedAttestation::decode(&other.encode().unwrap()).unwrap(),
+        other
+    );
+}
+
+#[test]
+fn oversized_user_ids_are_errors() {
+    let alice = DeviceIdentity::generate("alice".into(), "phone".into());
+    let mut kb = ContactKeys::from_identity(&alice);
+    kb.user_id = "b".repeat(usize::from(u16::MAX) + 1);
+    let qr = QrPayload::for_contact(&kb);
+    assert!(qr.encode().is_err());
+    assert!(qr.to_base45().is_err());
+    assert!(VerifiedAttestation::sign(&alice, &kb, 1).is_err());
+
+    // Charge QR encodable, mais trop longue pour le champ `qr_len:u16`.
+    kb.user_id.truncate(usize::from(u16::MAX) - 8);
+    assert!(QrPayload::for_contact(&kb).encode().is_ok());
+    assert!(VerifiedAttestation::sign(&alice, &kb, 1).is_err());
+
+    let ka = ContactKeys::from_identity(&alice);
+    let mut att = VerifiedAttestation::sign(&alice, &ka, 1).unwrap();
+    att.verifier_user_id = "a".repeat(usize::from(u16::MAX) + 1);
+    assert!(att.encode().is_err());
+    assert!(!att.verify(&ka, &ka));
+}
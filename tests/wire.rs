@@ -10,7 +10,7 @@ fn pack_unpack_roundtrip() {
     };
     let nonce = [5u8; 24];
     let ct = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
-    let frame = pack_message(0x0110, &h, &nonce, &ct, 64);
+    let frame = pack_message(0x0110, &h, &nonce, &ct).unwrap();
     let (ver, h2, n2, ct2) = unpack_message(&frame).expect("unpack");
     assert_eq!(ver, 0x0110);
     assert_eq!(h2.dh_pub, h.dh_pub);
@@ -0,0 +1,161 @@
+use hardlock_snc::envelope::PadProfile;
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::ratchet;
+use hardlock_snc::session::{bootstrap_initiator, bootstrap_responder};
+use hardlock_snc::wire::fragment::{
+    fragment, Fragment, Reassembler, ReassemblyLimits, FRAGMENT_HEADER_LEN,
+};
+use hardlock_snc::wire::{pack_message, unpack_message, WireError};
+use hardlock_snc::HL_VERSION;
+use std::time::{Duration, Instant};
+
+#[test]
+fn padded_frame_survives_ble_mtu_out_of_order() {
+    let bob = DeviceIdentity::generate("bob".into(), "d".into());
+    let (init, mut ra) = bootstrap_initiator(&bob.x25519.public()).unwrap();
+    let mut rb = bootstrap_responder(&bob, &init).unwrap();
+    let (h, n, ct) =
+        ratchet::encrypt_padded(&mut ra, b"ad", b"over BLE", PadProfile::Throughput).unwrap();
+    let frame = pack_message(HL_VERSION, &h, &n, &ct).unwrap();
+
+    let mut frags = fragment(&frame, 0xdead_beef, 185).unwrap();
+    assert!(frags.len() > 1 && frags.iter().all(|f| f.len() <= 185));
+    frags.reverse();
+    frags.swap(0, 2);
+
+    let mut r = Reassembler::default();
+    let now = Instant::now();
+    let last = frags.pop().unwrap();
+    for f in &frags {
+        assert_eq!(r.push(f, now).unwrap(), None);
+    }
+    assert_eq!(r.push(&frags[0], now).unwrap(), None); // doublon ignoré
+    let whole = r.push(&last, now).unwrap().unwrap();
+    assert_eq!(whole, frame);
+    assert_eq!((r.pending(), r.buffered_bytes()), (0, 0));
+
+    let (_, hh, nn, cc) = unpack_message(&whole).unwrap();
+    assert_eq!(
+        ratchet::decrypt_padded(&mut rb, b"ad", &hh, &nn, &cc).unwrap(),
+        b"over BLE"
+    );
+}
+
+#[test]
+fn tampered_fragment_fails_inner_aead() {
+    let bob = DeviceIdentity::generate("bob".into(), "d".into());
+    let (init, mut ra) = bootstrap_initiator(&bob.x25519.public()).unwrap();
+    let mut rb = bootstrap_responder(&bob, &init).unwrap();
+    let (h, n, ct) = ratchet::encrypt(&mut ra, b"ad", &[5u8; 300]).unwrap();
+    let frame = pack_message(HL_VERSION, &h, &n, &ct).unwrap();
+    let mut frags = fragment(&frame, 1, 100).unwrap();
+    let k = frags.len() - 2;
+    frags[k][FRAGMENT_HEADER_LEN] ^= 1;
+
+    let mut r = Reassembler::default();
+    let now = Instant::now();
+    let whole = frags.iter().find_map(|f| r.push(f, now).unwrap()).unwrap();
+    let (_, hh, nn, cc) = unpack_message(&whole).unwrap();
+    assert!(ratchet::decrypt(&mut rb, b"ad", &hh, &nn, &cc).is_err());
+}
+
+#[test]
+fn incomplete_sets_expire_and_bounds_hold() {
+    let t0 = Instant::now();
+    let mut r = Reassembler::new(ReassemblyLimits {
+        max_pending: 2,
+        max_bytes: 1000,
+        timeout: Duration::from_secs(5),
+    });
+    let a = fragment(&[1u8; 200], 1, 109).unwrap();
+    let b = fragment(&[2u8; 200], 2, 109).unwrap();
+    let c = fragment(&[3u8; 200], 3, 109).unwrap();
+    r.push(&a[0], t0).unwrap();
+    r.push(&b[0], t0 + Duration::from_secs(1)).unwrap();
+    // Troisième ensemble : le plus ancien (1) est évincé.
+    r.push(&c[0], t0 + Duration::from_secs(2)).unwrap();
+    assert_eq!(r.pending(), 2);
+    // Le set 1 repart de zéro et évince à son tour le set 2.
+    assert_eq!(r.push(&a[1], t0 + Duration::from_secs(4)).unwrap(), None);
+    assert_eq!(r.pending(), 2);
+
+    assert_eq!(r.expire(t0 + Duration::from_secs(8)), 1);
+    assert_eq!(r.expire(t0 + Duration::from_secs(60)), 1);
+    assert_eq!((r.pending(), r.buffered_bytes()), (0, 0));
+
+    let big = fragment(&[0u8; 2000], 9, 1100).unwrap();
+    assert!(matches!(
+        r.push(&big[0], t0),
+        Err(WireError::FrameTooLarge { .. })
+    ));
+}
+
+#[test]
+fn malformed_fragments_are_rejected() {
+    assert!(matches!(
+        fragment(b"x", 1, FRAGMENT_HEADER_LEN),
+        Err(WireError::Malformed(_))
+    ));
+    let single = fragment(b"", 3, 64).unwrap();
+    assert_eq!(single.len(), 1);
+    assert_eq!(
+        Reassembler::default()
+            .push(&single[0], Instant::now())
+            .unwrap(),
+        Some(vec![])
+    );
+
+    let frags = fragment(&[0u8; 100], 4, 50).unwrap();
+    let mut bad = frags[0].clone();
+    bad[5..7].copy_from_slice(&9u16.to_le_bytes());
+    assert!(Fragment::parse(&bad).is_err());
+    bad[0] = 2;
+    assert_eq!(Fragment::parse(&bad), Err(WireError::BadVersion(2)));
+
+    let mut r = Reassembler::default();
+    let now = Instant::now();
+    r.push(&frags[0], now).unwrap();
+    let other = fragment(&[0u8; 200], 4, 50).unwrap().remove(1);
+    assert!(matches!(r.push(&other, now), Err(WireError::Malformed(_))));
+    // Le fragment incohérent est ignoré : l'ensemble légitime se termine.
+    assert_eq!(r.pending(), 1);
+    let rest = frags[1..]
+        .iter()
+        .find_map(|f| r.push(f, now).unwrap())
+        .unwrap();
+    assert_eq!(rest, vec![0u8; 100]);
+}
+
+#[test]
+fn fragment_slots_count_against_byte_budget() {
+    let now = Instant::now();
+    let mut r = Reassembler::default();
+    // Un seul fragment annonçant 65 535 fragments réserverait ~1,5 Mio.
+    let mut huge = fragment(&[7u8; 40], 5, 40).unwrap().remove(0);
+    huge[7..9].copy_from_slice(&u16::MAX.to_le_bytes());
+    assert!(matches!(
+        r.push(&huge, now),
+        Err(WireError::FrameTooLarge { .. })
+    ));
+    assert_eq!((r.pending(), r.buffered_bytes()), (0, 0));
+
+    let frags = fragment(&[1u8; 100], 6, 50).unwrap();
+    r.push(&frags[0], now).unwrap();
+    assert!(r.buffered_bytes() > frags[0].len() - FRAGMENT_HEADER_LEN);
+
+    // Ensemble seul en attente qui ne tient plus : abandonné, pas recréé à vide.
+    let slots = 3 * std::mem::size_of::<Option<Vec<u8>>>();
+    let mut r = Reassembler::new(ReassemblyLimits {
+        max_bytes: slots + 150,
+        ..ReassemblyLimits::default()
+    });
+    let frags = fragment(&[2u8; 300], 7, FRAGMENT_HEADER_LEN + 100).unwrap();
+    assert_eq!(frags.len(), 3);
+    assert_eq!(r.push(&frags[0], now), Ok(None));
+    assert_eq!(r.buffered_bytes(), slots + 100);
+    assert!(matches!(
+        r.push(&frags[1], now),
+        Err(WireError::FrameTooLarge { .. })
+    ));
+    assert_eq!((r.pending(), r.buffered_bytes()), (0, 0));
+}
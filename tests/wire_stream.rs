@@ -0,0 +1,138 @@
+use hardlock_snc::error::ErrorCode;
+use hardlock_snc::identity::DeviceIdentity;
+use hardlock_snc::ratchet;
+use hardlock_snc::session::{bootstrap_initiator, bootstrap_responder};
+use hardlock_snc::wire::stream::{FrameReader, FrameWriter};
+use hardlock_snc::wire::version::{encode_message, FrameHeader, WIRE_CURRENT};
+use hardlock_snc::wire::WireError;
+use std::io::{self, Cursor, Read};
+
+/// Délivre un octet par appel et un `WouldBlock` sur deux, comme une socket non bloquante.
+struct Trickle {
+    data: Vec<u8>,
+    pos: usize,
+    block: bool,
+}
+impl Read for Trickle {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        self.block = !self.block;
+        if self.block {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        if self.pos == self.data.len() || out.is_empty() {
+            return Ok(0);
+        }
+        out[0] = self.data[self.pos];
+        self.pos += 1;
+        Ok(1)
+    }
+}
+
+fn wire_code(e: &io::Error) -> Option<ErrorCode> {
+    e.get_ref()?
+        .downcast_ref::<WireError>()
+        .map(WireError::code)
+}
+
+#[test]
+fn messages_flow_over_a_byte_stream() {
+    let bob = DeviceIdentity::generate("bob".into(), "d".into());
+    let (init, mut ra) = bootstrap_initiator(&bob.x25519.public()).unwrap();
+    let mut rb = bootstrap_responder(&bob, &init).unwrap();
+
+    let mut w = FrameWriter::new(Vec::new());
+    for pt in [&b"one"[..], b"two", b""] {
+        let (h, n, ct) = ratchet::encrypt(&mut ra, b"ad", pt).unwrap();
+        let f = encode_message(WIRE_CURRENT, &FrameHeader::Clear(h), &n, &ct).unwrap();
+        w.write_frame(&f).unwrap();
+    }
+    let mut r = FrameReader::new(Cursor::new(w.into_inner()));
+    let mut got = Vec::new();
+    while let Some(m) = r.next_message(false).unwrap() {
+        let FrameHeader::Clear(h) = m.header else {
+            panic!("clear header expected")
+        };
+        got.push(ratchet::decrypt(&mut rb, b"ad", &h, &m.nonce, &m.ct).unwrap());
+    }
+    assert_eq!(got, [b"one".to_vec(), b"two".to_vec(), Vec::new()]);
+}
+
+#[test]
+fn partial_reads_resume_after_would_block() {
+    let mut w = FrameWriter::new(Vec::new());
+    w.write_frame(b"alpha").unwrap();
+    w.write_frame(b"").unwrap();
+    w.write_frame(&[7u8; 300]).unwrap();
+    let mut r = FrameReader::new(Trickle {
+        data: w.into_inner(),
+        pos: 0,
+        block: false,
+    });
+    let mut frames = Vec::new();
+    loop {
+        match r.next_frame() {
+            Ok(Some(f)) => frames.push(f.to_vec()),
+            Ok(None) => break,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => panic!("{e}"),
+        }
+    }
+    assert_eq!(frames, [b"alpha".to_vec(), Vec::new(), vec![7u8; 300]]);
+}
+
+#[test]
+fn limits_and_truncation() {
+    let mut w = FrameWriter::with_max_frame(Vec::new(), 16);
+    let err = w.write_frame(&[0u8; 17]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(wire_code(&err), Some(ErrorCode::FrameTooLarge));
+    assert!(w.get_ref().is_empty());
+
+    // Longueur annoncée énorme : refusée sans lire ni allouer le corps.
+    let mut r = FrameReader::with_max_frame(Cursor::new(u32::MAX.to_le_bytes()), 16);
+    let err = r.next_frame().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(wire_code(&err), Some(ErrorCode::FrameTooLarge));
+
+    let mut bytes = 8u32.to_le_bytes().to_vec();
+    bytes.extend_from_slice(b"abc");
+    let err = FrameReader::new(Cursor::new(bytes))
+        .next_frame()
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    let err = FrameReader::new(Cursor::new(vec![1u8, 0]))
+        .next_frame()
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    let mut w = FrameWriter::new(Vec::new());
+    w.write_frame(&[0xff, 0xff, 0, 0]).unwrap();
+    let err = FrameReader::new(Cursor::new(w.into_inner()))
+        .next_message(false)
+        .unwrap_err();
+    assert_eq!(wire_code(&err), Some(ErrorCode::BadVersion));
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn tokio_codec_splits_frames() {
+    use bytes::BytesMut;
+    use hardlock_snc::wire::stream::FrameCodec;
+    use tokio_util::codec::{Decoder, Encoder};
+
+    let mut codec = FrameCodec::new(64);
+    let mut buf = BytesMut::new();
+    codec.encode(&b"hello"[..], &mut buf).unwrap();
+    codec.encode(&b"world!"[..], &mut buf).unwrap();
+    assert!(codec.encode(&[0u8; 65][..], &mut buf).is_err());
+
+    let mut rx = BytesMut::from(&buf[..6]);
+    assert!(codec.decode(&mut rx).unwrap().is_none());
+    rx.extend_from_slice(&buf[6..]);
+    assert_eq!(&codec.decode(&mut rx).unwrap().unwrap()[..], b"hello");
+    assert_eq!(&codec.decode(&mut rx).unwrap().unwrap()[..], b"world!");
+    assert!(codec.decode(&mut rx).unwrap().is_none());
+
+    let mut big = BytesMut::from(&1000u32.to_le_bytes()[..]);
+    assert!(codec.decode(&mut big).is_err());
+}
@@ -0,0 +1,123 @@
+use hardlock_snc::ratchet::state::Header;
+use hardlock_snc::wire::version::{
+    decode_message, encode_message, is_supported, transcode, FrameHeader, LAYOUTS, WIRE_CURRENT,
+    WIRE_V1_1, WIRE_V1_2,
+};
+use hardlock_snc::wire::{pack_message, pack_message_he, unpack_message, WireError};
+
+fn header() -> Header {
+    Header {
+        dh_pub: [7u8; 32],
+        pn: 3,
+        n: 9,
+    }
+}
+
+#[test]
+fn unknown_versions_are_rejected() {
+    let mut frame = pack_message(WIRE_V1_1, &header(), &[5u8; 24], b"ct").unwrap();
+    frame[0..2].copy_from_slice(&0x0200u16.to_le_bytes());
+    assert_eq!(
+        decode_message(&frame, false).unwrap_err(),
+        WireError::BadVersion(0x0200)
+    );
+    assert_eq!(
+        unpack_message(&frame).unwrap_err(),
+        WireError::BadVersion(0x0200)
+    );
+    assert!(encode_message(0x0200, &FrameHeader::Clear(header()), &[0u8; 24], b"").is_err());
+    assert!(!is_supported(0x0200));
+    assert!(LAYOUTS.iter().all(|l| is_supported(l.version)));
+}
+
+#[test]
+fn v1_1_frames_decode_alongside_current() {
+    let nonce = [5u8; 24];
+    let old = pack_message(WIRE_V1_1, &header(), &nonce, b"hello").unwrap();
+    let m = decode_message(&old, false).unwrap();
+    assert_eq!(m.version, WIRE_V1_1);
+    assert!(matches!(m.header, FrameHeader::Clear(ref h) if h.n == 9 && h.pn == 3));
+    assert_eq!(m.ct, b"hello");
+
+    let new = transcode(&old, false, WIRE_CURRENT).unwrap();
+    assert_eq!(WIRE_CURRENT, WIRE_V1_2);
+    let m2 = decode_message(&new, false).unwrap();
+    assert_eq!(m2.version, WIRE_V1_2);
+    assert_eq!((m2.nonce, m2.ct), (nonce, b"hello".to_vec()));
+    // Un décodeur v1.1 strict refuse la nouvelle disposition au lieu de la mal lire.
+    assert_eq!(
+        unpack_message(&new).unwrap_err(),
+        WireError::BadVersion(WIRE_V1_2)
+    );
+}
+
+#[test]
+fn v1_2_flags_encrypted_header() {
+    let eh = [9u8; 80];
+    let frame = encode_message(WIRE_V1_2, &FrameHeader::Encrypted(eh), &[1u8; 24], b"x").unwrap();
+    let m = decode_message(&frame, true).unwrap();
+    assert!(matches!(m.header, FrameHeader::Encrypted(e) if e == eh));
+    assert_eq!(
+        decode_message(&frame, false).unwrap_err(),
+        WireError::Malformed("header encryption mode mismatch")
+    );
+    assert!(matches!(
+        encode_message(WIRE_V1_1, &FrameHeader::Encrypted(eh), &[1u8; 24], b"x"),
+        Err(WireError::Malformed(_))
+    ));
+
+    let mut bad = frame.clone();
+    bad[2] |= 0x80;
+    assert!(matches!(
+        decode_message(&bad, true),
+        Err(WireError::Malformed(_))
+    ));
+
+    // v1.2 : flags (+1 o), plus de `pad_len` final (-4 o).
+    let he_v11 = pack_message_he(WIRE_V1_1, &eh, &[1u8; 24], b"x").unwrap();
+    assert_eq!(he_v11.len() - 3, frame.len());
+    let mut trailing = frame.clone();
+    trailing.push(0);
+    assert!(matches!(
+        decode_message(&trailing, true),
+        Err(WireError::Malformed(_))
+    ));
+}
+
+#[test]
+fn legacy_outer_padding_is_skipped() {
+    // Ancien émetteur v1.1 : pad_len = 8 et 8 zéros après ct.
+    let mut old = pack_message(WIRE_V1_1, &header(), &[5u8; 24], b"hello").unwrap();
+    let n = old.len();
+    old[n - 4..].copy_from_slice(&8u32.to_le_bytes());
+    old.extend_from_slice(&[0u8; 8]);
+    assert_eq!(decode_message(&old, false).unwrap().ct, b"hello");
+    assert_eq!(unpack_message(&old).unwrap().3, b"hello");
+    assert_eq!(
+        unpack_message(&old[..old.len() - 1]).unwrap_err(),
+        WireError::TruncatedCt
+    );
+}
+
+#[test]
+fn v1_1_header_mode_is_stated_by_caller() {
+    let eh = [9u8; 80];
+    let he = pack_message_he(WIRE_V1_1, &eh, &[1u8; 24], b"x").unwrap();
+    let m = decode_message(&he, true).unwrap();
+    assert!(matches!(m.header, FrameHeader::Encrypted(e) if e == eh));
+    assert_eq!(m.ct, b"x");
+    let v12 = transcode(&he, true, WIRE_V1_2).unwrap();
+    assert!(matches!(
+        decode_message(&v12, true).unwrap().header,
+        FrameHeader::Encrypted(e) if e == eh
+    ));
+
+    assert_eq!(
+        pack_message(WIRE_V1_2, &header(), &[5u8; 24], b"ct").unwrap_err(),
+        WireError::BadVersion(WIRE_V1_2)
+    );
+    assert_eq!(
+        pack_message_he(WIRE_V1_2, &eh, &[1u8; 24], b"x").unwrap_err(),
+        WireError::BadVersion(WIRE_V1_2)
+    );
+}
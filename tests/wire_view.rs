@@ -0,0 +1,105 @@
+use hardlock_snc::envelope::transport::{
+    decode_transport_header, decode_transport_header_ref, encode_transport_header, TransportHeader,
+};
+use hardlock_snc::envelope::PadProfile;
+use hardlock_snc::ratchet::state::Header;
+use hardlock_snc::wire::handshake::{decode_init, decode_init_v2, encode_init, encode_init_v2};
+use hardlock_snc::wire::stream::{FrameReader, FrameWriter};
+use hardlock_snc::wire::version::{encode_message, FrameHeader, WIRE_V1_2};
+use hardlock_snc::wire::{
+    pack_message, pack_message_he, unpack_message, FrameHeaderRef, InitFrame, MessageFrame,
+    WireError,
+};
+use std::io::Cursor;
+
+fn inside(buf: &[u8], part: &[u8]) -> bool {
+    let r = buf.as_ptr_range();
+    part.is_empty() || (r.contains(&part.as_ptr()) && part.as_ptr_range().end <= r.end)
+}
+
+#[test]
+fn message_views_borrow_the_input() {
+    let h = Header {
+        dh_pub: [7u8; 32],
+        pn: 1,
+        n: 2,
+    };
+    let frame = pack_message(0x0110, &h, &[5u8; 24], b"ciphertext").unwrap();
+    let v = MessageFrame::parse(&frame, false).unwrap();
+    assert!(inside(&frame, v.ct) && inside(&frame, v.nonce));
+    assert!(matches!(v.header, FrameHeaderRef::Clear(ref hh) if hh.n == 2));
+    let (_, _, nonce, ct) = unpack_message(&frame).unwrap();
+    assert_eq!((&nonce, &ct[..]), (v.nonce, v.ct));
+
+    let eh = [3u8; 80];
+    let he = pack_message_he(0x0110, &eh, &[1u8; 24], b"x").unwrap();
+    let v = MessageFrame::parse_v1_1(&he, true).unwrap();
+    assert!(matches!(v.header, FrameHeaderRef::Encrypted(e) if inside(&he, e)));
+
+    let v12 = encode_message(WIRE_V1_2, &FrameHeader::Encrypted(eh), &[1u8; 24], b"x").unwrap();
+    let owned = MessageFrame::parse(&v12, true).unwrap().into_owned();
+    assert_eq!(owned.ct, b"x");
+
+    assert_eq!(
+        MessageFrame::parse_v1_1(&v12, true).unwrap_err(),
+        WireError::BadVersion(WIRE_V1_2)
+    );
+    assert_eq!(
+        MessageFrame::parse(&frame[..frame.len() - 1], false).unwrap_err(),
+        WireError::TruncatedCt
+    );
+}
+
+#[test]
+fn init_views_match_owned_decoders() {
+    let enc = [9u8; 32];
+    let f = encode_init_v2(0x01, &enc, &[4u8; 32]);
+    let v = InitFrame::parse(&f).unwrap();
+    assert!(inside(&f, v.enc) && inside(&f, v.binder));
+    assert_eq!(
+        decode_init_v2(&f).unwrap(),
+        (v.suite, v.enc.to_vec(), *v.binder)
+    );
+    assert!(matches!(
+        InitFrame::parse(&f[..f.len() - 1]),
+        Err(WireError::Malformed(_))
+    ));
+
+    let f1 = encode_init(&enc);
+    let e = InitFrame::parse_v1_enc(&f1).unwrap();
+    assert!(inside(&f1, e));
+    assert_eq!(decode_init(&f1).unwrap(), e);
+    // Longueur annoncée énorme : pas de débordement arithmétique.
+    let mut huge = f1.clone();
+    huge[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(InitFrame::parse_v1_enc(&huge).is_err());
+}
+
+#[test]
+fn transport_and_stream_views() {
+    let h = TransportHeader {
+        suite: 0x01,
+        ts_unix_s: 7,
+        nonce: [6u8; 24],
+        token_len: 3,
+        profile: PadProfile::Padme,
+    };
+    let buf = encode_transport_header(&h, b"tok");
+    let (h2, tok) = decode_transport_header_ref(&buf).unwrap();
+    assert!(inside(&buf, tok));
+    assert_eq!((h2, tok.to_vec()), decode_transport_header(&buf).unwrap());
+    assert!(decode_transport_header_ref(&buf[..buf.len() - 1]).is_none());
+
+    let hdr = Header {
+        dh_pub: [1u8; 32],
+        pn: 0,
+        n: 5,
+    };
+    let mut w = FrameWriter::new(Vec::new());
+    w.write_frame(&pack_message(0x0110, &hdr, &[2u8; 24], b"abc").unwrap())
+        .unwrap();
+    let mut r = FrameReader::new(Cursor::new(w.into_inner()));
+    let m = r.next_message_ref(false).unwrap().unwrap();
+    assert_eq!(m.ct, b"abc");
+    assert!(r.next_message_ref(false).unwrap().is_none());
+}